@@ -106,7 +106,7 @@ offer =
 
 codec = %Specter.RtpCodecCapability{mime_type: "video/H264"}
 {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
-:ok = Specter.PeerConnection.add_track(specter, pc, track)
+{:ok, _sender} = Specter.PeerConnection.add_track(specter, pc, track)
 {:ok, _rtp_sender} = Stopwatch.wait_for_rtp_sender(pc, track)
 
 :ok = Specter.PeerConnection.set_remote_description(specter, pc, offer)
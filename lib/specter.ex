@@ -125,31 +125,189 @@ defmodule Specter do
   @opaque registry_t() :: String.t()
 
   @typedoc """
-  A uri in the form `protocol:host:port`, where protocol is either
-  `stun` or `turn`.
+  Options for creating a media engine with `new_media_engine/2`.
+  """
+  @type media_engine_options_t() ::
+          [] | [default_codecs: boolean(), vp9: boolean(), av1: boolean()]
+
+  @typedoc """
+  Options for selecting the interceptors added to a registry by `new_registry/3`.
+  """
+  @type registry_options_t() ::
+          []
+          | [
+              default_interceptors: boolean(),
+              audio_level: boolean(),
+              nack: boolean(),
+              pli: boolean(),
+              rtcp_reports: boolean(),
+              twcc: boolean()
+            ]
+
+  @typedoc """
+  An RTP payload type, between 0 and 127. Dynamic payload types are 96 and above.
+  """
+  @type payload_type_t() :: 0..127
+
+  @typedoc """
+  A codec registered on a media engine, as returned by `media_engine_codecs/2`.
+  """
+  @type codec_info_t() :: %{
+          mime_type: String.t(),
+          payload_type: payload_type_t(),
+          clock_rate: non_neg_integer(),
+          channels: non_neg_integer()
+        }
+
+  @typedoc """
+  A uri in the form `protocol:host:port`, where protocol is one of `stun`,
+  `stuns`, `turn` or `turns`. `Specter.init/1` returns
+  `{:error, :invalid_configuration}` for any other protocol. Each uri is given to
+  peer connections as a separate ICE server.
 
   Defaults to `stun:stun.l.google.com:19302`.
   """
   @type ice_server() :: String.t()
 
+  @typedoc """
+  The form in which gathered ICE candidates are sent in `{:ice_candidate, pc, candidate}`
+  messages: either a JSON string, or a `t:Specter.IceCandidate.t/0`.
+  """
+  @type ice_candidate_format() :: :json | :struct
+
+  @typedoc """
+  Whether ICE resolves and gathers mDNS `.local` candidates. See `init/1`.
+  """
+  @type ice_multicast_dns_mode() :: :disabled | :query_only | :query_and_gather
+
+  @typedoc """
+  A type of network on which ICE gathers local candidates. See `init/1`.
+  """
+  @type ice_network_type() :: :udp4 | :udp6 | :tcp4 | :tcp6
+
+  @typedoc """
+  The type of candidate that advertises the public addresses of a 1:1 NAT. See `init/1`.
+  """
+  @type nat_1to1_candidate_type() :: :host | :srflx
+
   @typedoc """
   Options for initializing RTCPeerConnections. This is set during initialization
   of the library, and later used when creating new connections.
   """
-  @type init_options() :: [] | [ice_servers: [ice_server()]]
+  @type init_options() ::
+          []
+          | [
+              ice_servers: [ice_server()],
+              ice_candidate_format: ice_candidate_format(),
+              certificate: String.t(),
+              private_key: String.t(),
+              command_buffer_size: pos_integer(),
+              ice_candidate_pool_size: 0..255,
+              udp_port_range: {1..65_535, 1..65_535},
+              ice_multicast_dns_mode: ice_multicast_dns_mode(),
+              ice_network_types: [ice_network_type()],
+              ice_interfaces: [String.t()],
+              ice_disconnected_timeout: pos_integer(),
+              ice_failed_timeout: pos_integer(),
+              ice_keepalive_interval: pos_integer(),
+              nat_1to1_ips: [String.t()],
+              nat_1to1_candidate_type: nat_1to1_candidate_type()
+            ]
 
   @doc """
   Initialize the library. This registers the calling process to receive
   callback messages to `handle_info/2`.
 
-  | param         | type               | default |
-  | ------------- | ------------------ | ------- |
-  | `ice_servers` | `list(String.t())` | `["stun:stun.l.google.com:19302"]` |
+  | param                      | type                        | default |
+  | -------------------------- | --------------------------- | ------- |
+  | `ice_servers`              | `list(String.t())`          | `["stun:stun.l.google.com:19302"]` |
+  | `ice_candidate_format`     | `ice_candidate_format()`    | `:json` |
+  | `certificate`              | `String.t()`                | |
+  | `private_key`              | `String.t()`                | |
+  | `command_buffer_size`      | `pos_integer()`             | 1000 |
+  | `ice_candidate_pool_size`  | `0..255`                    | 0 |
+  | `udp_port_range`           | `{min, max}`                | |
+  | `ice_multicast_dns_mode`   | `ice_multicast_dns_mode()`  | `:query_only` |
+  | `ice_network_types`        | `[ice_network_type()]`      | |
+  | `ice_interfaces`           | `list(String.t())`          | |
+  | `ice_disconnected_timeout` | `pos_integer()`             | |
+  | `ice_failed_timeout`       | `pos_integer()`             | |
+  | `ice_keepalive_interval`   | `pos_integer()`             | |
+  | `nat_1to1_ips`             | `list(String.t())`          | |
+  | `nat_1to1_candidate_type`  | `nat_1to1_candidate_type()` | `:host` |
+
+  With `ice_candidate_format: :struct`, gathered ICE candidates are sent as
+  `t:Specter.IceCandidate.t/0` structs, which may be given directly to
+  `Specter.PeerConnection.add_ice_candidate/3`.
+
+  By default, webrtc.rs generates a new DTLS certificate for every peer connection.
+  Given a PEM `certificate` chain, leaf first, and its PEM `private_key`, every peer
+  connection instead uses that certificate, so that its fingerprint is stable across
+  restarts. Returns `{:error, :invalid_certificate}` or `{:error, :invalid_private_key}`
+  when either cannot be parsed, or when only one of them is given.
+
+  Calls on a peer connection are queued as commands for the task that owns it, and
+  `command_buffer_size` bounds how many may be queued at once. Once the queue is
+  full, asynchronous calls return `{:error, :busy}` instead of piling up, and the
+  caller may retry once the peer connection has caught up. Calls that block for a
+  reply wait for room instead. A smaller buffer applies backpressure sooner and
+  holds less memory per peer connection, while a larger one absorbs bursts of
+  commands, such as candidates or stats polling, at the cost of commands waiting
+  longer behind one another.
+
+  `ice_candidate_pool_size` is given to every peer connection as the size of its pool
+  of prefetched ICE candidates, which are meant to be gathered before a local
+  description is set so that connections created on demand are set up sooner. It is
+  reported back by `Specter.PeerConnection.get_configuration/2`. Returns
+  `{:error, :invalid_configuration}` for sizes that are not integers from 0 to 255.
+
+  By default, ICE listens on any UDP port the OS hands out. Given `udp_port_range`,
+  peer connections of APIs created from this Specter only listen on ports from `min`
+  to `max`, inclusive, so that a firewall may let through just that range. Each peer
+  connection uses a port per local address, so the range should leave room for the
+  expected number of connections. Returns `{:error, :invalid_udp_port_range}` when
+  `min` is 0 or greater than `max`.
+
+  `ice_multicast_dns_mode` controls mDNS candidates, as used by browsers to avoid
+  revealing local IP addresses. With `:query_only`, ICE resolves the `.local`
+  candidates of remote peers. With `:query_and_gather`, it also replaces the addresses
+  of its own host candidates with random `.local` names, and answers queries for them.
+  `:disabled` neither resolves nor gathers mDNS candidates, so remote `.local`
+  candidates are ignored.
+
+  `ice_network_types` limits the networks on which ICE gathers candidates, such as
+  `[:udp4]` to leave out IPv6. By default, webrtc.rs gathers on `:udp4` and `:udp6`.
+  `ice_interfaces` limits host candidates to the named network interfaces, for
+  instance to leave out a docker bridge. Returns `{:error, :invalid_configuration}`
+  when either is empty.
+
+  `ice_disconnected_timeout`, `ice_failed_timeout` and `ice_keepalive_interval` are
+  given in milliseconds, and tune how quickly ICE gives up on a connection that has
+  gone quiet. ICE reports `:disconnected` once nothing has been received for
+  `ice_disconnected_timeout`, and `:failed` once nothing has been received for a
+  further `ice_failed_timeout`. While no media flows, it sends keepalives every
+  `ice_keepalive_interval`, which should be well under the disconnected timeout.
+  Those not given are left to webrtc.rs, which waits 5 seconds, then 25 seconds, and
+  sends keepalives every 2 seconds. Returns `{:error, :invalid_configuration}` for
+  values that are not positive integers.
+
+  A server behind a 1:1 NAT, such as a cloud instance with a static public IP, only
+  knows its private addresses, which remote peers cannot reach. Given its public
+  `nat_1to1_ips`, ICE advertises those instead. With `nat_1to1_candidate_type: :host`,
+  they replace the private addresses of host candidates. With `:srflx`, host candidates
+  are kept, and a server reflexive candidate is added for each public address. Returns
+  `{:error, :invalid_configuration}` when the list is empty or holds something other
+  than an IP address, when `:host` is combined with
+  `ice_multicast_dns_mode: :query_and_gather`, or when `:srflx` is combined with STUN
+  servers, since webrtc.rs cannot honor either combination.
 
   ## Usage
 
       iex> {:ok, _specter} = Specter.init(ice_servers: ["stun:stun.example.com:3478"])
 
+      iex> {:ok, specter} = Specter.init(ice_candidate_format: :struct)
+      iex> {:ok, %Specter.Config{ice_candidate_format: :struct}} = Specter.config(specter)
+
   """
   @spec init() :: {:ok, t()}
   @spec init(init_options()) :: {:ok, t()} | {:error, term()}
@@ -172,6 +330,183 @@ defmodule Specter do
   @spec config(t()) :: {:ok, Specter.Config.t()} | {:error, term()}
   def config(%Specter{native: ref}), do: Native.config(ref)
 
+  @doc """
+  Frees a local track, either a `Specter.TrackLocalStaticSample` or a
+  `Specter.TrackLocalStaticRTP`. Tracks are otherwise kept for as long as the Specter
+  instance lives.
+
+  A playback on the track is stopped, and sends `{:playback_finished, track}`. Peer
+  connections the track was added to keep sending whatever it was sending, but the
+  track can no longer be written to or added to a peer connection. Returns
+  `{:error, :not_found}` when the track does not exist.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      iex> {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      iex> Specter.delete_track(specter, track)
+      :ok
+      iex> Specter.list_tracks(specter)
+      {:ok, []}
+
+  """
+  @spec delete_track(t(), Specter.TrackLocalStaticSample.t() | Specter.TrackLocalStaticRTP.t()) ::
+          :ok | {:error, term()}
+  def delete_track(%Specter{native: ref}, track), do: Native.delete_track(ref, track)
+
+  @doc """
+  Frees an API created by `new_api/3`. APIs are otherwise kept for as long as the
+  Specter instance lives.
+
+  Returns `{:error, :in_use}` while any peer connection created from the API has
+  not been closed, including peer connections that are still being created, and
+  `{:error, :not_found}` when the API does not exist.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> Specter.destroy_api(specter, api)
+      :ok
+      iex> Specter.list_apis(specter)
+      {:ok, []}
+
+  """
+  @spec destroy_api(t(), api_t()) :: :ok | {:error, term()}
+  def destroy_api(%Specter{native: ref}, api), do: Native.destroy_api(ref, api)
+
+  @doc """
+  Returns the UUIDs of all APIs created by `new_api/3`, sorted. APIs live as long
+  as the Specter instance, so this grows with every call to `new_api/3`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> Specter.list_apis(specter)
+      {:ok, []}
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, [^api]} = Specter.list_apis(specter)
+
+  """
+  @spec list_apis(t()) :: {:ok, [api_t()]} | {:error, term()}
+  def list_apis(%Specter{native: ref}), do: Native.list_apis(ref)
+
+  @doc """
+  Returns the UUIDs of all media engines, sorted. As with `media_engine_exists?/2`,
+  media engines consumed by `new_api/3` are not included.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, [^media_engine]} = Specter.list_media_engines(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, _api} = Specter.new_api(specter, media_engine, registry)
+      iex> Specter.list_media_engines(specter)
+      {:ok, []}
+
+  """
+  @spec list_media_engines(t()) :: {:ok, [media_engine_t()]} | {:error, term()}
+  def list_media_engines(%Specter{native: ref}), do: Native.list_media_engines(ref)
+
+  @doc """
+  Returns the UUIDs of all registries, sorted. As with `registry_exists?/2`, registries
+  consumed by `new_api/3` are not included.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, [^registry]} = Specter.list_registries(specter)
+      iex> {:ok, _api} = Specter.new_api(specter, media_engine, registry)
+      iex> Specter.list_registries(specter)
+      {:ok, []}
+
+  """
+  @spec list_registries(t()) :: {:ok, [registry_t()]} | {:error, term()}
+  def list_registries(%Specter{native: ref}), do: Native.list_registries(ref)
+
+  @doc """
+  Returns the UUIDs of all local tracks, both `Specter.TrackLocalStaticSample` and
+  `Specter.TrackLocalStaticRTP`, sorted.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> Specter.list_tracks(specter)
+      {:ok, []}
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      iex> {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      iex> {:ok, [^track]} = Specter.list_tracks(specter)
+
+  """
+  @spec list_tracks(t()) ::
+          {:ok, [Specter.TrackLocalStaticSample.t() | Specter.TrackLocalStaticRTP.t()]}
+          | {:error, term()}
+  def list_tracks(%Specter{native: ref}), do: Native.list_tracks(ref)
+
+  @doc """
+  Creates a media engine with the same codecs as the given one, so that one codec
+  configuration can seed several APIs without registering codecs again. The original
+  is left untouched, and both may be configured further independently.
+
+  Only codecs are copied. The RTCP feedback and header extensions that `new_registry/3`
+  registers on a media engine for its interceptors are not, since each copy is given
+  its own registry.
+
+  Returns `{:error, :not_found}` when the media engine does not exist, or has been
+  consumed by `new_api/3`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter, default_codecs: false)
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "video/VP8", clock_rate: 90_000}
+      iex> :ok = Specter.register_codec(specter, media_engine, codec, 96, :video)
+      ...>
+      iex> {:ok, copy} = Specter.clone_media_engine(specter, media_engine)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, _api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, registry} = Specter.new_registry(specter, copy)
+      iex> {:ok, _api} = Specter.new_api(specter, copy, registry)
+
+  """
+  @spec clone_media_engine(t(), media_engine_t()) ::
+          {:ok, media_engine_t()} | {:error, term()}
+  def clone_media_engine(%Specter{native: ref}, media_engine),
+    do: Native.clone_media_engine(ref, media_engine)
+
+  @doc """
+  Lists the audio and video codecs registered on a media engine, which is useful
+  when debugging codec negotiation. Returns `{:error, :not_found}` once the media
+  engine has been consumed by `new_api/3`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter, default_codecs: false)
+      iex> codec =
+      ...>   %Specter.RtpCodecCapability{mime_type: "audio/opus", clock_rate: 48_000, channels: 2}
+      iex> :ok = Specter.register_codec(specter, media_engine, codec, 111, :audio)
+      iex> Specter.media_engine_codecs(specter, media_engine)
+      {:ok,
+       %{
+         audio: [%{mime_type: "audio/opus", payload_type: 111, clock_rate: 48_000, channels: 2}],
+         video: []
+       }}
+
+  """
+  @spec media_engine_codecs(t(), media_engine_t()) ::
+          {:ok, %{audio: [codec_info_t()], video: [codec_info_t()]}} | {:error, term()}
+  def media_engine_codecs(%Specter{native: ref}, media_engine),
+    do: Native.media_engine_codecs(ref, media_engine)
+
   @doc """
   Returns true or false, depending on whether the media engine is available for
   consumption, i.e. is initialized and has not been used by a function that takes
@@ -204,8 +539,13 @@ defmodule Specter do
   An APIBuilder is used to create RTCPeerConnections. This accepts as parameters
   the output of `init/1`, `new_media_enine/1`, and `new_registry/2`.
 
+  The API uses the `udp_port_range` given to `init/1`, if any.
+
   Note that this takes ownership of both the media engine and the registry,
-  effectively consuming them.
+  effectively consuming them. Afterwards neither exists, and neither can be given to
+  another call of `new_api/3` or `new_registry/3`. To build several APIs from one codec
+  configuration, copy the media engine with `clone_media_engine/2` before calling this,
+  and create a registry for each copy.
 
   | param          | type     | default |
   | -------------- | -------- | ------- |
@@ -225,20 +565,81 @@ defmodule Specter do
   def new_api(%Specter{native: ref}, media_engine, registry),
     do: Native.new_api(ref, media_engine, registry)
 
+  @doc """
+  Creates an API with the default codecs and interceptors, without going through a
+  media engine and registry. This is the same as calling `new_media_engine/2`,
+  `new_registry/3` and `new_api/3` without options, but there is no media engine or
+  registry left to be consumed by mistake.
+
+  Use the separate functions to register custom codecs or choose interceptors.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, api} = Specter.new_api_with_defaults(specter)
+      iex> {:ok, _pc} = Specter.PeerConnection.new(specter, api)
+
+  """
+  @spec new_api_with_defaults(t()) :: {:ok, api_t()} | {:error, term()}
+  def new_api_with_defaults(%Specter{native: ref}), do: Native.new_api_with_defaults(ref)
+
   @doc """
   Creates a MediaEngine to be configured and used by later function calls.
   Codecs and other high level configuration are done on instances of MediaEngines.
   A MediaEngine is combined with a Registry in an entity called an APIBuilder,
   which is then used to create RTCPeerConnections.
 
+  By default the media engine is configured with the default codecs of webrtc.rs.
+  With `default_codecs: false` it starts out empty, so that only codecs added with
+  `register_codec/5` are negotiated.
+
+  Since some peers misbehave when offered VP9 or AV1, these may be left out of the
+  default codecs. `vp9` and `av1` are ignored without default codecs.
+
+  | option           | type        | default |
+  | ---------------- | ----------- | ------- |
+  | `default_codecs` | `boolean()` | true |
+  | `vp9`            | `boolean()` | true |
+  | `av1`            | `boolean()` | true |
+
+  The default codecs use these payload types:
+
+  | codec      | payload types |
+  | ---------- | ------------- |
+  | Opus       | 111 |
+  | G722       | 9 |
+  | PCMU       | 0 |
+  | PCMA       | 8 |
+  | VP8        | 96 |
+  | VP9        | 98 (profile 0), 100 (profile 1) |
+  | H264       | 102, 108, 123, 125, 127 |
+  | AV1        | 41 |
+  | ulpfec     | 116 |
+
   ## Usage
 
       iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
       iex> {:ok, _media_engine} = Specter.new_media_engine(specter)
 
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter, default_codecs: false)
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "video/VP8", clock_rate: 90_000}
+      iex> :ok = Specter.register_codec(specter, media_engine, codec, 96, :video)
+
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, _media_engine} = Specter.new_media_engine(specter, vp9: false, av1: false)
+
   """
-  @spec new_media_engine(t()) :: {:ok, media_engine_t()} | {:error, term()}
-  def new_media_engine(%Specter{native: ref}), do: Native.new_media_engine(ref)
+  @spec new_media_engine(t(), media_engine_options_t()) ::
+          {:ok, media_engine_t()} | {:error, term()}
+  def new_media_engine(%Specter{native: ref}, opts \\ []) do
+    Native.new_media_engine(
+      ref,
+      Keyword.get(opts, :default_codecs, true),
+      Keyword.get(opts, :vp9, true),
+      Keyword.get(opts, :av1, true)
+    )
+  end
 
   @doc """
   Creates an intercepter registry. This is a user configurable RTP/RTCP pipeline,
@@ -250,6 +651,45 @@ defmodule Specter do
 
   Note that creating a registry does **not** take ownership of the media engine.
 
+  ## Options
+
+  Without options, the default interceptors are registered. Given any options, the
+  registry is instead built from the interceptors that are enabled, where options
+  that are not given default to the value of `default_interceptors`.
+
+  | option                 | type        | default |
+  | ---------------------- | ----------- | ------- |
+  | `default_interceptors` | `boolean()` | true |
+  | `audio_level`          | `boolean()` | false |
+  | `nack`                 | `boolean()` | `default_interceptors` |
+  | `pli`                  | `boolean()` | `default_interceptors` |
+  | `rtcp_reports`         | `boolean()` | `default_interceptors` |
+  | `twcc`                 | `boolean()` | `default_interceptors` |
+
+  With only `default_interceptors: false` the registry is bare, which is useful when
+  measuring raw RTP behaviour. Peer connections created from it still negotiate and
+  carry media, but lose NACK retransmission, sender and receiver reports, and TWCC
+  feedback, and do not advertise `nack`, `nack pli` or `transport-cc` feedback.
+
+  Each interceptor only works when the remote peer negotiates the matching RTCP
+  feedback or RTP header extension, which is registered on the media engine when the
+  interceptor is enabled:
+
+  - `nack` registers `nack` feedback for video codecs, and retransmits or requests
+    lost packets.
+  - `pli` registers `nack pli` feedback for video codecs. No interceptor is added, as
+    keyframe requests are sent by the application.
+  - `rtcp_reports` needs no registration, and sends sender and receiver reports.
+  - `twcc` registers `transport-cc` feedback and the transport-wide CC header
+    extension, and generates TWCC feedback for received packets.
+
+  `audio_level` is not an interceptor, and is only enabled when given. It registers the
+  `urn:ietf:params:rtp-hdrext:ssrc-audio-level` header extension for audio. Remote audio
+  tracks whose sender includes it report their levels as
+  `t:Specter.PeerConnection.audio_level_msg_t/0`, for instance to show who is speaking.
+
+  Returns `{:error, :invalid_registry_options}` when an option is not a boolean.
+
   ## Usage
 
       iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
@@ -259,10 +699,63 @@ defmodule Specter do
       iex> Specter.media_engine_exists?(specter, media_engine)
       true
 
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, _registry} = Specter.new_registry(specter, media_engine, nack: false)
+
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, _registry} =
+      ...>   Specter.new_registry(specter, media_engine, default_interceptors: false)
+
+  """
+  @spec new_registry(t(), media_engine_t(), registry_options_t()) ::
+          {:ok, registry_t()} | {:error, term()}
+  def new_registry(%Specter{native: ref}, media_engine, opts \\ []),
+    do: Native.new_registry(ref, media_engine, Enum.into(opts, %{}))
+
+  @doc """
+  Registers a codec on a media engine, to be negotiated with the given payload type.
+  The `kind` of the codec is either `:audio` or `:video`. Codecs must be registered
+  before the media engine is consumed by `new_api/3`.
+
+  | param          | type                           | default |
+  | -------------- | ------------------------------ | ------- |
+  | `specter`      | `t()`                          | |
+  | `media_engine` | `opaque`                       | |
+  | `codec`        | `Specter.RtpCodecCapability.t()` | |
+  | `payload_type` | `t:payload_type_t/0`           | |
+  | `kind`         | `:audio`, `:video`             | |
+
+  The mime type of the codec must be one of `Specter.RtpCodecCapability.mime_types/0`.
+  It is matched regardless of case, and registered in the case webrtc.rs expects.
+
+  Returns `{:error, :not_found}` when the media engine does not exist or has already
+  been consumed, `{:error, :invalid_atom}` for any other kind,
+  `{:error, :invalid_payload_type}` when the payload type is out of range, and
+  `{:error, :unknown_mime_type}` for a mime type that webrtc.rs does not know.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> codec = %{Specter.RtpCodecCapability.h264() | sdp_fmtp_line: "packetization-mode=1"}
+      iex> Specter.register_codec(specter, media_engine, codec, 125, :video)
+      :ok
+      iex> codec = %{codec | mime_type: "video/h.264"}
+      iex> Specter.register_codec(specter, media_engine, codec, 125, :video)
+      {:error, :unknown_mime_type}
+
   """
-  @spec new_registry(t(), media_engine_t()) :: {:ok, registry_t()} | {:error, term()}
-  def new_registry(%Specter{native: ref}, media_engine),
-    do: Native.new_registry(ref, media_engine)
+  @spec register_codec(
+          t(),
+          media_engine_t(),
+          Specter.RtpCodecCapability.t(),
+          payload_type_t(),
+          :audio | :video
+        ) :: :ok | {:error, term()}
+  def register_codec(%Specter{native: ref}, media_engine, codec, payload_type, kind),
+    do: Native.register_codec(ref, media_engine, codec, payload_type, kind)
 
   @doc """
   Returns true or false, depending on whether the registry is available for
@@ -1,6 +1,20 @@
 defmodule Specter.RtpCodecCapability do
   @moduledoc """
   A representation of webrtc.rs RTCRtpCodecCapability.
+
+  The mime type must be one that webrtc.rs knows, as listed by `mime_types/0`.
+  Functions named after codecs return capabilities with the clock rate and channels
+  webrtc.rs uses by default, to be completed with an fmtp line where one is needed.
+
+      iex> Specter.RtpCodecCapability.opus()
+      %Specter.RtpCodecCapability{mime_type: "audio/opus", clock_rate: 48_000, channels: 2}
+      iex> %{Specter.RtpCodecCapability.h264() | sdp_fmtp_line: "packetization-mode=1"}
+      %Specter.RtpCodecCapability{
+        mime_type: "video/H264",
+        clock_rate: 90_000,
+        sdp_fmtp_line: "packetization-mode=1"
+      }
+
   """
 
   @typedoc """
@@ -21,4 +35,62 @@ defmodule Specter.RtpCodecCapability do
             clock_rate: 0,
             channels: 0,
             sdp_fmtp_line: ""
+
+  @mime_types [
+    "audio/opus",
+    "audio/G722",
+    "audio/PCMU",
+    "audio/PCMA",
+    "audio/telephone-event",
+    "video/VP8",
+    "video/VP9",
+    "video/H264",
+    "video/H265",
+    "video/AV1",
+    "video/rtx",
+    "video/ulpfec"
+  ]
+
+  @doc """
+  The mime types known to webrtc.rs, in the case it expects. Codecs with other mime
+  types are rejected with `{:error, :unknown_mime_type}`.
+  """
+  @spec mime_types() :: [String.t()]
+  def mime_types, do: @mime_types
+
+  @doc "Opus audio, at 48kHz in stereo."
+  @spec opus() :: t()
+  def opus, do: %__MODULE__{mime_type: "audio/opus", clock_rate: 48_000, channels: 2}
+
+  @doc "G722 audio. Its clock rate is 8kHz, as given in SDP, although it samples at 16kHz."
+  @spec g722() :: t()
+  def g722, do: %__MODULE__{mime_type: "audio/G722", clock_rate: 8_000}
+
+  @doc "G711 μ-law audio, at 8kHz."
+  @spec pcmu() :: t()
+  def pcmu, do: %__MODULE__{mime_type: "audio/PCMU", clock_rate: 8_000}
+
+  @doc "G711 A-law audio, at 8kHz."
+  @spec pcma() :: t()
+  def pcma, do: %__MODULE__{mime_type: "audio/PCMA", clock_rate: 8_000}
+
+  @doc "VP8 video."
+  @spec vp8() :: t()
+  def vp8, do: %__MODULE__{mime_type: "video/VP8", clock_rate: 90_000}
+
+  @doc "VP9 video."
+  @spec vp9() :: t()
+  def vp9, do: %__MODULE__{mime_type: "video/VP9", clock_rate: 90_000}
+
+  @doc "H264 video."
+  @spec h264() :: t()
+  def h264, do: %__MODULE__{mime_type: "video/H264", clock_rate: 90_000}
+
+  @doc "H265 video."
+  @spec h265() :: t()
+  def h265, do: %__MODULE__{mime_type: "video/H265", clock_rate: 90_000}
+
+  @doc "AV1 video."
+  @spec av1() :: t()
+  def av1, do: %__MODULE__{mime_type: "video/AV1", clock_rate: 90_000}
 end
@@ -4,15 +4,44 @@ defmodule Specter.Config do
   """
 
   defstruct [
-    :ice_servers
+    :ice_servers,
+    ice_candidate_format: :json,
+    certificate: nil,
+    command_buffer_size: 1000,
+    ice_candidate_pool_size: 0,
+    udp_port_range: nil,
+    ice_multicast_dns_mode: :query_only,
+    ice_network_types: nil,
+    ice_interfaces: nil,
+    ice_disconnected_timeout: nil,
+    ice_failed_timeout: nil,
+    ice_keepalive_interval: nil,
+    nat_1to1_ips: nil,
+    nat_1to1_candidate_type: :host
   ]
 
   @typedoc """
   A representation of the configuration kept by the initialized NIF.
   Note that this does not map 1:1 to actual webrtc.rs data structures, but
   is an Elixir data structure into which the NIF can encode its config.
+
+  When a DTLS certificate was given to `Specter.init/1`, `certificate` holds its
+  PEM. The private key is never returned.
   """
   @type t() :: %__MODULE__{
-          ice_servers: [Specter.ice_server()]
+          ice_servers: [Specter.ice_server()],
+          ice_candidate_format: Specter.ice_candidate_format(),
+          certificate: String.t() | nil,
+          command_buffer_size: pos_integer(),
+          ice_candidate_pool_size: 0..255,
+          udp_port_range: {1..65_535, 1..65_535} | nil,
+          ice_multicast_dns_mode: Specter.ice_multicast_dns_mode(),
+          ice_network_types: [Specter.ice_network_type()] | nil,
+          ice_interfaces: [String.t()] | nil,
+          ice_disconnected_timeout: pos_integer() | nil,
+          ice_failed_timeout: pos_integer() | nil,
+          ice_keepalive_interval: pos_integer() | nil,
+          nat_1to1_ips: [String.t()] | nil,
+          nat_1to1_candidate_type: Specter.nat_1to1_candidate_type()
         }
 end
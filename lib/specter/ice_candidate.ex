@@ -0,0 +1,20 @@
+defmodule Specter.IceCandidate do
+  @moduledoc """
+  A representation of webrtc.rs `RTCIceCandidateInit`.
+
+  `Specter.PeerConnection.add_ice_candidate/3` accepts candidates either as JSON
+  strings or as this struct, so that signaling layers which carry candidates as
+  maps do not need to encode them to JSON. When Specter is initialized with
+  `ice_candidate_format: :struct`, gathered candidates are sent as this struct.
+  """
+
+  @type t() :: %__MODULE__{
+          candidate: String.t(),
+          sdp_mid: String.t() | nil,
+          sdp_mline_index: non_neg_integer() | nil,
+          username_fragment: String.t() | nil
+        }
+
+  @enforce_keys [:candidate]
+  defstruct [:candidate, sdp_mid: nil, sdp_mline_index: nil, username_fragment: nil]
+end
@@ -18,7 +18,20 @@ defmodule Specter.Native do
   end
 
   @doc """
-  Asynchronously adds the candidate to the given RTCPeerConnection.
+  Adds a track as another simulcast encoding of an RTP sender. Blocks until the peer
+  connection replies.
+  """
+  @spec add_encoding(
+          t(),
+          peer_conn_t(),
+          String.t(),
+          Specter.TrackLocalStaticSample.t() | Specter.TrackLocalStaticRTP.t()
+        ) :: :ok | {:error, term()}
+  def add_encoding(_ref, _pc, _sender, _track), do: error()
+
+  @doc """
+  Asynchronously adds the candidate to the given RTCPeerConnection. The candidate
+  is either a JSON string or a `Specter.IceCandidate` struct.
   Sends back `{:ok, _uuid, :add_ice_candidate}` when successful.
   """
   @spec add_ice_candidate(t(), peer_conn_t(), Specter.PeerConnection.ice_candidate_t()) ::
@@ -26,32 +39,92 @@ defmodule Specter.Native do
   def add_ice_candidate(_ref, _pc, _candidate), do: error()
 
   @doc """
-  Adds track to peer connection.
-
-  Sends back newly created rtp sender UUID.
+  Adds track to peer connection, returning the UUID of the rtp sender it will be
+  sent by. The UUID refers to a sender once `rtp_sender` is sent back, and never when
+  the track is refused.
   """
-  @spec add_track(t(), peer_conn_t(), Specter.TrackLocalStaticSample.t()) ::
-          :ok | {:error, term()}
+  @spec add_track(
+          t(),
+          peer_conn_t(),
+          Specter.TrackLocalStaticSample.t() | Specter.TrackLocalStaticRTP.t()
+        ) :: {:ok, String.t()} | {:error, term()}
   def add_track(_ref, _pc, _track), do: error()
 
+  @doc """
+  Adds a transceiver with the given kind and direction to peer connection.
+
+  Sends back newly created transceiver UUID.
+  """
+  @spec add_transceiver(
+          t(),
+          peer_conn_t(),
+          Specter.PeerConnection.transceiver_kind_t(),
+          Specter.PeerConnection.transceiver_direction_t()
+        ) :: :ok | {:error, term()}
+  def add_transceiver(_ref, _pc, _kind, _direction), do: error()
+
+  @doc """
+  Returns whether the remote peer accepts trickled ICE candidates, or `nil` before a
+  remote description is set. Blocks until the peer connection replies.
+  """
+  @spec can_trickle_ice_candidates(t(), peer_conn_t()) ::
+          {:ok, boolean() | nil} | {:error, term()}
+  def can_trickle_ice_candidates(_ref, _pc), do: error()
+
+  @doc """
+  Stops sending REMB packets capping the remote peer's bitrate.
+  Sends back `{:ok, _uuid, :clear_receive_bitrate_cap}`.
+  """
+  @spec clear_receive_bitrate_cap(t(), peer_conn_t()) :: :ok | {:error, term()}
+  def clear_receive_bitrate_cap(_ref, _pc), do: error()
+
+  @doc """
+  Creates a MediaEngine with the same codecs as a MediaEngine that has not yet been
+  consumed by an API.
+  """
+  @spec clone_media_engine(t(), Specter.media_engine_t()) ::
+          {:ok, Specter.media_engine_t()} | {:error, term()}
+  def clone_media_engine(_ref, _media_engine), do: error()
+
   @doc """
   Closes an RTCPeerConnection represented by the given UUID.
   """
   @spec close_peer_connection(t(), peer_conn_t()) :: :ok | {:error, term()}
   def close_peer_connection(_ref, _pc), do: error()
 
+  @doc """
+  Closes an RTCPeerConnection represented by the given UUID, returning once it has
+  been closed.
+  """
+  @spec close_peer_connection_sync(t(), peer_conn_t()) :: :ok | {:error, term()}
+  def close_peer_connection_sync(_ref, _pc), do: error()
+
   @doc """
   Create an answer from an RTCPeerConnection that has been given a remote description.
   """
   @spec create_answer(t(), peer_conn_t(), boolean) :: :ok | {:error, term()}
   def create_answer(_ref, _pc, _vad), do: error()
 
+  @doc """
+  Create an answer, returning its JSON. Blocks until the peer connection replies.
+  """
+  @spec create_answer_sync(t(), peer_conn_t(), boolean) :: {:ok, String.t()} | {:error, term()}
+  def create_answer_sync(_ref, _pc, _vad), do: error()
+
   @doc """
   Add a data channel to an RTCPeerConnection.
   """
-  @spec create_data_channel(t(), peer_conn_t(), String.t()) ::
+  @spec create_data_channel(t(), peer_conn_t(), String.t(), map()) ::
           :ok | {:error, term()}
-  def create_data_channel(_ref, _pc, _label), do: error()
+  def create_data_channel(_ref, _pc, _label, _opts), do: error()
+
+  @doc """
+  Add a data channel to an RTCPeerConnection, returning the UUID that identifies it.
+  Blocks until the peer connection replies.
+  """
+  @spec create_data_channel_sync(t(), peer_conn_t(), String.t(), map()) ::
+          {:ok, String.t()} | {:error, term()}
+  def create_data_channel_sync(_ref, _pc, _label, _opts), do: error()
 
   @doc """
   Create an offer from an RTCPeerConnection.
@@ -59,6 +132,21 @@ defmodule Specter.Native do
   @spec create_offer(t(), peer_conn_t(), bool(), bool()) :: :ok | {:error, term()}
   def create_offer(_ref, _pc, _vad, _ice_restart), do: error()
 
+  @doc """
+  Create an offer when the signaling state is stable. Blocks until the peer connection
+  replies, but sends back the offer itself.
+  """
+  @spec create_offer_if_needed(t(), peer_conn_t(), bool(), bool()) ::
+          :ok | {:ok, :no_offer_needed} | {:error, term()}
+  def create_offer_if_needed(_ref, _pc, _vad, _ice_restart), do: error()
+
+  @doc """
+  Create an offer, returning its JSON. Blocks until the peer connection replies.
+  """
+  @spec create_offer_sync(t(), peer_conn_t(), bool(), bool()) ::
+          {:ok, String.t()} | {:error, term()}
+  def create_offer_sync(_ref, _pc, _vad, _ice_restart), do: error()
+
   @doc """
   Given an initialized NIF, get the current config back out into Elixir.
 
@@ -87,12 +175,104 @@ defmodule Specter.Native do
   @spec current_remote_description(t(), peer_conn_t()) :: :ok | {:error, term()}
   def current_remote_description(_ref, _pc), do: error()
 
+  @doc """
+  Returns the number of bytes queued to be sent on a data channel. Blocks until the
+  peer connection replies.
+  """
+  @spec data_channel_buffered_amount(t(), peer_conn_t(), String.t()) ::
+          {:ok, non_neg_integer()} | {:error, term()}
+  def data_channel_buffered_amount(_ref, _pc, _data_channel), do: error()
+
+  @doc """
+  Refuses new tracks and data channels on an RTCPeerConnection, then closes it once
+  the grace period has elapsed. Sends back `{:ok, _uuid, :drain_peer_connection}`.
+  """
+  @spec drain_peer_connection(t(), peer_conn_t(), non_neg_integer()) :: :ok | {:error, term()}
+  def drain_peer_connection(_ref, _pc, _grace_period_ms), do: error()
+
+  @doc """
+  Removes a local track of any type, stopping any playback on it.
+  """
+  @spec delete_track(t(), Specter.TrackLocalStaticSample.t() | Specter.TrackLocalStaticRTP.t()) ::
+          :ok | {:error, term()}
+  def delete_track(_ref, _track), do: error()
+
+  @doc """
+  Removes an API, unless a peer connection created from it is still open.
+  """
+  @spec destroy_api(t(), Specter.api_t()) :: :ok | {:error, term()}
+  def destroy_api(_ref, _api), do: error()
+
+  @doc """
+  Forwards the RTP packets of a remote track to local RTP tracks. Sends back
+  `{:ok, _uuid, :forward_track}`.
+  """
+  @spec forward_track(t(), peer_conn_t(), String.t(), [Specter.TrackLocalStaticRTP.t()]) ::
+          :ok | {:error, term()}
+  def forward_track(_ref, _pc, _track_id, _targets), do: error()
+
+  @doc """
+  Sends back `{:local_description, _uuid, _sdp}` once ICE gathering has finished.
+  """
+  @spec gathered_local_description(t(), peer_conn_t()) :: :ok | {:error, term()}
+  def gathered_local_description(_ref, _pc), do: error()
+
+  @doc """
+  Sends back `{:gathering_complete, _uuid}` once ICE gathering has finished.
+  """
+  @spec gathering_complete_promise(t(), peer_conn_t()) :: :ok | {:error, term()}
+  def gathering_complete_promise(_ref, _pc), do: error()
+
+  @doc """
+  Returns the configuration in effect on an RTCPeerConnection. Blocks until the peer
+  connection replies.
+  """
+  @spec get_peer_connection_configuration(t(), peer_conn_t()) ::
+          {:ok, Specter.PeerConnection.configuration_t()} | {:error, term()}
+  def get_peer_connection_configuration(_ref, _pc), do: error()
+
+  @doc """
+  Sends back `{:receivers, _uuid, receivers}` describing the RTP receiver of each
+  transceiver of a peer connection.
+  """
+  @spec get_receivers(t(), peer_conn_t()) :: :ok | {:error, term()}
+  def get_receivers(_ref, _pc), do: error()
+
+  @doc """
+  Sends back `{:selected_candidate_pair, _uuid, pair}` with the local and remote
+  candidates selected by ICE, or `nil` until a pair is selected.
+  """
+  @spec get_selected_candidate_pair(t(), peer_conn_t()) :: :ok | {:error, term()}
+  def get_selected_candidate_pair(_ref, _pc), do: error()
+
+  @doc """
+  Sends back `{:senders, _uuid, senders}` describing each RTP sender of a peer connection.
+  """
+  @spec get_senders(t(), peer_conn_t()) :: :ok | {:error, term()}
+  def get_senders(_ref, _pc), do: error()
+
+  @doc """
+  Returns the send parameters of the RTP sender represented by the given UUID. Blocks
+  until the peer connection replies.
+  """
+  @spec get_sender_parameters(t(), peer_conn_t(), String.t()) ::
+          {:ok, Specter.PeerConnection.send_parameters_t()} | {:error, term()}
+  def get_sender_parameters(_ref, _pc, _sender), do: error()
+
   @doc """
   Get the current stats of a peer connection.
   """
   @spec get_stats(t(), peer_conn_t()) :: :ok | {:error, term()}
   def get_stats(_ref, _pc), do: error()
 
+  @doc """
+  Get the inbound-rtp and outbound-rtp stats of a single track, as JSON. Blocks until
+  the peer connection replies.
+  """
+  @spec get_track_stats(t(), peer_conn_t(), String.t()) ::
+          {:ok, String.t() | nil} | {:error, term()}
+  def get_track_stats(_ref, _pc, _track), do: error()
+
   @doc """
   Sends back state of ICE connection.
   """
@@ -105,12 +285,53 @@ defmodule Specter.Native do
   @spec ice_gathering_state(t(), peer_conn_t()) :: :ok | {:error, term()}
   def ice_gathering_state(_ref, _pc), do: error()
 
+  @doc """
+  Returns the UUIDs of all APIs.
+  """
+  @spec list_apis(t()) :: {:ok, [Specter.api_t()]} | {:error, term()}
+  def list_apis(_ref), do: error()
+
+  @doc """
+  Returns the UUIDs of all MediaEngines that have not been consumed.
+  """
+  @spec list_media_engines(t()) :: {:ok, [Specter.media_engine_t()]} | {:error, term()}
+  def list_media_engines(_ref), do: error()
+
+  @doc """
+  Returns the UUIDs of all RTCPeerConnections.
+  """
+  @spec list_peer_connections(t()) :: {:ok, [peer_conn_t()]} | {:error, term()}
+  def list_peer_connections(_ref), do: error()
+
+  @doc """
+  Returns the UUIDs of all Registries that have not been consumed.
+  """
+  @spec list_registries(t()) :: {:ok, [Specter.registry_t()]} | {:error, term()}
+  def list_registries(_ref), do: error()
+
+  @doc """
+  Returns the UUIDs of all local tracks, of any type.
+  """
+  @spec list_tracks(t()) ::
+          {:ok, [Specter.TrackLocalStaticSample.t() | Specter.TrackLocalStaticRTP.t()]}
+          | {:error, term()}
+  def list_tracks(_ref), do: error()
+
   @doc """
   Sends back the pending or current session description, depending on the state of the connection.
   """
   @spec local_description(t(), peer_conn_t()) :: :ok | {:error, term()}
   def local_description(_ref, _pc), do: error()
 
+  @doc """
+  Lists the audio and video codecs registered on a MediaEngine that has not yet been
+  consumed by an API.
+  """
+  @spec media_engine_codecs(t(), Specter.media_engine_t()) ::
+          {:ok, %{audio: [Specter.codec_info_t()], video: [Specter.codec_info_t()]}}
+          | {:error, term()}
+  def media_engine_codecs(_ref, _media_engine), do: error()
+
   @doc """
   Checks whether the UUID representing a MediaEngine points to an initialized
   MediaEngine that has not been moved into a context owned by some other resource.
@@ -119,33 +340,35 @@ defmodule Specter.Native do
   def media_engine_exists(_ref, _media_engine), do: error()
 
   @doc """
-  A media engine with default codecs configured.
+  A media engine, with default codecs configured unless `default_codecs` is false.
 
   - https://github.com/webrtc-rs/webrtc/blob/master/src/api/media_engine/mod.rs
   """
-  @spec new_media_engine(t()) :: {:ok, Specter.media_engine_t()} | {:error, term()}
-  def new_media_engine(_ref), do: error()
+  @spec new_media_engine(t(), boolean(), boolean(), boolean()) ::
+          {:ok, Specter.media_engine_t()} | {:error, term()}
+  def new_media_engine(_ref, _default_codecs, _vp9, _av1), do: error()
 
   @doc """
   An RTCPeerConnection.
 
   - https://github.com/webrtc-rs/webrtc/blob/master/src/peer_connection/mod.rs
   """
-  @spec new_peer_connection(t(), Specter.api_t()) ::
+  @spec new_peer_connection(t(), Specter.api_t(), pos_integer() | nil) ::
           {:ok, peer_conn_t()} | {:error, term()}
-  def new_peer_connection(_ref, _api), do: error()
+  def new_peer_connection(_ref, _api, _command_buffer_size), do: error()
 
   @doc """
   Creates an intercepter registry. This is a user configurable RTP/RTCP pipeline,
   and provides features such as NACKs and RTCP Reports.
 
-  A registry must be created for each peer connection.
+  A registry must be created for each peer connection. An empty map of options
+  registers the default interceptors.
 
   - https://github.com/webrtc-rs/webrtc/blob/master/src/api/interceptor_registry/mod.rs
   """
-  @spec new_registry(t(), Specter.media_engine_t()) ::
+  @spec new_registry(t(), Specter.media_engine_t(), map()) ::
           {:ok, Specter.registry_t()} | {:error, term()}
-  def new_registry(_ref, _media_engine), do: error()
+  def new_registry(_ref, _media_engine, _opts), do: error()
 
   @doc """
   Creates an API.
@@ -156,14 +379,56 @@ defmodule Specter.Native do
           {:ok, Specter.api_t()} | {:error, term()}
   def new_api(_ref, _media_engine, _registry), do: error()
 
+  @doc """
+  Creates an API from a media engine with the default codecs and a registry with the
+  default interceptors, in one step.
+  """
+  @spec new_api_with_defaults(t()) :: {:ok, Specter.api_t()} | {:error, term()}
+  def new_api_with_defaults(_ref), do: error()
+
+  @doc """
+  Creates new TrackLocalStaticRTP.
+
+  - https://github.com/webrtc-rs/webrtc/blob/master/src/track/track_local/track_local_static_rtp.rs
+  """
+  @spec new_track_local_static_rtp(
+          t(),
+          Specter.RtpCodecCapability.t(),
+          String.t(),
+          String.t(),
+          String.t() | nil
+        ) :: {:ok, Specter.TrackLocalStaticRTP.t()} | {:error, term()}
+  def new_track_local_static_rtp(_ref, _codec, _id, _stream_id, _rid), do: error()
+
   @doc """
   Creates new TrackLocalStaticSample.
 
   - https://github.com/webrtc-rs/webrtc/blob/master/src/track/track_local/track_local_static_sample.rs
   """
-  @spec new_track_local_static_sample(t(), Specter.RtpCodecCapability.t(), String.t(), String.t()) ::
-          {:ok, Specter.TrackLocalStaticSample.t()} | {:error, term()}
-  def new_track_local_static_sample(_ref, _codec, _id, _stream_id), do: error()
+  @spec new_track_local_static_sample(
+          t(),
+          Specter.RtpCodecCapability.t(),
+          String.t(),
+          String.t(),
+          String.t() | nil
+        ) :: {:ok, Specter.TrackLocalStaticSample.t()} | {:error, term()}
+  def new_track_local_static_sample(_ref, _codec, _id, _stream_id, _rid), do: error()
+
+  @doc """
+  Returns the size of the command buffer of an RTCPeerConnection, and how much room
+  is left in it.
+  """
+  @spec peer_connection_command_buffer(t(), peer_conn_t()) ::
+          {:ok, %{size: pos_integer(), available: non_neg_integer()}} | {:error, term()}
+  def peer_connection_command_buffer(_ref, _pc), do: error()
+
+  @doc """
+  Returns the fingerprint of the local DTLS certificate of an RTCPeerConnection. Blocks
+  until the peer connection replies.
+  """
+  @spec peer_connection_fingerprint(t(), peer_conn_t()) ::
+          {:ok, Specter.PeerConnection.fingerprint_t()} | {:error, term()}
+  def peer_connection_fingerprint(_ref, _pc), do: error()
 
   @doc """
   Checks whether the UUID representing an RTCPeerConnection points to an initialized
@@ -188,11 +453,46 @@ defmodule Specter.Native do
   def pending_remote_description(_ref, _pc), do: error()
 
   @doc """
-  Reads H264 file and writes it to the track.
+  Reads Ogg file containing Opus audio and writes it to the track.
+  """
+  @spec play_audio_from_file(t(), Specter.TrackLocalStaticSample.t(), Path.t()) ::
+          :ok | {:error, term()}
+  def play_audio_from_file(_ref, _track, _path), do: error()
+
+  @doc """
+  Reads H264 file and writes it to the track at `fps` samples per second. Parsed
+  samples are buffered in a queue of `queue_size` samples ahead of being written.
   """
-  @spec play_from_file_h264(t(), Specter.TrackLocalStaticSample.t(), Path.t()) ::
+  @spec play_from_file_h264(
+          t(),
+          Specter.TrackLocalStaticSample.t(),
+          Path.t(),
+          pos_integer(),
+          pos_integer()
+        ) ::
           :ok | {:error, term()}
-  def play_from_file_h264(_ref, _track, _path), do: error()
+  def play_from_file_h264(_ref, _track, _path, _queue_size, _fps), do: error()
+
+  @doc """
+  Reads IVF file containing VP8 video and writes it to the track.
+  """
+  @spec play_vp8_from_file(t(), Specter.TrackLocalStaticSample.t(), Path.t()) ::
+          :ok | {:error, term()}
+  def play_vp8_from_file(_ref, _track, _path), do: error()
+
+  @doc """
+  Registers a codec with the given payload type and kind on a media engine.
+
+  - https://github.com/webrtc-rs/webrtc/blob/master/src/api/media_engine/mod.rs
+  """
+  @spec register_codec(
+          t(),
+          Specter.media_engine_t(),
+          Specter.RtpCodecCapability.t(),
+          Specter.payload_type_t(),
+          :audio | :video
+        ) :: :ok | {:error, term()}
+  def register_codec(_ref, _media_engine, _codec, _payload_type, _kind), do: error()
 
   @doc """
   Checks whether the UUID representing a Registry points to an initialized
@@ -201,12 +501,109 @@ defmodule Specter.Native do
   @spec registry_exists(t(), Specter.registry_t()) :: {:ok, boolean()} | {:error, term()}
   def registry_exists(_ref, _registry), do: error()
 
+  @doc """
+  Records a remote H264, VP8 or Opus track to a file. Sends back
+  `{:ok, _uuid, :record_to_file}`, and `{:recording_finished, _uuid, _track_id}` once the
+  track ends.
+  """
+  @spec record_to_file(t(), peer_conn_t(), String.t(), Path.t()) :: :ok | {:error, term()}
+  def record_to_file(_ref, _pc, _track_id, _path), do: error()
+
+  @doc """
+  Replaces the track sent by an RTP sender, or detaches it when the track is nil. Blocks
+  until the peer connection replies.
+  """
+  @spec replace_track(
+          t(),
+          peer_conn_t(),
+          String.t(),
+          Specter.TrackLocalStaticSample.t() | Specter.TrackLocalStaticRTP.t() | nil
+        ) :: :ok | {:error, term()}
+  def replace_track(_ref, _pc, _sender, _track), do: error()
+
+  @doc """
+  Sends a PLI for the remote track with the given id, asking the remote peer for a keyframe.
+  Sends back `{:ok, _uuid, :request_keyframe}`.
+  """
+  @spec request_keyframe(t(), peer_conn_t(), String.t()) :: :ok | {:error, term()}
+  def request_keyframe(_ref, _pc, _track_id), do: error()
+
+  @doc """
+  Restarts ICE with the next offer created by an RTCPeerConnection.
+  Sends back `{:ok, _uuid, :restart_ice}`.
+  """
+  @spec restart_ice(t(), peer_conn_t()) :: :ok | {:error, term()}
+  def restart_ice(_ref, _pc), do: error()
+
   @doc """
   Sends back the pending or current remote session description, depending on the state of the connection.
   """
   @spec remote_description(t(), peer_conn_t()) :: :ok | {:error, term()}
   def remote_description(_ref, _pc), do: error()
 
+  @doc """
+  Converts a JSON session description into a `Specter.SessionDescription` struct.
+  """
+  @spec sdp_json_to_struct(String.t()) :: {:ok, Specter.SessionDescription.t()} | {:error, term()}
+  def sdp_json_to_struct(_json), do: error()
+
+  @doc """
+  Converts a `Specter.SessionDescription` struct into a JSON session description.
+  """
+  @spec sdp_struct_to_json(Specter.SessionDescription.t()) :: {:ok, String.t()} | {:error, term()}
+  def sdp_struct_to_json(_desc), do: error()
+
+  @doc """
+  Returns frame rate, frame count and resolution from the outbound-rtp stats of the RTP
+  sender represented by the given UUID. Blocks until the peer connection replies.
+  """
+  @spec get_outbound_video_stats(t(), peer_conn_t(), String.t()) ::
+          {:ok, Specter.PeerConnection.outbound_video_stats_t()} | {:error, term()}
+  def get_outbound_video_stats(_ref, _pc, _sender), do: error()
+
+  @doc """
+  Returns the state and limits of the SCTP transport of an RTCPeerConnection.
+  """
+  @spec sctp_transport(t(), peer_conn_t()) ::
+          {:ok, Specter.PeerConnection.sctp_transport_t()} | {:error, term()}
+  def sctp_transport(_ref, _pc), do: error()
+
+  @doc """
+  Checks whether the RTP sender represented by the given UUID currently has a track
+  attached.
+  """
+  @spec sender_active(t(), peer_conn_t(), String.t()) :: {:ok, boolean()} | {:error, term()}
+  def sender_active(_ref, _pc, _sender), do: error()
+
+  @doc """
+  Sends back `{:sender_stats, _uuid, _sender, stats}` with the bytes and packets sent by
+  the RTP sender represented by the given UUID, or `nil` stats for an unknown sender.
+  """
+  @spec sender_stats(t(), peer_conn_t(), String.t()) :: :ok | {:error, term()}
+  def sender_stats(_ref, _pc, _sender), do: error()
+
+  @doc """
+  Sets the codecs negotiated for the transceiver with the given mid, in order of
+  preference. Blocks until the peer connection replies.
+  """
+  @spec set_codec_preferences(t(), peer_conn_t(), String.t(), [Specter.RtpCodecCapability.t()]) ::
+          :ok | {:error, term()}
+  def set_codec_preferences(_ref, _pc, _mid, _codecs), do: error()
+
+  @doc """
+  Sets the number of bytes below which the queue of a data channel must drop for
+  `{:data_channel_buffered_low, _uuid, _data_channel}` to be sent. Blocks until the peer
+  connection replies.
+  """
+  @spec set_data_channel_buffered_amount_low_threshold(
+          t(),
+          peer_conn_t(),
+          String.t(),
+          non_neg_integer()
+        ) :: :ok | {:error, term()}
+  def set_data_channel_buffered_amount_low_threshold(_ref, _pc, _data_channel, _bytes),
+    do: error()
+
   @doc """
   Given a UUID representing an RTCPeerConnection and an offer or an answer from that same
   peer connection, set it as the local session description.
@@ -215,14 +612,48 @@ defmodule Specter.Native do
           :ok | {:error, term()}
   def set_local_description(_ref, _pc, _desc), do: error()
 
+  @doc """
+  Periodically sends REMB packets capping the bitrate that the remote peer sends.
+  Sends back `{:ok, _uuid, :set_receive_bitrate_cap}`.
+  """
+  @spec set_receive_bitrate_cap(t(), peer_conn_t(), pos_integer()) :: :ok | {:error, term()}
+  def set_receive_bitrate_cap(_ref, _pc, _bps), do: error()
+
   @doc """
   Given a UUID representing an RTCPeerConnection and an offer from that peer connection or an
   answer from a different peer connection, set it on the peer connection as the remote session
-  description.
+  description. When `polite` is not nil, offers received during glare are resolved according
+  to perfect negotiation.
   """
-  @spec set_remote_description(t(), peer_conn_t(), Specter.PeerConnection.session_description_t()) ::
+  @spec set_remote_description(
+          t(),
+          peer_conn_t(),
+          Specter.PeerConnection.session_description_t(),
+          boolean() | nil
+        ) ::
           :ok | {:error, term()}
-  def set_remote_description(_ref, _pc, _desc), do: error()
+  def set_remote_description(_ref, _pc, _desc, _polite), do: error()
+
+  @doc """
+  Sets the remote session description on a peer connection from its type and raw SDP,
+  rather than from JSON.
+  """
+  @spec set_remote_sdp(
+          t(),
+          peer_conn_t(),
+          Specter.PeerConnection.remote_sdp_type_t(),
+          Specter.PeerConnection.sdp_t(),
+          boolean() | nil
+        ) ::
+          :ok | {:error, term()}
+  def set_remote_sdp(_ref, _pc, _sdp_type, _sdp, _polite), do: error()
+
+  @doc """
+  Starts or stops sending the RTP packets of a remote track to the owning process. Sends
+  back `{:ok, _uuid, :set_rtp_forwarding}`.
+  """
+  @spec set_rtp_forwarding(t(), peer_conn_t(), String.t(), boolean()) :: :ok | {:error, term()}
+  def set_rtp_forwarding(_ref, _pc, _track_id, _enabled), do: error()
 
   @doc """
   Sends back state of sesion parameters negotiation.
@@ -230,6 +661,74 @@ defmodule Specter.Native do
   @spec signaling_state(t(), peer_conn_t()) :: :ok | {:error, term()}
   def signaling_state(_ref, _pc), do: error()
 
+  @doc """
+  Sends back `{:snapshot, _uuid, snapshot}` with the states and a few metrics of an
+  RTCPeerConnection.
+  """
+  @spec snapshot(t(), peer_conn_t()) :: :ok | {:error, term()}
+  def snapshot(_ref, _pc), do: error()
+
+  @doc """
+  Periodically sends `{:stats, _uuid, json}` with the stats of an RTCPeerConnection.
+  Sends back `{:ok, _uuid, :start_stats_reporting}`.
+  """
+  @spec start_stats_reporting(t(), peer_conn_t(), pos_integer()) :: :ok | {:error, term()}
+  def start_stats_reporting(_ref, _pc, _interval), do: error()
+
+  @doc """
+  Stops an in-progress playback on the track.
+  """
+  @spec stop_playback(t(), Specter.TrackLocalStaticSample.t()) :: :ok | {:error, term()}
+  def stop_playback(_ref, _track), do: error()
+
+  @doc """
+  Stops periodically sending the stats of an RTCPeerConnection.
+  Sends back `{:ok, _uuid, :stop_stats_reporting}`.
+  """
+  @spec stop_stats_reporting(t(), peer_conn_t()) :: :ok | {:error, term()}
+  def stop_stats_reporting(_ref, _pc), do: error()
+
+  @doc """
+  Returns the negotiated direction of the transceiver with the given mid. Blocks until
+  the peer connection replies.
+  """
+  @spec transceiver_current_direction(t(), peer_conn_t(), String.t()) ::
+          {:ok, Specter.PeerConnection.transceiver_direction_t() | nil} | {:error, term()}
+  def transceiver_current_direction(_ref, _pc, _mid), do: error()
+
+  @doc """
+  Returns the mid of the transceiver with the given UUID, or nil until it has one.
+  """
+  @spec transceiver_mid(t(), peer_conn_t(), String.t()) ::
+          {:ok, String.t() | nil} | {:error, term()}
+  def transceiver_mid(_ref, _pc, _transceiver), do: error()
+
+  @doc """
+  Writes an RTCP packet to the remote peer of an RTCPeerConnection. Sends back
+  `{:ok, _uuid, :write_rtcp}`.
+  """
+  @spec write_rtcp(t(), peer_conn_t(), Specter.PeerConnection.rtcp_packet_t()) ::
+          :ok | {:error, term()}
+  def write_rtcp(_ref, _pc, _packet), do: error()
+
+  @doc """
+  Writes a raw RTP packet to a TrackLocalStaticRTP, blocking until it has been written.
+  """
+  @spec write_track_rtp(t(), Specter.TrackLocalStaticRTP.t(), binary()) ::
+          :ok | {:error, term()}
+  def write_track_rtp(_ref, _track, _packet), do: error()
+
+  @doc """
+  Writes an encoded sample to the track, blocking until it has been written.
+  """
+  @spec write_track_sample(
+          t(),
+          Specter.TrackLocalStaticSample.t(),
+          binary(),
+          non_neg_integer()
+        ) :: :ok | {:error, term()}
+  def write_track_sample(_ref, _track, _payload, _duration_ms), do: error()
+
   ##
   ## PRIVATE
   ##
@@ -17,6 +17,11 @@ defmodule Specter.PeerConnection do
   """
   @opaque t() :: String.t()
 
+  @typedoc """
+  Options for creating a peer connection with `new/3`.
+  """
+  @type new_options_t() :: [] | [command_buffer_size: pos_integer()]
+
   @typedoc """
   Options for creating a webrtc answer. Values default to false.
   """
@@ -27,11 +32,222 @@ defmodule Specter.PeerConnection do
   """
   @type offer_options_t() :: [] | [voice_activity_detection: bool, ice_restart: bool]
 
+  @typedoc """
+  Why webrtc could not create an offer or answer. `:invalid_state` is given when the
+  signaling state does not allow it, `:no_ice_agent` when restarting ICE before it has
+  started, and `:webrtc_error` for errors that are not otherwise classified.
+  """
+  @type description_error_t() ::
+          :closed
+          | :excessive_retries
+          | :invalid_state
+          | :no_ice_agent
+          | :no_remote_description
+          | :webrtc_error
+
+  @typedoc """
+  Message sent at most every 100 milliseconds while a remote audio track carries the
+  audio level header extension, which is negotiated by the `audio_level` option of
+  `Specter.new_registry/3`. The string is the id of the remote track. The level is the
+  loudest since the last message, in -dBov from 0 for the loudest to 127 for silence,
+  and the boolean tells whether the sender detected voice in that time.
+  """
+  @type audio_level_msg_t() :: {:audio_level, t(), String.t(), 0..127, boolean()}
+
+  @typedoc """
+  Message sent when an offer cannot be created by `create_offer/3`, or a data channel
+  by `create_data_channel/4`. The string is the error as described by webrtc.
+  """
+  @type offer_error_msg_t() :: {:offer_error, t(), description_error_t(), String.t()}
+
+  @typedoc """
+  Message sent when an answer cannot be created by `create_answer/3`. The string is
+  the error as described by webrtc.
+  """
+  @type answer_error_msg_t() :: {:answer_error, t(), description_error_t(), String.t()}
+
   @typedoc """
   The type of an SDP message, either an `:offer` or an `:answer`.
   """
   @type sdp_type_t() :: :offer | :answer
 
+  @typedoc """
+  The type of a raw SDP set as the remote description. `:rollback` discards a remote
+  offer that has not yet been answered.
+  """
+  @type remote_sdp_type_t() :: :offer | :answer | :pranswer | :rollback
+
+  @typedoc """
+  Options for setting a remote description.
+  """
+  @type remote_description_options_t() :: [] | [polite: boolean()]
+
+  @typedoc """
+  Message sent when an offer is received while a local offer is pending, and the
+  `polite` option was given to `set_remote_description/4`. A polite peer rolls back
+  its local offer, while an impolite peer ignores the remote offer.
+  """
+  @type glare_msg_t() :: {:glare, t(), :rolled_back | :ignored}
+
+  @typedoc """
+  Options for creating a data channel. See `create_data_channel/4`.
+  """
+  @type data_channel_options_t() :: [
+          ordered: boolean(),
+          max_retransmits: non_neg_integer(),
+          max_packet_life_time: non_neg_integer(),
+          protocol: String.t(),
+          negotiated: boolean(),
+          id: non_neg_integer()
+        ]
+
+  @typedoc """
+  Message sent when a data channel has been created by `create_data_channel/4`.
+  """
+  @type data_channel_created_msg_t() :: {:data_channel_created, t()}
+
+  @typedoc """
+  Message sent when the bytes queued on a data channel drop below its threshold. See
+  `set_data_channel_buffered_amount_low_threshold/4`.
+  """
+  @type data_channel_buffered_low_msg_t() :: {:data_channel_buffered_low, t(), String.t()}
+
+  @typedoc """
+  Options for draining a peer connection.
+  """
+  @type drain_options_t() :: [] | [grace_period: non_neg_integer()]
+
+  @typedoc """
+  Message sent when a track, transceiver or data channel is added to a peer connection
+  that is being drained with `drain/3`.
+  """
+  @type draining_error_msg_t() ::
+          {:draining_error, t(), :add_track | :add_transceiver | :create_data_channel}
+
+  @typedoc """
+  The configuration in effect on a peer connection, as returned by `get_configuration/2`.
+  `ice_servers` lists the URLs of every ICE server. Policies are given as their names in
+  the WebRTC specification, such as `"max-bundle"`, or `"unspecified"` when webrtc is
+  left to pick.
+  """
+  @type configuration_t() :: %{
+          ice_servers: [String.t()],
+          ice_transport_policy: String.t(),
+          bundle_policy: String.t(),
+          rtcp_mux_policy: String.t(),
+          ice_candidate_pool_size: non_neg_integer()
+        }
+
+  @typedoc """
+  The fingerprint of a DTLS certificate, as returned by `fingerprint/2`. `value` is the
+  colon separated hex digest of the certificate, as in `a=fingerprint` of an SDP.
+  """
+  @type fingerprint_t() :: %{algorithm: String.t(), value: String.t()}
+
+  @typedoc """
+  Video metrics of an RTP sender, as returned by `get_outbound_video_stats/3`. Fields
+  are `nil` when they are not present in the outbound-rtp stats.
+  """
+  @type outbound_video_stats_t() :: %{
+          frames_per_second: float() | nil,
+          frames_sent: non_neg_integer() | nil,
+          frame_width: non_neg_integer() | nil,
+          frame_height: non_neg_integer() | nil
+        }
+
+  @typedoc """
+  The RTP receiver of a transceiver, as sent back by `get_receivers/2`, with the
+  remote tracks it is receiving. `mid` is `nil` until the transceiver has been
+  negotiated, and there is one track per simulcast `rid`.
+  """
+  @type receiver_t() :: %{
+          mid: String.t() | nil,
+          kind: :audio | :video | nil,
+          tracks: [
+            %{
+              id: String.t(),
+              stream_id: String.t(),
+              rid: String.t(),
+              ssrc: non_neg_integer(),
+              mime_type: String.t()
+            }
+          ]
+        }
+
+  @typedoc """
+  Message sent as a result of a call to `get_receivers/2`.
+  """
+  @type receivers_msg_t() :: {:receivers, t(), [receiver_t()]}
+
+  @typedoc """
+  One side of a candidate pair. `candidate_type` is one of `"host"`, `"srflx"`,
+  `"prflx"` or `"relay"`, and `protocol` is `"udp"` or `"tcp"`.
+  """
+  @type candidate_t() :: %{
+          candidate_type: String.t(),
+          protocol: String.t(),
+          address: String.t(),
+          port: :inet.port_number()
+        }
+
+  @typedoc """
+  Message sent as a result of a call to `get_selected_candidate_pair/2`. The pair is
+  `nil` until ICE has selected one.
+  """
+  @type selected_candidate_pair_msg_t() ::
+          {:selected_candidate_pair, t(), %{local: candidate_t(), remote: candidate_t()} | nil}
+
+  @typedoc """
+  An RTP sender, as sent back by `get_senders/2`. Track fields are `nil` when the sender
+  has no track attached. `mime_type` is that of the first codec the sender may use,
+  which after negotiation is the codec it sends with. The `stream_id` and `track_id`
+  form the `a=msid` announced for the sender. `ssrc` is that of the sender's first
+  encoding, which is its only one unless it sends simulcast.
+  """
+  @type sender_t() :: %{
+          id: String.t(),
+          track_id: String.t() | nil,
+          stream_id: String.t() | nil,
+          kind: :audio | :video | nil,
+          mime_type: String.t() | nil,
+          ssrc: non_neg_integer() | nil
+        }
+
+  @typedoc """
+  Message sent as a result of a call to `get_senders/2`.
+  """
+  @type senders_msg_t() :: {:senders, t(), [sender_t()]}
+
+  @typedoc """
+  The bytes and packets sent by an RTP sender, summed over its encodings, as sent back
+  by `sender_stats/3`. Both count from 0 until the sender has sent any packets.
+  """
+  @type sender_stats_t() :: %{bytes_sent: non_neg_integer(), packets_sent: non_neg_integer()}
+
+  @typedoc """
+  Message sent as a result of a call to `sender_stats/3`. The stats are `nil` when the
+  peer connection has no sender with the given UUID.
+  """
+  @type sender_stats_msg_t() :: {:sender_stats, t(), String.t(), sender_stats_t() | nil}
+
+  @typedoc """
+  The parameters of an RTP sender, as returned by `get_sender_parameters/3`. There is
+  one encoding per stream sent, for instance per simulcast layer.
+  """
+  @type send_parameters_t() :: %{
+          encodings: [%{rid: String.t(), ssrc: non_neg_integer(), payload_type: 0..127}],
+          codecs: [
+            %{
+              mime_type: String.t(),
+              clock_rate: non_neg_integer(),
+              channels: non_neg_integer(),
+              sdp_fmtp_line: String.t(),
+              payload_type: 0..127
+            }
+          ],
+          header_extensions: [%{uri: String.t(), id: integer()}]
+        }
+
   @typedoc """
   A UTF-8 encoded string encapsulating either an offer or an answer.
   """
@@ -48,10 +264,22 @@ defmodule Specter.PeerConnection do
   """
   @type session_description_t() :: String.t()
 
+  @typedoc """
+  Where the SDP given to `set_local_description/3` fails to parse. `line` counts from 1,
+  and `text` is the line itself. `section` is the index of the media section holding
+  the line, or `nil` for the session section. `reason` is the error from the parser.
+  """
+  @type sdp_error_t() :: %{
+          line: pos_integer(),
+          text: String.t(),
+          section: non_neg_integer() | nil,
+          reason: String.t()
+        }
+
   @typedoc """
   An ICE candidate as JSON.
   """
-  @type ice_candidate_t() :: String.t()
+  @type ice_candidate_t() :: String.t() | Specter.IceCandidate.t() | map()
 
   @typedoc """
   Possible states of ICE connection.
@@ -72,6 +300,12 @@ defmodule Specter.PeerConnection do
   @type ice_connection_state_msg_t() ::
           {:ice_connection_state, t(), ice_connection_state_t()}
 
+  @typedoc """
+  Message sent whenever the state of ICE connection changes.
+  """
+  @type ice_connection_state_change_msg_t() ::
+          {:ice_connection_state_change, t(), ice_connection_state_t()}
+
   @typedoc """
   Possible states of ICE gathering process.
   """
@@ -83,6 +317,12 @@ defmodule Specter.PeerConnection do
   @type ice_gathering_state_msg_t() ::
           {:ice_gathering_state, t(), ice_connection_state_t()}
 
+  @typedoc """
+  Message sent once a peer connection has finished gathering ICE candidates.
+  After this, no more `:ice_candidate` messages will be sent until ICE restarts.
+  """
+  @type ice_gathering_complete_msg_t() :: {:ice_gathering_complete, t()}
+
   @typedoc """
   Possible states of session parameters negotiation.
   """
@@ -111,21 +351,167 @@ defmodule Specter.PeerConnection do
   """
   @type connection_state_msg_t() :: {:connection_state, t(), connection_state_t()}
 
+  @typedoc """
+  Message sent whenever the state of a peer connection changes.
+  """
+  @type connection_state_change_msg_t() ::
+          {:connection_state_change, t(), connection_state_t()}
+
+  @typedoc """
+  Possible states of the DTLS transport of a peer connection, which secures its media
+  and data channels once ICE has connected.
+  """
+  @type dtls_transport_state_t() ::
+          :closed | :connected | :connecting | :failed | :new | :unspecified
+
+  @typedoc """
+  Message sent whenever the state of the DTLS transport of a peer connection changes.
+  All media and data channels share one DTLS transport. A peer connection whose ICE
+  connection state reaches `:connected` while its DTLS transport never does is stuck
+  in the DTLS handshake, for instance because the remote fingerprint does not match.
+  """
+  @type dtls_transport_state_change_msg_t() ::
+          {:dtls_transport_state_change, t(), dtls_transport_state_t()}
+
+  @typedoc """
+  The SCTP transport carrying the data channels of a peer connection, as returned by
+  `sctp_transport/2`. `max_channels` is how many data channels may be open at once, and
+  `max_message_size` is the largest message, in bytes, that can be sent on one of them.
+  webrtc.rs sends messages of up to 65536 bytes, a fixed limit that it does not
+  negotiate, so `max_message_size` is that limit, lowered to the `a=max-message-size`
+  of the remote description once one has been set.
+  """
+  @type sctp_transport_t() :: %{
+          state: :closed | :connected | :connecting | :unspecified,
+          max_channels: pos_integer(),
+          max_message_size: pos_integer()
+        }
+
+  @typedoc """
+  A summary of the health of a peer connection, as sent back by `snapshot/2`. `senders`
+  counts the senders with a track attached, and `receivers` those receiving at least
+  one remote track. `round_trip_time` is that of the selected candidate pair, in
+  seconds, and is `nil` until ICE has selected a pair.
+  """
+  @type snapshot_t() :: %{
+          connection_state: connection_state_t(),
+          ice_connection_state: ice_connection_state_t(),
+          ice_gathering_state: ice_gathering_state_t(),
+          signaling_state: signaling_state_t(),
+          senders: non_neg_integer(),
+          receivers: non_neg_integer(),
+          round_trip_time: float() | nil
+        }
+
+  @typedoc """
+  Message sent as a result of a call to `snapshot/2`.
+  """
+  @type snapshot_msg_t() :: {:snapshot, t(), snapshot_t()}
+
   @typedoc """
   Message sent as a result of a call to `add_track/3`.
   """
-  @type rtp_sender_t() :: {:rtp_sender, t(), Specter.TrackLocalStaticSample.t(), String.t()}
+  @type rtp_sender_t() ::
+          {:rtp_sender, t(), Specter.TrackLocalStaticSample.t() | Specter.TrackLocalStaticRTP.t(),
+           String.t()}
+
+  @typedoc """
+  Message sent once a remote track being recorded with `record_to_file/4` has ended, and
+  the recording has been closed. The string is the id of the remote track.
+  """
+  @type recording_finished_msg_t() :: {:recording_finished, t(), String.t()}
+
+  @typedoc """
+  Message sent when a recording started with `record_to_file/4` cannot be written, such
+  as when the remote track has a codec that cannot be recorded. The first string is the
+  id of the remote track, and the second is the reason.
+  """
+  @type recording_error_msg_t() :: {:recording_error, t(), String.t(), String.t()}
+
+  @typedoc """
+  Message sent when a track received from the remote peer can no longer be read, for
+  instance once its transceiver has stopped or the peer connection has closed. The
+  string is the id of the remote track, as given by `get_receivers/2`.
+  """
+  @type track_ended_msg_t() :: {:track_ended, t(), String.t()}
+
+  @typedoc """
+  Message sent for each RTP packet read from a remote track, once forwarding has been
+  enabled with `set_rtp_forwarding/4`. The binary is the marshaled packet, which can be
+  written as-is to a `Specter.TrackLocalStaticRTP`.
+  """
+  @type rtp_msg_t() :: {:rtp, t(), String.t(), binary()}
+
+  @typedoc """
+  An RTCP packet given to `write_rtcp/3`, either as one of the common feedback
+  messages, or as a binary holding a serialized compound packet:
+
+  - `{:pli, media_ssrc}` is a Picture Loss Indication, asking for a keyframe.
+  - `{:fir, media_ssrc}` is a Full Intra Request, asking for a keyframe.
+  - `{:remb, bps, media_ssrcs}` caps the bitrate the remote sends for the SSRCs.
+  """
+  @type rtcp_packet_t() ::
+          {:pli, non_neg_integer()}
+          | {:fir, non_neg_integer()}
+          | {:remb, pos_integer(), [non_neg_integer()]}
+          | binary()
+
+  @typedoc """
+  Message sent when an RTCP packet given to `write_rtcp/3` cannot be written. The reason
+  is `:not_connected` before the peer connection has connected, and otherwise a string.
+  """
+  @type rtcp_error_msg_t() :: {:rtcp_error, t(), :not_connected | String.t()}
+
+  @typedoc """
+  Message sent when the peer connection rejects a track given to `add_track/3`.
+  """
+  @type track_error_msg_t() :: {:track_error, t(), String.t()}
+
+  @typedoc """
+  Message sent in place of a JSON message, such as an offer, answer, session
+  description, stats report or ICE candidate, when the value could not be
+  serialized to JSON. The atom is the tag the message would otherwise have had.
+  """
+  @type json_error_msg_t() :: {:json_error, t(), atom(), String.t()}
+
+  @typedoc """
+  Message sent in place of `{:peer_connection_ready, pc}` when webrtc fails to create
+  the peer connection, for instance because a TURN server is configured without
+  credentials. The string is the error given by webrtc.
+  """
+  @type peer_connection_error_msg_t() :: {:peer_connection_error, t(), String.t()}
+
+  @typedoc """
+  The kind of media sent or received by a transceiver.
+  """
+  @type transceiver_kind_t() :: :audio | :video
+
+  @typedoc """
+  The direction in which a transceiver negotiates media.
+  """
+  @type transceiver_direction_t() :: :sendrecv | :sendonly | :recvonly | :inactive
+
+  @typedoc """
+  Message sent as a result of a call to `add_transceiver/4`. The uuid identifies the
+  transceiver in `transceiver_mid/3`.
+  """
+  @type rtp_transceiver_msg_t() :: {:rtp_transceiver, t(), String.t()}
 
   @doc """
   Creates a new RTCPeerConnection, using an API reference created with `new_api/3`. The
   functionality wrapped by this function is async, so `:ok` is returned immediately.
   Callers should listen for the `{:peer_connection_ready, peer_connection_t()}` message
-  to receive the results of this function.
+  to receive the results of this function, or `t:peer_connection_error_msg_t/0` if the
+  peer connection could not be created.
 
-  | param     | type     | default |
-  | --------- | -------- | ------- |
-  | `specter` | `t()`    | |
-  | `api`     | `opaque` | |
+  | param                 | type            | default |
+  | --------------------- | --------------- | ------- |
+  | `specter`             | `t()`           | |
+  | `api`                 | `opaque`        | |
+  | `command_buffer_size` | `pos_integer()` | from `Specter.init/1` |
+
+  `command_buffer_size` overrides the size of the command buffer configured in
+  `Specter.init/1` for this peer connection only. See `command_buffer/2`.
 
   ## Usage
 
@@ -142,8 +528,50 @@ defmodule Specter.PeerConnection do
       ...>       500 -> {:error, :timeout}
       ...>     end
   """
-  @spec new(Specter.t(), Specter.api_t()) :: {:ok, t()} | {:error, term()}
-  def new(%Specter{native: ref}, api), do: Native.new_peer_connection(ref, api)
+  @spec new(Specter.t(), Specter.api_t(), new_options_t()) :: {:ok, t()} | {:error, term()}
+  def new(%Specter{native: ref}, api, opts \\ []),
+    do: Native.new_peer_connection(ref, api, Keyword.get(opts, :command_buffer_size))
+
+  @doc """
+  Returns the `size` of the command buffer of a peer connection, and how many more
+  commands are `available` before calls return `{:error, :busy}`. Calls on a peer
+  connection are queued in this buffer until the task owning the peer connection
+  handles them.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api, command_buffer_size: 16)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      iex> {:ok, %{size: 16}} = Specter.PeerConnection.command_buffer(specter, pc)
+  """
+  @spec command_buffer(Specter.t(), t()) ::
+          {:ok, %{size: pos_integer(), available: non_neg_integer()}} | {:error, term()}
+  def command_buffer(%Specter{native: ref}, pc),
+    do: Native.peer_connection_command_buffer(ref, pc)
+
+  @doc """
+  Returns the UUIDs of all RTCPeerConnections that have not been closed, sorted.
+  Peer connections that are draining are included until they close. This is useful
+  when auditing for peer connections that were never closed.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> Specter.PeerConnection.list(specter)
+      {:ok, []}
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      iex> {:ok, [^pc]} = Specter.PeerConnection.list(specter)
+  """
+  @spec list(Specter.t()) :: {:ok, [t()]} | {:error, term()}
+  def list(%Specter{native: ref}), do: Native.list_peer_connections(ref)
 
   @doc """
   Returns true or false, depending on whether the RTCPeerConnection is initialized.
@@ -174,6 +602,41 @@ defmodule Specter.PeerConnection do
     end
   end
 
+  @doc """
+  Returns whether the remote peer accepts ICE candidates trickled after its session
+  description, as it announces with `a=ice-options:trickle`. Until a remote description
+  is set this is not known, and `nil` is returned.
+
+  Signaling that supports peers without trickle ICE can use this to decide whether to
+  wait for `gathering_complete_promise/2` before sending a session description, so that
+  it includes every candidate.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> Specter.PeerConnection.can_trickle_ice_candidates(specter, pc)
+      {:ok, nil}
+  """
+  @spec can_trickle_ice_candidates(Specter.t(), t()) :: {:ok, boolean() | nil} | {:error, term()}
+  def can_trickle_ice_candidates(%Specter{native: ref}, pc),
+    do: Native.can_trickle_ice_candidates(ref, pc)
+
+  @doc """
+  Removes a receive bitrate cap set with `set_receive_bitrate_cap/3`. The remote
+  peer will recover to its own bandwidth estimate over time.
+
+  Sends back `{:ok, peer_connection, :clear_receive_bitrate_cap}`.
+  """
+  @spec clear_receive_bitrate_cap(Specter.t(), t()) :: :ok | {:error, term()}
+  def clear_receive_bitrate_cap(%Specter{native: ref}, pc),
+    do: Native.clear_receive_bitrate_cap(ref, pc)
+
   @doc """
   Closes an open instance of an RTCPeerConnection.
 
@@ -201,20 +664,96 @@ defmodule Specter.PeerConnection do
   @spec close(Specter.t(), t()) :: :ok | {:error, term()}
   def close(%Specter{native: ref}, pc), do: Native.close_peer_connection(ref, pc)
 
+  @doc """
+  Closes an open instance of an RTCPeerConnection, returning once the peer connection
+  and its ICE and DTLS transports have been closed. `close/2` returns immediately and
+  closes the peer connection in the background, while this blocks on a dirty scheduler
+  until it is done. `{:peer_connection_closed, pc}` is sent in both cases.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> Specter.PeerConnection.close_sync(specter, pc)
+      :ok
+      iex> Specter.PeerConnection.exists?(specter, pc)
+      false
+  """
+  @spec close_sync(Specter.t(), t()) :: :ok | {:error, term()}
+  def close_sync(%Specter{native: ref}, pc), do: Native.close_peer_connection_sync(ref, pc)
+
   @doc """
   Given an ICE candidate, add it to the given peer connection. Assumes trickle ICE.
-  Candidates must be JSON, with the keys `candidate`, `sdp_mid`, `sdp_mline_index`, and
-  `username_fragment`.
+  Candidates may be JSON, with the keys `candidate`, `sdp_mid`, `sdp_mline_index`, and
+  `username_fragment`, or a `t:Specter.IceCandidate.t/0` or map with the same keys.
+
+  Returns `{:error, :invalid_ice_candidate}` when a map cannot be decoded into a
+  candidate.
   """
   @spec add_ice_candidate(Specter.t(), t(), ice_candidate_t()) :: :ok | {:error, term()}
+  def add_ice_candidate(specter, pc, %{candidate: _} = candidate) when not is_struct(candidate),
+    do: add_ice_candidate(specter, pc, struct(Specter.IceCandidate, candidate))
+
   def add_ice_candidate(%Specter{native: ref}, pc, candidate),
     do: Native.add_ice_candidate(ref, pc, candidate)
 
   @doc """
-  Adds track to peer connection.
+  Adds a track as another encoding of an RTP sender, so that the sender offers the
+  encodings as simulcast layers, with an `a=rid` line for each one and an
+  `a=simulcast` line listing them.
+
+  The sender must have been returned by `add_track/3` for a track created with a `rid`.
+  The track must have been created with a different `rid`, but with the same id, stream
+  id and kind. Encodings must be added before the offer is created.
+
+  Returns `{:error, :not_found}` when the peer connection or sender do not exist,
+  `{:error, :invalid_track}` when the track does not exist, and `{:error, reason}` as a
+  string when webrtc rejects the encoding.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      iex> {:ok, low} =
+      ...>   Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter", rid: "low")
+      iex> {:ok, high} =
+      ...>   Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter", rid: "high")
+      iex> {:ok, sender} = Specter.PeerConnection.add_track(specter, pc, low)
+      iex> Specter.PeerConnection.add_encoding(specter, pc, sender, high)
+      :ok
+  """
+  @spec add_encoding(
+          Specter.t(),
+          t(),
+          String.t(),
+          Specter.TrackLocalStaticSample.t() | Specter.TrackLocalStaticRTP.t()
+        ) :: :ok | {:error, term()}
+  def add_encoding(%Specter{native: ref}, pc, sender, track),
+    do: Native.add_encoding(ref, pc, sender, track)
 
-  Sends back uuid of newly created rtp sender.
-  This will send message `t:rtp_sender_msg_t/0`.
+  @doc """
+  Adds track to peer connection, returning the uuid of the rtp sender that will send
+  it. The track is added asynchronously, after which this will send message
+  `t:rtp_sender_msg_t/0` with the same uuid, or `t:track_error_msg_t/0` when the
+  peer connection rejects the track.
+
+  The uuid only refers to an rtp sender once `t:rtp_sender_msg_t/0` has been sent. When
+  the track is rejected, or refused with `t:draining_error_msg_t/0` because the peer
+  connection is draining, no sender is ever created for it. The uuid may still be given
+  to functions such as `get_sender_parameters/3` right away, since they are handled
+  after the track has been added, and they return `{:error, :not_found}` when it was
+  not.
 
   ## Usage
 
@@ -224,21 +763,58 @@ defmodule Specter.PeerConnection do
       iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
       iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
       iex> assert_receive {:peer_connection_ready, ^pc}
-      iex> codec = %Specter.RtpCodecCapability{mime_type: "audio"}
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
       iex> {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
-      iex> :ok = Specter.PeerConnection.add_track(specter, pc, track)
-      iex> assert_receive {:rtp_sender, ^pc, ^track, _rtp_sender}
+      iex> {:ok, rtp_sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      iex> assert_receive {:rtp_sender, ^pc, ^track, ^rtp_sender}
       ...>
       iex> {:error, :invalid_track} = Specter.PeerConnection.add_track(specter, pc, "invalid_track")
   """
-  @spec add_track(Specter.t(), t(), Specter.TrackLocalStaticSample.t()) :: :ok | {:error | term()}
+  @spec add_track(
+          Specter.t(),
+          t(),
+          Specter.TrackLocalStaticSample.t() | Specter.TrackLocalStaticRTP.t()
+        ) :: {:ok, String.t()} | {:error, term()}
   def add_track(%Specter{native: ref}, pc, track) do
     Native.add_track(ref, pc, track)
   end
 
+  @doc """
+  Adds a transceiver for the given kind of media, negotiated in the given direction.
+  This declares a media section before any track is added, for instance to receive
+  media with `:recvonly`, or to control the order of media sections in an offer.
+  Media sections are offered in the order that their transceivers were added.
+
+  Sends back the uuid of the newly created transceiver, as `t:rtp_transceiver_msg_t/0`,
+  or `{:transceiver_error, peer_connection, reason}` when it cannot be added. Returns
+  `{:error, :invalid_atom}` when given an unknown kind or direction.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> :ok = Specter.PeerConnection.add_transceiver(specter, pc, :video, :recvonly)
+      iex> assert_receive {:rtp_transceiver, ^pc, _transceiver}
+      ...>
+      iex> Specter.PeerConnection.add_transceiver(specter, pc, :text, :recvonly)
+      {:error, :invalid_atom}
+  """
+  @spec add_transceiver(Specter.t(), t(), transceiver_kind_t(), transceiver_direction_t()) ::
+          :ok | {:error, term()}
+  def add_transceiver(%Specter{native: ref}, pc, kind, direction),
+    do: Native.add_transceiver(ref, pc, kind, direction)
+
   @doc """
   Sends back state of peer connection.
   This will send message `t:connection_state_msg_t/0`.
+
+  Note that transitions are also pushed to the owning process as they happen,
+  as `t:connection_state_change_msg_t/0`.
   """
   @spec connection_state(Specter.t(), t()) :: :ok | {:error, term()}
   def connection_state(%Specter{native: ref}, pc) do
@@ -248,6 +824,7 @@ defmodule Specter.PeerConnection do
   @doc """
   Given an RTCPeerConnection where the remote description has been assigned via
   `set_remote_description/4`, create an answer that can be passed to another connection.
+  Sends back `{:answer, peer_connection, json}`, or `t:answer_error_msg_t/0`.
 
   | param             | type                 | default |
   | ----------------- | -------------------- | ------- |
@@ -266,40 +843,206 @@ defmodule Specter.PeerConnection do
       )
 
   @doc """
-  Creates a data channel on an RTCPeerConnection.
-
-  Note: this can be useful when attempting to generate a valid offer, but where no media
-  tracks are expected to be sent or received. Callbacks from data channels have not yet
-  been implemented.
-  """
-  @spec create_data_channel(Specter.t(), t(), String.t()) :: :ok | {:error, term()}
-  def create_data_channel(%Specter{native: ref}, pc, label),
-    do: Native.create_data_channel(ref, pc, label)
-
-  @doc """
-  Given an RTCPeerConnection, create an offer that can be passed to another connection.
-
-  | param             | type                | default |
-  | ----------------- | ------------------- | ------- |
-  | `specter`         | `t()`               | |
-  | `peer_connection` | `opaque`            | |
-  | `options`         | `offer_options_t()` | voice_activity_detection: false |
-  |                   |                     | ice_restart: false |
+  Creates an answer as `create_answer/3` does, but returns its JSON instead of sending
+  it back, which saves a GenServer from correlating replies in its mailbox. This blocks
+  on a dirty scheduler until the answer has been created.
 
+  Returns `{:error, :not_found}` when the peer connection does not exist, and
+  `{:error, reason}` with the reason as a string when the answer cannot be created,
+  for instance when no remote description has been set.
   """
-  @spec create_offer(Specter.t(), t(), offer_options_t()) :: :ok | {:error, term()}
-  def create_offer(%Specter{native: ref}, pc, opts \\ []),
+  @spec create_answer_sync(Specter.t(), t(), answer_options_t()) ::
+          {:ok, String.t()} | {:error, term()}
+  def create_answer_sync(%Specter{native: ref}, pc, opts \\ []),
     do:
-      Native.create_offer(
+      Native.create_answer_sync(
         ref,
         pc,
-        Keyword.get(opts, :voice_activity_detection, false),
-        Keyword.get(opts, :ice_restart, false)
+        Keyword.get(opts, :voice_activity_detection, false)
       )
 
   @doc """
-  Sends back the value of the current session description on a peer connection. This will
-  send back JSON representing an offer or an answer when the peer connection has had
+  Creates a data channel on an RTCPeerConnection.
+
+  Sends back `t:data_channel_created_msg_t/0`, or `t:offer_error_msg_t/0` when webrtc
+  cannot create the channel. A channel created this way cannot be referred to in later
+  calls. Use `create_data_channel_sync/4` for the uuid identifying the channel.
+
+  Note: this can be useful when attempting to generate a valid offer, but where no media
+  tracks are expected to be sent or received. Callbacks from data channels have not yet
+  been implemented.
+
+  By default the channel is ordered and reliable. An unreliable channel, for instance
+  for low-latency application data, is created by giving either `max_retransmits` or
+  `max_packet_life_time` in milliseconds, often with `ordered: false`.
+
+  | option                 | type                  | default |
+  | ---------------------- | --------------------- | ------- |
+  | `ordered`              | `boolean()`           | `true` |
+  | `max_retransmits`      | `non_neg_integer()`   | unlimited |
+  | `max_packet_life_time` | `non_neg_integer()`   | unlimited |
+  | `protocol`             | `String.t()`          | `""` |
+  | `negotiated`           | `boolean()`           | `false` |
+  | `id`                   | `non_neg_integer()`   | |
+
+  A negotiated channel is not announced to the remote peer, which must create a channel
+  with the same `id`. `id` is required with `negotiated: true`, and only allowed with it.
+
+  Returns `{:error, :invalid_data_channel_options}` when an option has the wrong type,
+  when both `max_retransmits` and `max_packet_life_time` are given, or when only one of
+  `negotiated: true` and `id` is given.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> Specter.PeerConnection.create_data_channel(specter, pc, "state",
+      ...>   ordered: false,
+      ...>   max_retransmits: 0
+      ...> )
+      :ok
+      iex> assert_receive {:data_channel_created, ^pc}
+  """
+  @spec create_data_channel(Specter.t(), t(), String.t(), data_channel_options_t()) ::
+          :ok | {:error, term()}
+  def create_data_channel(%Specter{native: ref}, pc, label, opts \\ []),
+    do: Native.create_data_channel(ref, pc, label, Enum.into(opts, %{}))
+
+  @doc """
+  Creates a data channel as `create_data_channel/4` does, but returns the uuid that
+  identifies the channel in later calls, such as `data_channel_buffered_amount/3`.
+  `t:data_channel_buffered_low_msg_t/0` is sent with the same uuid. This blocks on a
+  dirty scheduler until the channel has been created.
+
+  Returns `{:error, :not_found}` when the peer connection does not exist,
+  `{:error, :draining}` once it is draining, and `{:error, reason}` with the reason as
+  a string when webrtc cannot create the channel. Options are as for
+  `create_data_channel/4`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> {:ok, _data_channel} =
+      ...>   Specter.PeerConnection.create_data_channel_sync(specter, pc, "data")
+      iex> refute_receive {:data_channel_created, ^pc}
+  """
+  @spec create_data_channel_sync(Specter.t(), t(), String.t(), data_channel_options_t()) ::
+          {:ok, String.t()} | {:error, term()}
+  def create_data_channel_sync(%Specter{native: ref}, pc, label, opts \\ []),
+    do: Native.create_data_channel_sync(ref, pc, label, Enum.into(opts, %{}))
+
+  @doc """
+  Given an RTCPeerConnection, create an offer that can be passed to another connection.
+  Sends back `{:offer, peer_connection, json}`, or `t:offer_error_msg_t/0`.
+
+  | param             | type                | default |
+  | ----------------- | ------------------- | ------- |
+  | `specter`         | `t()`               | |
+  | `peer_connection` | `opaque`            | |
+  | `options`         | `offer_options_t()` | voice_activity_detection: false |
+  |                   |                     | ice_restart: false |
+
+  """
+  @spec create_offer(Specter.t(), t(), offer_options_t()) :: :ok | {:error, term()}
+  def create_offer(%Specter{native: ref}, pc, opts \\ []),
+    do:
+      Native.create_offer(
+        ref,
+        pc,
+        Keyword.get(opts, :voice_activity_detection, false),
+        Keyword.get(opts, :ice_restart, false)
+      )
+
+  @doc """
+  Creates an offer as `create_offer/3` does, but only when the signaling state is
+  `:stable`. Otherwise returns `{:ok, :no_offer_needed}`, since an offer or answer is
+  already under way, and a second offer would only add to the glare. This is the check
+  that starts each round of perfect negotiation, for instance in response to
+  `:negotiation_needed`.
+
+  This assumes the perfect negotiation pattern, where exactly one of the two peers is
+  polite, and both pass their politeness to `set_remote_description/4`. An offer that
+  still collides with one from the remote peer is then resolved there: the polite peer
+  rolls its offer back, while the impolite peer ignores the remote offer.
+
+  The state is checked by the peer connection right before the offer is created, so
+  the check cannot race with descriptions being set. This blocks on a dirty scheduler
+  until then, while the offer is sent back as `{:offer, peer_connection, json}` or
+  `t:offer_error_msg_t/0`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> :ok = Specter.PeerConnection.create_offer_if_needed(specter, pc)
+      iex> assert_receive {:offer, ^pc, offer}
+      ...>
+      iex> :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      iex> assert_receive {:ok, ^pc, :set_local_description}
+      ...>
+      iex> Specter.PeerConnection.create_offer_if_needed(specter, pc)
+      {:ok, :no_offer_needed}
+  """
+  @spec create_offer_if_needed(Specter.t(), t(), offer_options_t()) ::
+          :ok | {:ok, :no_offer_needed} | {:error, term()}
+  def create_offer_if_needed(%Specter{native: ref}, pc, opts \\ []),
+    do:
+      Native.create_offer_if_needed(
+        ref,
+        pc,
+        Keyword.get(opts, :voice_activity_detection, false),
+        Keyword.get(opts, :ice_restart, false)
+      )
+
+  @doc """
+  Creates an offer as `create_offer/3` does, but returns its JSON instead of sending it
+  back. This blocks on a dirty scheduler until the offer has been created.
+
+  Returns `{:error, :not_found}` when the peer connection does not exist, and
+  `{:error, reason}` with the reason as a string when the offer cannot be created.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      iex> %{"type" => "offer"} = Jason.decode!(offer)
+  """
+  @spec create_offer_sync(Specter.t(), t(), offer_options_t()) ::
+          {:ok, String.t()} | {:error, term()}
+  def create_offer_sync(%Specter{native: ref}, pc, opts \\ []),
+    do:
+      Native.create_offer_sync(
+        ref,
+        pc,
+        Keyword.get(opts, :voice_activity_detection, false),
+        Keyword.get(opts, :ice_restart, false)
+      )
+
+  @doc """
+  Sends back the value of the current session description on a peer connection. This will
+  send back JSON representing an offer or an answer when the peer connection has had
   `set_local_description/3` called and has successfully negotiated ICE. In all other cases,
   `nil` will be sent.
 
@@ -346,7 +1089,10 @@ defmodule Specter.PeerConnection do
     do: Native.current_remote_description(ref, pc)
 
   @doc """
-  Sends back a JSON encoded string representing the current stats of a peer connection.
+  Returns the number of bytes queued to be sent on a data channel, for flow control
+  when sending large payloads. See `set_data_channel_buffered_amount_low_threshold/4`.
+
+  Returns `{:error, :not_found}` when the peer connection or data channel does not exist.
 
   ## Usage
 
@@ -357,41 +1103,97 @@ defmodule Specter.PeerConnection do
       iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
       iex> assert_receive {:peer_connection_ready, ^pc}
       ...>
-      iex> Specter.PeerConnection.get_stats(specter, pc)
-      :ok
-      iex> assert_receive {:stats, ^pc, json}
-      iex> {:ok, _stats} = Jason.decode(json)
+      iex> {:ok, data_channel} =
+      ...>   Specter.PeerConnection.create_data_channel_sync(specter, pc, "data")
+      iex> Specter.PeerConnection.data_channel_buffered_amount(specter, pc, data_channel)
+      {:ok, 0}
   """
-  @spec get_stats(Specter.t(), t()) :: :ok | {:error, term()}
-  def get_stats(%Specter{native: ref}, pc),
-    do: Native.get_stats(ref, pc)
+  @spec data_channel_buffered_amount(Specter.t(), t(), String.t()) ::
+          {:ok, non_neg_integer()} | {:error, term()}
+  def data_channel_buffered_amount(%Specter{native: ref}, pc, data_channel),
+    do: Native.data_channel_buffered_amount(ref, pc, data_channel)
 
   @doc """
-  Sends back state of ICE connection for given peer connection.
-  This will send message `t:ice_connection_state_msg_t/0`
+  Drains a peer connection before closing it, for removing a participant without
+  cutting off their media abruptly. Media keeps flowing on tracks that have already
+  been added, but new tracks, transceivers and data channels are refused with
+  `t:draining_error_msg_t/0`. Once the grace period has elapsed, the peer connection
+  is closed as with `close/2`.
+
+  Sends back `{:ok, peer_connection, :drain_peer_connection}`, followed by
+  `{:peer_connection_closed, peer_connection}` when the drain completes. Draining a
+  peer connection that is already draining does not extend its grace period.
+
+  | param             | type                 | default |
+  | ----------------- | -------------------- | ------- |
+  | `specter`         | `t()`                | |
+  | `peer_connection` | `opaque`             | |
+  | `options`         | `drain_options_t()`  | grace_period: 5_000 (ms) |
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> :ok = Specter.PeerConnection.drain(specter, pc, grace_period: 100)
+      iex> assert_receive {:ok, ^pc, :drain_peer_connection}
+      iex> :ok = Specter.PeerConnection.create_data_channel(specter, pc, "data")
+      iex> assert_receive {:draining_error, ^pc, :create_data_channel}
+      iex> assert_receive {:peer_connection_closed, ^pc}
+      ...>
+      iex> Specter.PeerConnection.exists?(specter, pc)
+      false
   """
-  @spec ice_connection_state(Specter.t(), t()) :: :ok | {:error, term()}
-  def ice_connection_state(%Specter{native: ref}, pc) do
-    Native.ice_connection_state(ref, pc)
-  end
+  @spec drain(Specter.t(), t(), drain_options_t()) :: :ok | {:error, term()}
+  def drain(%Specter{native: ref}, pc, opts \\ []),
+    do: Native.drain_peer_connection(ref, pc, Keyword.get(opts, :grace_period, 5_000))
 
   @doc """
-  Sends back state of ICE gathering process.
-  This will send message `t:ice_gathering_state_t/0`.
+  Returns the fingerprint of the local DTLS certificate of a peer connection. When a
+  `certificate` is given to `Specter.init/1`, this is the fingerprint of that certificate,
+  which stays the same across restarts. Otherwise, it belongs to a certificate generated
+  for the peer connection.
+
+  Services that advertise their identity out of band can send this fingerprint to
+  remote peers, to be compared with the `a=fingerprint` of session descriptions.
+
+  Returns `{:error, :not_found}` when the peer connection does not exist.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> {:ok, fingerprint} = Specter.PeerConnection.fingerprint(specter, pc)
+      iex> fingerprint.algorithm
+      "sha-256"
   """
-  @spec ice_gathering_state(Specter.t(), t()) :: :ok | {:error, term()}
-  def ice_gathering_state(%Specter{native: ref}, pc) do
-    Native.ice_gathering_state(ref, pc)
-  end
+  @spec fingerprint(Specter.t(), t()) :: {:ok, fingerprint_t()} | {:error, term()}
+  def fingerprint(%Specter{native: ref}, pc),
+    do: Native.peer_connection_fingerprint(ref, pc)
 
   @doc """
-  Sends back the value of the local session description on a peer connection. This will
-  send back JSON representing an offer or an answer when the peer connection has had
-  `set_local_description/3` called. If ICE has been successfully negotated, the current
-  local description will be sent back, otherwise the caller will receive the pending
-  local description.
+  Forwards the RTP packets of a remote track to local tracks, without sending them
+  through Elixir. This is the read and write loop of an SFU: each target is a
+  `Specter.TrackLocalStaticRTP` added to the peer connection of a viewer, with the
+  codec of the remote track.
 
-  See `current_local_description/2` and `pending_local_description/2`.
+  The remote track is identified by its id, as given by `get_receivers/2`, and
+  forwarding may start before the track arrives. The targets replace any the track
+  already had, and an empty list stops forwarding. A target deleted with
+  `Specter.delete_track/2` is skipped once no peer connection sends it, so viewers
+  may leave without forwarding being updated.
+
+  Returns `{:error, :invalid_track}` when a target is not a `Specter.TrackLocalStaticRTP`.
+  Otherwise, sends back `{:ok, peer_connection, :forward_track}`.
 
   ## Usage
 
@@ -402,26 +1204,54 @@ defmodule Specter.PeerConnection do
       iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
       iex> assert_receive {:peer_connection_ready, ^pc}
       ...>
-      iex> Specter.PeerConnection.local_description(specter, pc)
-      :ok
-      iex> assert_receive {:local_description, ^pc, nil}
-      ...>
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      iex> {:ok, relay} = Specter.TrackLocalStaticRTP.new(specter, codec, "video", "specter")
+      iex> :ok = Specter.PeerConnection.forward_track(specter, pc, "video", [relay])
+      iex> assert_receive {:ok, ^pc, :forward_track}
+  """
+  @spec forward_track(Specter.t(), t(), String.t(), [Specter.TrackLocalStaticRTP.t()]) ::
+          :ok | {:error, term()}
+  def forward_track(%Specter{native: ref}, pc, track_id, targets),
+    do: Native.forward_track(ref, pc, track_id, targets)
+
+  @doc """
+  Sends `{:local_description, peer_connection, session_description}` once ICE gathering
+  has finished, so that the local description includes all candidates. If gathering has
+  already finished, the local description is sent immediately.
+
+  This is meant for non-trickle ICE, in place of waiting for `gathering_complete_promise/2`
+  and then calling `local_description/2`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      iex> :ok = Specter.PeerConnection.create_data_channel(specter, pc, "data")
+      iex> assert_receive {:data_channel_created, ^pc}
       iex> :ok = Specter.PeerConnection.create_offer(specter, pc)
       iex> assert_receive {:offer, ^pc, offer}
       iex> :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
       iex> assert_receive {:ok, ^pc, :set_local_description}
       ...>
-      iex> Specter.PeerConnection.local_description(specter, pc)
-      :ok
-      iex> assert_receive {:local_description, ^pc, ^offer}
+      iex> :ok = Specter.PeerConnection.gathered_local_description(specter, pc)
+      iex> assert_receive {:local_description, ^pc, description}, 5_000
+      iex> description |> Jason.decode!() |> Map.get("sdp") =~ "a=candidate:"
+      true
   """
-  @spec local_description(Specter.t(), t()) :: :ok | {:error, term()}
-  def local_description(%Specter{native: ref}, pc),
-    do: Native.local_description(ref, pc)
+  @spec gathered_local_description(Specter.t(), t()) :: :ok | {:error, term()}
+  def gathered_local_description(%Specter{native: ref}, pc),
+    do: Native.gathered_local_description(ref, pc)
 
   @doc """
-  Sends back the value of the session description on a peer connection that is pending
-  connection, or nil.
+  Sends `{:gathering_complete, peer_connection}` once ICE gathering has finished. If
+  gathering has already finished, the message is sent immediately.
+
+  For non-trickle ICE, call this after `set_local_description/3` and wait for the message
+  before reading `local_description/2`, which will then include all candidates.
 
   ## Usage
 
@@ -431,72 +1261,80 @@ defmodule Specter.PeerConnection do
       iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
       iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
       iex> assert_receive {:peer_connection_ready, ^pc}
-      ...>
-      iex> Specter.PeerConnection.pending_local_description(specter, pc)
-      :ok
-      iex> assert_receive {:pending_local_description, ^pc, nil}
-      ...>
+      iex> :ok = Specter.PeerConnection.create_data_channel(specter, pc, "data")
+      iex> assert_receive {:data_channel_created, ^pc}
       iex> :ok = Specter.PeerConnection.create_offer(specter, pc)
       iex> assert_receive {:offer, ^pc, offer}
       iex> :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
       iex> assert_receive {:ok, ^pc, :set_local_description}
       ...>
-      iex> Specter.PeerConnection.pending_local_description(specter, pc)
-      :ok
-      iex> assert_receive {:pending_local_description, ^pc, ^offer}
+      iex> :ok = Specter.PeerConnection.gathering_complete_promise(specter, pc)
+      iex> assert_receive {:gathering_complete, ^pc}, 5_000
   """
-  @spec pending_local_description(Specter.t(), t()) :: :ok | {:error, term()}
-  def pending_local_description(%Specter{native: ref}, pc),
-    do: Native.pending_local_description(ref, pc)
+  @spec gathering_complete_promise(Specter.t(), t()) :: :ok | {:error, term()}
+  def gathering_complete_promise(%Specter{native: ref}, pc),
+    do: Native.gathering_complete_promise(ref, pc)
 
   @doc """
-  Sends back the value of the remote session description on a peer connection on a peer
-  that is pending connection, or nil.
+  Returns the configuration in effect on a peer connection, as seen by webrtc. This
+  can be used to verify which ICE servers a peer connection ended up with.
 
-  See `current_remote_description/2` and `pending_remote_description/2`.
+  Returns `{:error, :not_found}` when the peer connection does not exist.
 
   ## Usage
 
-      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, specter} = Specter.init(ice_servers: ["stun:stun.l.google.com:19302"])
       iex> {:ok, media_engine} = Specter.new_media_engine(specter)
       iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
       iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
-      iex> {:ok, pc_offer} = Specter.PeerConnection.new(specter, api)
-      iex> assert_receive {:peer_connection_ready, ^pc_offer}
-      iex> :ok = Specter.PeerConnection.create_data_channel(specter, pc_offer, "foo")
-      iex> assert_receive {:data_channel_created, ^pc_offer}
-      iex> :ok = Specter.PeerConnection.create_offer(specter, pc_offer)
-      iex> assert_receive {:offer, ^pc_offer, offer}
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
       ...>
+      iex> {:ok, config} = Specter.PeerConnection.get_configuration(specter, pc)
+      iex> config.ice_servers
+      ["stun:stun.l.google.com:19302"]
+  """
+  @spec get_configuration(Specter.t(), t()) :: {:ok, configuration_t()} | {:error, term()}
+  def get_configuration(%Specter{native: ref}, pc),
+    do: Native.get_peer_connection_configuration(ref, pc)
+
+  @doc """
+  Returns the frame rate, number of frames sent and frame resolution of the video sent
+  by an RTP sender, as reported by its outbound-rtp stats. Unlike `get_stats/2`, this
+  returns its result directly.
+
+  Returns `{:error, :not_found}` when the peer connection or the sender does not exist.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
       iex> {:ok, media_engine} = Specter.new_media_engine(specter)
       iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
       iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
-      iex> {:ok, pc_answer} = Specter.PeerConnection.new(specter, api)
-      iex> assert_receive {:peer_connection_ready, ^pc_answer}
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
       ...>
-      iex> Specter.PeerConnection.pending_remote_description(specter, pc_answer)
-      :ok
-      iex> assert_receive {:pending_remote_description, ^pc_answer, nil}
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+      iex> {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      iex> {:ok, _rtp_sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      iex> assert_receive {:rtp_sender, ^pc, ^track, rtp_sender}
       ...>
-      iex> :ok = Specter.PeerConnection.set_remote_description(specter, pc_answer, offer)
-      iex> assert_receive {:ok, ^pc_answer, :set_remote_description}
+      iex> {:ok, stats} = Specter.PeerConnection.get_outbound_video_stats(specter, pc, rtp_sender)
+      iex> stats.frames_sent
+      nil
       ...>
-      iex> Specter.PeerConnection.pending_remote_description(specter, pc_answer)
-      :ok
-      iex> assert_receive {:pending_remote_description, ^pc_answer, ^offer}
+      iex> Specter.PeerConnection.get_outbound_video_stats(specter, pc, "invalid")
+      {:error, :not_found}
   """
-  @spec pending_remote_description(Specter.t(), t()) :: :ok | {:error, term()}
-  def pending_remote_description(%Specter{native: ref}, pc),
-    do: Native.pending_remote_description(ref, pc)
+  @spec get_outbound_video_stats(Specter.t(), t(), String.t()) ::
+          {:ok, outbound_video_stats_t()} | {:error, term()}
+  def get_outbound_video_stats(%Specter{native: ref}, pc, sender),
+    do: Native.get_outbound_video_stats(ref, pc, sender)
 
   @doc """
-  Sends back the value of the remote session description on a peer connection. This will
-  send back JSON representing an offer or an answer when the peer connection has had
-  `set_remote_description/3` called. If ICE has been successfully negotated, the current
-  remote description will be sent back, otherwise the caller will receive the pending
-  remote description.
-
-  See `current_remote_description/2` and `remote_description/2`.
+  Sends back the RTP receiver of each transceiver on the peer connection, as
+  `t:receivers_msg_t/0`. The `mid` of each receiver correlates incoming tracks with
+  the media sections of the session description.
 
   ## Usage
 
@@ -504,14 +1342,295 @@ defmodule Specter.PeerConnection do
       iex> {:ok, media_engine} = Specter.new_media_engine(specter)
       iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
       iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
-      iex> {:ok, pc_offer} = Specter.PeerConnection.new(specter, api)
-      iex> assert_receive {:peer_connection_ready, ^pc_offer}
-      iex> :ok = Specter.PeerConnection.create_data_channel(specter, pc_offer, "foo")
-      iex> assert_receive {:data_channel_created, ^pc_offer}
-      iex> :ok = Specter.PeerConnection.create_offer(specter, pc_offer)
-      iex> assert_receive {:offer, ^pc_offer, offer}
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
       ...>
-      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> :ok = Specter.PeerConnection.add_transceiver(specter, pc, :video, :recvonly)
+      iex> assert_receive {:rtp_transceiver, ^pc, _transceiver}
+      ...>
+      iex> :ok = Specter.PeerConnection.get_receivers(specter, pc)
+      iex> assert_receive {:receivers, ^pc, [%{kind: :video, tracks: _tracks}]}
+  """
+  @spec get_receivers(Specter.t(), t()) :: :ok | {:error, term()}
+  def get_receivers(%Specter{native: ref}, pc),
+    do: Native.get_receivers(ref, pc)
+
+  @doc """
+  Sends back the local and remote candidates of the candidate pair selected by ICE, as
+  `t:selected_candidate_pair_msg_t/0`. This tells whether media flows directly between
+  hosts, through a NAT mapping found by STUN, or through a TURN relay. The pair is
+  read from the stats of the peer connection, and is `nil` until ICE has selected one.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> :ok = Specter.PeerConnection.get_selected_candidate_pair(specter, pc)
+      iex> assert_receive {:selected_candidate_pair, ^pc, nil}
+  """
+  @spec get_selected_candidate_pair(Specter.t(), t()) :: :ok | {:error, term()}
+  def get_selected_candidate_pair(%Specter{native: ref}, pc),
+    do: Native.get_selected_candidate_pair(ref, pc)
+
+  @doc """
+  Returns the encodings, codecs and RTP header extensions of an RTP sender, as
+  `t:send_parameters_t/0`. These reflect negotiation once the remote description has
+  been set. Unlike `get_senders/2`, this returns its result directly.
+
+  Returns `{:error, :not_found}` when the peer connection or the sender does not exist.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      iex> {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      iex> {:ok, _rtp_sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      iex> assert_receive {:rtp_sender, ^pc, ^track, rtp_sender}
+      ...>
+      iex> {:ok, params} = Specter.PeerConnection.get_sender_parameters(specter, pc, rtp_sender)
+      iex> [%{ssrc: _ssrc}] = params.encodings
+      ...>
+      iex> Specter.PeerConnection.get_sender_parameters(specter, pc, "invalid")
+      {:error, :not_found}
+  """
+  @spec get_sender_parameters(Specter.t(), t(), String.t()) ::
+          {:ok, send_parameters_t()} | {:error, term()}
+  def get_sender_parameters(%Specter{native: ref}, pc, sender),
+    do: Native.get_sender_parameters(ref, pc, sender)
+
+  @doc """
+  Sends back a summary of each RTP sender created by `add_track/3`, as
+  `t:senders_msg_t/0`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      iex> {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      iex> {:ok, _rtp_sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      iex> assert_receive {:rtp_sender, ^pc, ^track, rtp_sender}
+      ...>
+      iex> :ok = Specter.PeerConnection.get_senders(specter, pc)
+      iex> assert_receive {:senders, ^pc, [%{id: ^rtp_sender, track_id: "audio", kind: :audio}]}
+  """
+  @spec get_senders(Specter.t(), t()) :: :ok | {:error, term()}
+  def get_senders(%Specter{native: ref}, pc),
+    do: Native.get_senders(ref, pc)
+
+  @doc """
+  Sends back a JSON encoded string representing the current stats of a peer connection.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> Specter.PeerConnection.get_stats(specter, pc)
+      :ok
+      iex> assert_receive {:stats, ^pc, json}
+      iex> {:ok, _stats} = Jason.decode(json)
+  """
+  @spec get_stats(Specter.t(), t()) :: :ok | {:error, term()}
+  def get_stats(%Specter{native: ref}, pc),
+    do: Native.get_stats(ref, pc)
+
+  @doc """
+  Returns the stats of a single track as a JSON encoded string, holding only the
+  inbound-rtp and outbound-rtp reports of the track, keyed by id as in `get_stats/2`.
+  The track is either a local track sent by the peer connection, or a remote track
+  given by its id. Its SSRCs are looked up from the senders or receivers of the track.
+  Unlike `get_stats/2`, this returns its result directly.
+
+  Returns `{:ok, nil}` when the track has no stats yet, and `{:error, :not_found}` when
+  the peer connection does not exist, or neither sends nor receives the track.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> Specter.PeerConnection.get_track_stats(specter, pc, "audio")
+      {:error, :not_found}
+  """
+  @spec get_track_stats(Specter.t(), t(), String.t()) ::
+          {:ok, String.t() | nil} | {:error, term()}
+  def get_track_stats(%Specter{native: ref}, pc, track),
+    do: Native.get_track_stats(ref, pc, track)
+
+  @doc """
+  Sends back state of ICE connection for given peer connection.
+  This will send message `t:ice_connection_state_msg_t/0`
+
+  Note that transitions are also pushed to the owning process as they happen,
+  as `t:ice_connection_state_change_msg_t/0`.
+  """
+  @spec ice_connection_state(Specter.t(), t()) :: :ok | {:error, term()}
+  def ice_connection_state(%Specter{native: ref}, pc) do
+    Native.ice_connection_state(ref, pc)
+  end
+
+  @doc """
+  Sends back state of ICE gathering process.
+  This will send message `t:ice_gathering_state_t/0`.
+  """
+  @spec ice_gathering_state(Specter.t(), t()) :: :ok | {:error, term()}
+  def ice_gathering_state(%Specter{native: ref}, pc) do
+    Native.ice_gathering_state(ref, pc)
+  end
+
+  @doc """
+  Sends back the value of the local session description on a peer connection. This will
+  send back JSON representing an offer or an answer when the peer connection has had
+  `set_local_description/3` called. If ICE has been successfully negotated, the current
+  local description will be sent back, otherwise the caller will receive the pending
+  local description.
+
+  See `current_local_description/2` and `pending_local_description/2`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> Specter.PeerConnection.local_description(specter, pc)
+      :ok
+      iex> assert_receive {:local_description, ^pc, nil}
+      ...>
+      iex> :ok = Specter.PeerConnection.create_offer(specter, pc)
+      iex> assert_receive {:offer, ^pc, offer}
+      iex> :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      iex> assert_receive {:ok, ^pc, :set_local_description}
+      ...>
+      iex> Specter.PeerConnection.local_description(specter, pc)
+      :ok
+      iex> assert_receive {:local_description, ^pc, ^offer}
+  """
+  @spec local_description(Specter.t(), t()) :: :ok | {:error, term()}
+  def local_description(%Specter{native: ref}, pc),
+    do: Native.local_description(ref, pc)
+
+  @doc """
+  Sends back the value of the session description on a peer connection that is pending
+  connection, or nil.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> Specter.PeerConnection.pending_local_description(specter, pc)
+      :ok
+      iex> assert_receive {:pending_local_description, ^pc, nil}
+      ...>
+      iex> :ok = Specter.PeerConnection.create_offer(specter, pc)
+      iex> assert_receive {:offer, ^pc, offer}
+      iex> :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      iex> assert_receive {:ok, ^pc, :set_local_description}
+      ...>
+      iex> Specter.PeerConnection.pending_local_description(specter, pc)
+      :ok
+      iex> assert_receive {:pending_local_description, ^pc, ^offer}
+  """
+  @spec pending_local_description(Specter.t(), t()) :: :ok | {:error, term()}
+  def pending_local_description(%Specter{native: ref}, pc),
+    do: Native.pending_local_description(ref, pc)
+
+  @doc """
+  Sends back the value of the remote session description on a peer connection on a peer
+  that is pending connection, or nil.
+
+  See `current_remote_description/2` and `pending_remote_description/2`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc_offer} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc_offer}
+      iex> :ok = Specter.PeerConnection.create_data_channel(specter, pc_offer, "foo")
+      iex> assert_receive {:data_channel_created, ^pc_offer}
+      iex> :ok = Specter.PeerConnection.create_offer(specter, pc_offer)
+      iex> assert_receive {:offer, ^pc_offer, offer}
+      ...>
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc_answer} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc_answer}
+      ...>
+      iex> Specter.PeerConnection.pending_remote_description(specter, pc_answer)
+      :ok
+      iex> assert_receive {:pending_remote_description, ^pc_answer, nil}
+      ...>
+      iex> :ok = Specter.PeerConnection.set_remote_description(specter, pc_answer, offer)
+      iex> assert_receive {:ok, ^pc_answer, :set_remote_description}
+      ...>
+      iex> Specter.PeerConnection.pending_remote_description(specter, pc_answer)
+      :ok
+      iex> assert_receive {:pending_remote_description, ^pc_answer, ^offer}
+  """
+  @spec pending_remote_description(Specter.t(), t()) :: :ok | {:error, term()}
+  def pending_remote_description(%Specter{native: ref}, pc),
+    do: Native.pending_remote_description(ref, pc)
+
+  @doc """
+  Sends back the value of the remote session description on a peer connection. This will
+  send back JSON representing an offer or an answer when the peer connection has had
+  `set_remote_description/3` called. If ICE has been successfully negotated, the current
+  remote description will be sent back, otherwise the caller will receive the pending
+  remote description.
+
+  See `current_remote_description/2` and `remote_description/2`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc_offer} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc_offer}
+      iex> :ok = Specter.PeerConnection.create_data_channel(specter, pc_offer, "foo")
+      iex> assert_receive {:data_channel_created, ^pc_offer}
+      iex> :ok = Specter.PeerConnection.create_offer(specter, pc_offer)
+      iex> assert_receive {:offer, ^pc_offer, offer}
+      ...>
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
       iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
       iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
       iex> {:ok, pc_answer} = Specter.PeerConnection.new(specter, api)
@@ -532,6 +1651,320 @@ defmodule Specter.PeerConnection do
   def remote_description(%Specter{native: ref}, pc),
     do: Native.remote_description(ref, pc)
 
+  @doc """
+  Records a remote track to a file, in a format that depends on the codec of the track:
+
+  | codec  | format  |
+  | ------ | ------- |
+  | `H264` | Annex-B, as read by `Specter.TrackLocalStaticSample.play_from_file_h264/4` |
+  | `VP8`  | IVF, as read by `Specter.TrackLocalStaticSample.play_vp8_from_file/3` |
+  | `opus` | Ogg, as read by `Specter.TrackLocalStaticSample.play_audio_from_file/3` |
+
+  Video is recorded from the next keyframe, and Opus from the next packet. The file is
+  overwritten if it exists. When the track has any other codec,
+  `t:recording_error_msg_t/0` is sent once the track arrives, and nothing is recorded.
+
+  The track is identified by its id, as given by `get_receivers/2`, and recording may
+  start before the track arrives. Recording a track that is already being recorded
+  moves the recording to the new file. Once the track ends, the file is closed and
+  `t:recording_finished_msg_t/0` is sent.
+
+  Sends back `{:ok, peer_connection, :record_to_file}`. Returns `{:error, reason}` as a
+  string when the file cannot be created.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> path = Path.join(System.tmp_dir!(), "specter_doctest.h264")
+      iex> :ok = Specter.PeerConnection.record_to_file(specter, pc, "video", path)
+      iex> assert_receive {:ok, ^pc, :record_to_file}
+  """
+  @spec record_to_file(Specter.t(), t(), String.t(), Path.t()) :: :ok | {:error, term()}
+  def record_to_file(%Specter{native: ref}, pc, track_id, path),
+    do: Native.record_to_file(ref, pc, track_id, path)
+
+  @doc """
+  Replaces the track sent by an RTP sender without renegotiating, for instance to switch
+  from a camera to a screen share. Passing `nil` detaches the current track, after which
+  the sender stops sending until it is given another track.
+
+  The sender must have been returned by `add_track/3`. The new track must be of the same
+  kind as the track it replaces, and once the connection has been negotiated, its codec
+  must be one accepted by the remote peer.
+
+  Returns `{:error, :not_found}` when the peer connection or sender do not exist,
+  `{:error, :invalid_track}` when the track does not exist, `{:error, :incompatible_track}`
+  when the track's kind or codec does not match the sender, and `{:error, reason}` as a
+  string when webrtc fails to replace the track otherwise.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      iex> {:ok, camera} = Specter.TrackLocalStaticSample.new(specter, codec, "camera", "specter")
+      iex> {:ok, screen} = Specter.TrackLocalStaticSample.new(specter, codec, "screen", "specter")
+      iex> {:ok, sender} = Specter.PeerConnection.add_track(specter, pc, camera)
+      iex> Specter.PeerConnection.replace_track(specter, pc, sender, screen)
+      :ok
+  """
+  @spec replace_track(
+          Specter.t(),
+          t(),
+          String.t(),
+          Specter.TrackLocalStaticSample.t() | Specter.TrackLocalStaticRTP.t() | nil
+        ) :: :ok | {:error, term()}
+  def replace_track(%Specter{native: ref}, pc, sender, track),
+    do: Native.replace_track(ref, pc, sender, track)
+
+  @doc """
+  Asks the remote peer to send a keyframe on one of its tracks, by sending a Picture
+  Loss Indication (PLI) for the track's SSRC. This is useful when forwarding a track
+  to a new viewer, who cannot decode anything until the next keyframe arrives.
+
+  The track is identified by its id, as sent back by `get_receivers/2`. Sends back
+  `{:ok, peer_connection, :request_keyframe}`, or
+  `{:keyframe_error, peer_connection, reason}` where `reason` is `:not_found` when no
+  remote track has the id, `:not_connected` before the peer connection has connected,
+  or a string when the PLI cannot be written.
+  """
+  @spec request_keyframe(Specter.t(), t(), String.t()) :: :ok | {:error, term()}
+  def request_keyframe(%Specter{native: ref}, pc, track_id),
+    do: Native.request_keyframe(ref, pc, track_id)
+
+  @doc """
+  Restarts ICE, for instance to reconnect after a network change. As with
+  `restartIce()` in browsers, the next offer created by `create_offer/3` or
+  `create_offer_sync/3` restarts ICE as though it were given `ice_restart: true`,
+  with new ICE credentials. The restart takes effect once that offer has been
+  negotiated with the remote peer. Before a local description has been set there
+  is nothing to restart, and this has no effect.
+
+  Sends back `{:ok, peer_connection, :restart_ice}`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> Specter.PeerConnection.restart_ice(specter, pc)
+      :ok
+      iex> assert_receive {:ok, ^pc, :restart_ice}
+  """
+  @spec restart_ice(Specter.t(), t()) :: :ok | {:error, term()}
+  def restart_ice(%Specter{native: ref}, pc), do: Native.restart_ice(ref, pc)
+
+  @doc """
+  Returns the state and limits of the SCTP transport carrying the data channels of a
+  peer connection, as `t:sctp_transport_t/0`. Messages larger than `max_message_size`
+  cannot be sent, or may be refused by the remote peer, so larger payloads must be split
+  by the application. The transport is `:connecting` until the peer connection has
+  connected with a data channel negotiated.
+
+  Returns `{:error, :not_found}` when the peer connection does not exist.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> {:ok, %{max_message_size: max_message_size}} =
+      ...>   Specter.PeerConnection.sctp_transport(specter, pc)
+      iex> max_message_size
+      65536
+  """
+  @spec sctp_transport(Specter.t(), t()) :: {:ok, sctp_transport_t()} | {:error, term()}
+  def sctp_transport(%Specter{native: ref}, pc), do: Native.sctp_transport(ref, pc)
+
+  @doc """
+  Returns true or false, depending on whether the RTP sender currently has a track
+  attached. A sender whose track has been removed or replaced with nothing is not
+  active. Raises when the peer connection or sender cannot be found.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      iex> {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      iex> {:ok, _rtp_sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      iex> assert_receive {:rtp_sender, ^pc, ^track, rtp_sender}
+      ...>
+      iex> Specter.PeerConnection.sender_active?(specter, pc, rtp_sender)
+      true
+  """
+  @spec sender_active?(Specter.t(), t(), String.t()) :: boolean() | no_return()
+  def sender_active?(%Specter{native: ref}, pc, sender) do
+    case Native.sender_active(ref, pc, sender) do
+      {:ok, value} ->
+        value
+
+      {:error, error} ->
+        raise "Unable to determine whether sender is active:\n#{inspect(error)}"
+    end
+  end
+
+  @doc """
+  Sends back the bytes and packets sent by an RTP sender, as `t:sender_stats_msg_t/0`.
+  Only the sender's outbound-rtp stats are read, so this is a lighter check than
+  `get_stats/2` for whether media is flowing.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      iex> {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      iex> {:ok, _rtp_sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      iex> assert_receive {:rtp_sender, ^pc, ^track, rtp_sender}
+      ...>
+      iex> :ok = Specter.PeerConnection.sender_stats(specter, pc, rtp_sender)
+      iex> assert_receive {:sender_stats, ^pc, ^rtp_sender, %{bytes_sent: 0, packets_sent: 0}}
+      ...>
+      iex> :ok = Specter.PeerConnection.sender_stats(specter, pc, "invalid")
+      iex> assert_receive {:sender_stats, ^pc, "invalid", nil}
+  """
+  @spec sender_stats(Specter.t(), t(), String.t()) :: :ok | {:error, term()}
+  def sender_stats(%Specter{native: ref}, pc, sender),
+    do: Native.sender_stats(ref, pc, sender)
+
+  @doc """
+  Sets the codecs negotiated for a transceiver, in order of preference, so that for
+  example H264 can be preferred over VP8 for a single video stream without removing
+  codecs from the media engine. The transceiver is given by its mid, which it is
+  assigned once a local or remote description has been set, so the preferences take
+  effect on the next offer or answer.
+
+  Payload types are taken from the media engine. Each codec is matched by its
+  `mime_type`, and by its `sdp_fmtp_line` when one is given.
+
+  Returns `{:error, :not_found}` when the peer connection or a transceiver with the mid
+  does not exist, and `{:error, :unsupported_codec}` when any codec is not registered
+  on the media engine for the kind of the transceiver.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> :ok = Specter.PeerConnection.add_transceiver(specter, pc, :video, :recvonly)
+      iex> {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      iex> :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      iex> assert_receive {:ok, ^pc, :set_local_description}
+      ...>
+      iex> h264 = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+      iex> Specter.PeerConnection.set_codec_preferences(specter, pc, "0", [h264])
+      :ok
+  """
+  @spec set_codec_preferences(Specter.t(), t(), String.t(), [Specter.RtpCodecCapability.t()]) ::
+          :ok | {:error, term()}
+  def set_codec_preferences(%Specter{native: ref}, pc, mid, codecs),
+    do: Native.set_codec_preferences(ref, pc, mid, codecs)
+
+  @doc """
+  Sets the number of bytes below which the queue of a data channel must drop for
+  `{:data_channel_buffered_low, peer_connection, data_channel}` to be sent. The message
+  is sent each time the queue drops below the threshold, so a sender can pause once
+  `data_channel_buffered_amount/3` grows too large, and resume on the message. The
+  threshold starts at 0.
+
+  Returns `{:error, :not_found}` when the peer connection or data channel does not exist.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> {:ok, data_channel} =
+      ...>   Specter.PeerConnection.create_data_channel_sync(specter, pc, "data")
+      iex> Specter.PeerConnection.set_data_channel_buffered_amount_low_threshold(
+      ...>   specter,
+      ...>   pc,
+      ...>   data_channel,
+      ...>   65_536
+      ...> )
+      :ok
+  """
+  @spec set_data_channel_buffered_amount_low_threshold(
+          Specter.t(),
+          t(),
+          String.t(),
+          non_neg_integer()
+        ) :: :ok | {:error, term()}
+  def set_data_channel_buffered_amount_low_threshold(
+        %Specter{native: ref},
+        pc,
+        data_channel,
+        bytes
+      ),
+      do: Native.set_data_channel_buffered_amount_low_threshold(ref, pc, data_channel, bytes)
+
+  @doc """
+  Caps the bitrate, in bits per second, at which the remote peer should send media to
+  this peer connection. While set, a REMB (Receiver Estimated Maximum Bitrate) packet
+  covering all incoming tracks is sent to the remote once per second. Setting a new cap
+  replaces the previous one.
+
+  Sends back `{:ok, peer_connection, :set_receive_bitrate_cap}`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> :ok = Specter.PeerConnection.set_receive_bitrate_cap(specter, pc, 500_000)
+      iex> assert_receive {:ok, ^pc, :set_receive_bitrate_cap}
+      iex> :ok = Specter.PeerConnection.clear_receive_bitrate_cap(specter, pc)
+      iex> assert_receive {:ok, ^pc, :clear_receive_bitrate_cap}
+      ...>
+      iex> Specter.PeerConnection.set_receive_bitrate_cap(specter, pc, 0)
+      {:error, :invalid_bitrate}
+  """
+  @spec set_receive_bitrate_cap(Specter.t(), t(), pos_integer()) :: :ok | {:error, term()}
+  def set_receive_bitrate_cap(%Specter{native: ref}, pc, bps) when is_integer(bps) and bps > 0,
+    do: Native.set_receive_bitrate_cap(ref, pc, bps)
+
+  def set_receive_bitrate_cap(%Specter{}, _pc, _bps), do: {:error, :invalid_bitrate}
+
   @doc """
   Given an offer or an answer session description, sets the local description on
   a peer connection. The description should be in the form of JSON with the keys
@@ -542,9 +1975,28 @@ defmodule Specter.PeerConnection do
   | `specter`         | `t:t/0`                     | |
   | `peer_connection` | `opaque`                    | |
   | `description`     | `t:session_description_t()` | |
+
+  The SDP is parsed before it is set, so that SDP munged between `create_offer/3` and
+  this call is refused with `{:error, {:invalid_sdp, sdp_error}}`, pointing at the line
+  that fails to parse, as `t:sdp_error_t/0`. SDP that parses but cannot be applied is
+  sent back as `{:invalid_local_description, peer_connection, reason}`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      iex> munged = String.replace(offer, "s=-", "s=-\\\\r\\\\nbogus")
+      iex> {:error, {:invalid_sdp, %{line: 4, section: nil}}} =
+      ...>   Specter.PeerConnection.set_local_description(specter, pc, munged)
   """
   @spec set_local_description(Specter.t(), t(), session_description_t()) ::
-          :ok | {:error, term()}
+          :ok | {:error, {:invalid_sdp, sdp_error_t()}} | {:error, term()}
   def set_local_description(%Specter{native: ref}, pc, description),
     do: Native.set_local_description(ref, pc, description)
 
@@ -553,18 +2005,107 @@ defmodule Specter.PeerConnection do
   the remote description on a peer connection. Expects a session description in the
   form of JSON with the keys `type` and `sdp`.
 
-  | param             | type                        | default |
-  | ----------------- | --------------------------- | ------- |
-  | `specter`         | `t:t/0`                     | |
-  | `peer_connection` | `opaque`                    | |
-  | `description`     | `t:session_description_t/0` | |
+  | param             | type                                | default |
+  | ----------------- | ----------------------------------- | ------- |
+  | `specter`         | `t:t/0`                             | |
+  | `peer_connection` | `opaque`                            | |
+  | `description`     | `t:session_description_t/0`         | |
+  | `options`         | `t:remote_description_options_t/0`  | |
+
+  ## Glare
+
+  When both peers send offers at the same time, an offer arrives while the local offer
+  is still pending. Passing `polite: true` or `polite: false` resolves this following the
+  perfect negotiation pattern, where exactly one of the two peers is polite:
+
+  - a polite peer rolls back its local offer and applies the remote offer, sending
+    `{:glare, peer_connection, :rolled_back}` before the usual reply.
+  - an impolite peer ignores the remote offer, sending only
+    `{:glare, peer_connection, :ignored}`.
+
+  Without the option, the remote offer is applied as is, and fails.
+
+  ## Plain SDP
+
+  When the fourth argument is an SDP type rather than options, `description` is taken
+  to be raw SDP text of that type, as with `set_remote_sdp/5`. The type is one of
+  `:offer`, `:answer`, `:pranswer` or `:rollback`.
+
+      :ok = Specter.PeerConnection.set_remote_description(specter, pc, sdp, :offer)
   """
-  @spec set_remote_description(Specter.t(), t(), session_description_t()) ::
+  @spec set_remote_description(
+          Specter.t(),
+          t(),
+          session_description_t() | sdp_t(),
+          remote_description_options_t() | remote_sdp_type_t()
+        ) ::
           :ok | {:error, term()}
-  def set_remote_description(%Specter{native: ref}, pc, description) do
-    Native.set_remote_description(ref, pc, description)
+  def set_remote_description(specter, pc, description, opts_or_sdp_type \\ [])
+
+  def set_remote_description(%Specter{} = specter, pc, sdp, sdp_type) when is_atom(sdp_type),
+    do: set_remote_sdp(specter, pc, sdp_type, sdp)
+
+  def set_remote_description(%Specter{native: ref}, pc, description, opts) do
+    Native.set_remote_description(ref, pc, description, Keyword.get(opts, :polite))
+  end
+
+  @doc """
+  Sets the remote description on a peer connection from an SDP type and the raw SDP
+  text, for signaling servers that exchange plain SDP rather than JSON. Otherwise
+  behaves like `set_remote_description/4`, including the `polite` option.
+
+  | param             | type                                | default |
+  | ----------------- | ----------------------------------- | ------- |
+  | `specter`         | `t:t/0`                             | |
+  | `peer_connection` | `opaque`                            | |
+  | `sdp_type`        | `t:remote_sdp_type_t/0`             | |
+  | `sdp`             | `t:sdp_t/0`                         | |
+  | `options`         | `t:remote_description_options_t/0`  | |
+
+  Returns `{:error, :invalid_atom}` for any other type, and
+  `{:error, :invalid_session_description}` when the SDP cannot be parsed.
+  """
+  @spec set_remote_sdp(
+          Specter.t(),
+          t(),
+          remote_sdp_type_t(),
+          sdp_t(),
+          remote_description_options_t()
+        ) ::
+          :ok | {:error, term()}
+  def set_remote_sdp(%Specter{native: ref}, pc, sdp_type, sdp, opts \\ []) do
+    Native.set_remote_sdp(ref, pc, sdp_type, sdp, Keyword.get(opts, :polite))
   end
 
+  @doc """
+  Starts or stops forwarding the RTP packets of a remote track to the owning process,
+  as `t:rtp_msg_t/0` messages. This is the building block of an SFU, which can write the
+  packets to a `Specter.TrackLocalStaticRTP` on other peer connections.
+
+  Packets are only forwarded for tracks that ask for it, since a video track can send
+  hundreds of packets per second. The track is identified by its id, as given by
+  `get_receivers/2`, and forwarding may be enabled before the track arrives.
+
+  Sends back `{:ok, peer_connection, :set_rtp_forwarding}`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> :ok = Specter.PeerConnection.set_rtp_forwarding(specter, pc, "video", true)
+      iex> assert_receive {:ok, ^pc, :set_rtp_forwarding}
+      iex> :ok = Specter.PeerConnection.set_rtp_forwarding(specter, pc, "video", false)
+      iex> assert_receive {:ok, ^pc, :set_rtp_forwarding}
+  """
+  @spec set_rtp_forwarding(Specter.t(), t(), String.t(), boolean()) :: :ok | {:error, term()}
+  def set_rtp_forwarding(%Specter{native: ref}, pc, track_id, enabled),
+    do: Native.set_rtp_forwarding(ref, pc, track_id, enabled)
+
   @doc """
   Sends back state of session parameters negotiation.
   This will send message `t:signaling_state_msg_t/0`.
@@ -573,4 +2114,164 @@ defmodule Specter.PeerConnection do
   def signaling_state(%Specter{native: ref}, pc) do
     Native.signaling_state(ref, pc)
   end
+
+  @doc """
+  Sends back the states of a peer connection, how many of its senders and receivers are
+  in use, and the round trip time of its selected candidate pair, all in one
+  `t:snapshot_msg_t/0`. This is lighter than `get_stats/2` for health checks and
+  dashboards, and saves asking for each state separately.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> :ok = Specter.PeerConnection.snapshot(specter, pc)
+      iex> assert_receive {:snapshot, ^pc, %{connection_state: :new, round_trip_time: nil}}
+  """
+  @spec snapshot(Specter.t(), t()) :: :ok | {:error, term()}
+  def snapshot(%Specter{native: ref}, pc),
+    do: Native.snapshot(ref, pc)
+
+  @doc """
+  Sends the stats of a peer connection every `interval` milliseconds, as
+  `{:stats, peer_connection, json}` like `get_stats/2`, starting right away. This saves
+  calling `get_stats/2` on a timer when monitoring a peer connection closely. Starting
+  again replaces the interval, and reporting stops with `stop_stats_reporting/2` or
+  when the peer connection is closed.
+
+  Sends back `{:ok, peer_connection, :start_stats_reporting}`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> :ok = Specter.PeerConnection.start_stats_reporting(specter, pc, 100)
+      iex> assert_receive {:ok, ^pc, :start_stats_reporting}
+      iex> assert_receive {:stats, ^pc, _json}
+      iex> :ok = Specter.PeerConnection.stop_stats_reporting(specter, pc)
+      iex> assert_receive {:ok, ^pc, :stop_stats_reporting}
+      ...>
+      iex> Specter.PeerConnection.start_stats_reporting(specter, pc, 0)
+      {:error, :invalid_interval}
+  """
+  @spec start_stats_reporting(Specter.t(), t(), pos_integer()) :: :ok | {:error, term()}
+  def start_stats_reporting(%Specter{native: ref}, pc, interval)
+      when is_integer(interval) and interval > 0,
+      do: Native.start_stats_reporting(ref, pc, interval)
+
+  def start_stats_reporting(%Specter{}, _pc, _interval), do: {:error, :invalid_interval}
+
+  @doc """
+  Stops sending the stats of a peer connection started by `start_stats_reporting/3`.
+
+  Sends back `{:ok, peer_connection, :stop_stats_reporting}`.
+  """
+  @spec stop_stats_reporting(Specter.t(), t()) :: :ok | {:error, term()}
+  def stop_stats_reporting(%Specter{native: ref}, pc),
+    do: Native.stop_stats_reporting(ref, pc)
+
+  @doc """
+  Returns the direction negotiated for the transceiver with the given mid. After
+  renegotiation this may differ from the direction the transceiver was added with, for
+  instance `:sendonly` rather than `:sendrecv` when the remote peer declines to send.
+
+  Returns `{:ok, nil}` until the transceiver has been negotiated, and
+  `{:error, :not_found}` when the peer connection does not exist or has no transceiver
+  with the mid.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> :ok = Specter.PeerConnection.add_transceiver(specter, pc, :video, :sendrecv)
+      iex> assert_receive {:rtp_transceiver, ^pc, _transceiver}
+      iex> {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      iex> :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      iex> assert_receive {:ok, ^pc, :set_local_description}
+      ...>
+      iex> Specter.PeerConnection.transceiver_current_direction(specter, pc, "0")
+      {:ok, nil}
+  """
+  @spec transceiver_current_direction(Specter.t(), t(), String.t()) ::
+          {:ok, transceiver_direction_t() | nil} | {:error, term()}
+  def transceiver_current_direction(%Specter{native: ref}, pc, mid),
+    do: Native.transceiver_current_direction(ref, pc, mid)
+
+  @doc """
+  Returns the mid of a transceiver added by `add_transceiver/4`, given the uuid sent
+  back in `t:rtp_transceiver_msg_t/0`. The mid is what `set_codec_preferences/4` and
+  `transceiver_current_direction/3` take, and is only assigned once a description
+  including the transceiver has been set.
+
+  Returns `{:ok, nil}` until then, and `{:error, :not_found}` when the peer connection
+  does not exist or did not add a transceiver with the uuid.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> :ok = Specter.PeerConnection.add_transceiver(specter, pc, :video, :sendrecv)
+      iex> assert_receive {:rtp_transceiver, ^pc, transceiver}
+      iex> Specter.PeerConnection.transceiver_mid(specter, pc, transceiver)
+      {:ok, nil}
+      iex> {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      iex> :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      iex> assert_receive {:ok, ^pc, :set_local_description}
+      iex> Specter.PeerConnection.transceiver_mid(specter, pc, transceiver)
+      {:ok, "0"}
+  """
+  @spec transceiver_mid(Specter.t(), t(), String.t()) ::
+          {:ok, String.t() | nil} | {:error, term()}
+  def transceiver_mid(%Specter{native: ref}, pc, transceiver),
+    do: Native.transceiver_mid(ref, pc, transceiver)
+
+  @doc """
+  Writes an RTCP packet to the remote peer, for instance to send feedback about the
+  media received from it. See `t:rtcp_packet_t/0` for the packets that may be given.
+  SSRCs of remote tracks are sent back by `get_receivers/2`.
+
+  `request_keyframe/3` and `set_receive_bitrate_cap/3` cover the most common uses of
+  PLI and REMB, without needing to know the SSRCs of remote tracks.
+
+  Returns `{:error, :unsupported_rtcp_packet}` for a packet of any other shape, or for
+  a serialized packet of a type unknown to webrtc.rs, and
+  `{:error, :invalid_rtcp_packet}` for a binary that cannot be parsed as RTCP.
+  Otherwise, sends back `{:ok, peer_connection, :write_rtcp}` once the packet has been
+  written, or `t:rtcp_error_msg_t/0`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> {:ok, media_engine} = Specter.new_media_engine(specter)
+      iex> {:ok, registry} = Specter.new_registry(specter, media_engine)
+      iex> {:ok, api} = Specter.new_api(specter, media_engine, registry)
+      iex> {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      iex> assert_receive {:peer_connection_ready, ^pc}
+      ...>
+      iex> Specter.PeerConnection.write_rtcp(specter, pc, {:nack, 1})
+      {:error, :unsupported_rtcp_packet}
+  """
+  @spec write_rtcp(Specter.t(), t(), rtcp_packet_t()) :: :ok | {:error, term()}
+  def write_rtcp(%Specter{native: ref}, pc, packet),
+    do: Native.write_rtcp(ref, pc, packet)
 end
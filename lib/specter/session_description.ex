@@ -0,0 +1,57 @@
+defmodule Specter.SessionDescription do
+  @moduledoc """
+  A representation of webrtc.rs `RTCSessionDescription`.
+
+  Functions in `Specter.PeerConnection` send and receive session descriptions as
+  JSON strings. This struct is an alternative representation, and the functions in
+  this module convert between the two.
+  """
+  alias Specter.Native
+
+  @typedoc """
+  The type of a session description. Note that `:unspecified` is never valid
+  when given to a peer connection.
+  """
+  @type sdp_type_t() :: :offer | :answer | :pranswer | :rollback | :unspecified
+
+  @type t() :: %__MODULE__{
+          type: sdp_type_t(),
+          sdp: Specter.PeerConnection.sdp_t()
+        }
+
+  @enforce_keys [:type, :sdp]
+  defstruct [:type, :sdp]
+
+  @doc """
+  Converts a JSON session description, as sent by `Specter.PeerConnection`, into
+  a struct.
+
+  ## Usage
+
+      iex> Specter.SessionDescription.from_json(~S[{"type":"offer","sdp":"v=0"}])
+      {:ok, %Specter.SessionDescription{type: :offer, sdp: "v=0"}}
+
+      iex> Specter.SessionDescription.from_json("{blah:")
+      {:error, :invalid_json}
+  """
+  @spec from_json(Specter.PeerConnection.session_description_t()) ::
+          {:ok, t()} | {:error, term()}
+  def from_json(json), do: Native.sdp_json_to_struct(json)
+
+  @doc """
+  Converts a struct into a JSON session description, which may be given to
+  `Specter.PeerConnection`.
+
+  ## Usage
+
+      iex> desc = %Specter.SessionDescription{type: :answer, sdp: "v=0"}
+      iex> {:ok, json} = Specter.SessionDescription.to_json(desc)
+      iex> Jason.decode!(json)
+      %{"type" => "answer", "sdp" => "v=0"}
+
+      iex> Specter.SessionDescription.to_json(%{type: :bogus, sdp: "v=0"})
+      {:error, :invalid_session_description}
+  """
+  @spec to_json(t()) :: {:ok, Specter.PeerConnection.session_description_t()} | {:error, term()}
+  def to_json(description), do: Native.sdp_struct_to_json(description)
+end
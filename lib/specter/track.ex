@@ -30,30 +30,153 @@ defmodule Specter.TrackLocalStaticSample do
   """
   @opaque t() :: String.t()
 
+  @typedoc """
+  Options for creating a local track. A `rid` makes the track one encoding of a
+  simulcast source. See `Specter.PeerConnection.add_encoding/4`.
+  """
+  @type new_options_t() :: [] | [rid: String.t()]
+
   @doc """
   Creates new TrackLocalStaticSample.
 
+  The `id` and `stream_id` are announced in the SDP as `a=msid:<stream_id> <id>`.
+  Browsers group remote tracks sharing a `stream_id` into one `MediaStream`, so the
+  audio and video of a single source should be given the same `stream_id`.
+
+  Returns `{:error, :invalid_codec}` when the codec is not a `Specter.RtpCodecCapability`,
+  `{:error, :unknown_mime_type}` when its mime type is not one of
+  `Specter.RtpCodecCapability.mime_types/0`, and `{:error, :invalid_track}` when the
+  `id` or `stream_id` is not a string. Mime types are matched regardless of case.
+
   ## Usage
 
       iex> {:ok, specter} = Specter.init()
-      iex> codec = %Specter.RtpCodecCapability{mime_type: "audio"}
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
       iex> {:ok, _track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      ...>
+      iex> Specter.TrackLocalStaticSample.new(specter, %{clock_rate: 48_000}, "audio", "specter")
+      {:error, :invalid_codec}
   """
-  @spec new(Specter.t(), Specter.RtpCodecCapability.t(), String.t(), String.t()) ::
-          {:ok, t()} | {:error, term()}
-  def new(%Specter{native: ref}, codec, id, stream_id) do
-    Native.new_track_local_static_sample(ref, codec, id, stream_id)
+  @spec new(
+          Specter.t(),
+          Specter.RtpCodecCapability.t(),
+          String.t(),
+          String.t(),
+          new_options_t()
+        ) :: {:ok, t()} | {:error, term()}
+  def new(%Specter{native: ref}, codec, id, stream_id, opts \\ []) do
+    Native.new_track_local_static_sample(ref, codec, id, stream_id, Keyword.get(opts, :rid))
+  end
+
+  @doc """
+  Reads an Ogg file containing Opus audio and writes it to the track, page by page.
+  The track must have been created with an Opus codec, otherwise
+  `{:error, :invalid_codec}` is returned.
+
+  Sends `{:playback_finished, track}` when the end of the file is reached, or
+  `{:playback_error, track, reason}` when the file cannot be opened or read.
+  """
+  @spec play_audio_from_file(Specter.t(), t(), Path.t()) :: :ok | {:error | term()}
+  def play_audio_from_file(%Specter{native: ref}, track, path) do
+    if File.exists?(path) do
+      Native.play_audio_from_file(ref, track, path)
+    else
+      {:error, :file_not_found}
+    end
   end
 
+  @typedoc """
+  Options for playing a file into a track.
+  """
+  @type play_options_t() :: [] | [queue_size: pos_integer(), fps: pos_integer()]
+
   @doc """
   Reads H264 file and writes it to the track.
+
+  Samples are parsed ahead of time into a bounded queue, which is drained on a
+  fixed interval. A deeper queue absorbs more parsing jitter on busy hosts, at
+  the cost of holding more samples in memory.
+
+  Each sample is written with a duration of `1 / fps` seconds, which should match
+  the frame rate the file was encoded at.
+
+  | param        | type               | default |
+  | ------------ | ------------------ | ------- |
+  | `queue_size` | `pos_integer()`    | 32 |
+  | `fps`        | `pos_integer()`    | 30 |
+
+  Sends `{:playback_finished, track}` when the end of the file is reached, or
+  `{:playback_error, track, reason}` when the file cannot be opened or read.
+  Returns `{:error, :invalid_track}` when the track does not exist.
   """
-  @spec play_from_file_h264(Specter.t(), t(), Path.t()) :: :ok | {:error | term()}
-  def play_from_file_h264(%Specter{native: ref}, track, path) do
+  @spec play_from_file_h264(Specter.t(), t(), Path.t(), play_options_t()) ::
+          :ok | {:error | term()}
+  def play_from_file_h264(%Specter{native: ref}, track, path, opts \\ []) do
     if File.exists?(path) do
-      Native.play_from_file_h264(ref, track, path)
+      Native.play_from_file_h264(
+        ref,
+        track,
+        path,
+        Keyword.get(opts, :queue_size, 32),
+        Keyword.get(opts, :fps, 30)
+      )
     else
       {:error, :file_not_found}
     end
   end
+
+  @doc """
+  Reads an IVF file containing VP8 video and writes it to the track, frame by frame.
+  Frame durations are derived from the timebase in the IVF header. The track must
+  have been created with a VP8 codec, otherwise `{:error, :invalid_codec}` is returned.
+
+  Sends `{:playback_finished, track}` when the end of the file is reached, or
+  `{:playback_error, track, reason}` when the file cannot be opened or read, when its
+  timebase has a zero denominator, or when a frame cannot be written.
+  """
+  @spec play_vp8_from_file(Specter.t(), t(), Path.t()) :: :ok | {:error | term()}
+  def play_vp8_from_file(%Specter{native: ref}, track, path) do
+    if File.exists?(path) do
+      Native.play_vp8_from_file(ref, track, path)
+    else
+      {:error, :file_not_found}
+    end
+  end
+
+  @doc """
+  Stops a playback started by one of the `play_*` functions before it reaches the
+  end of its file. The track still sends `{:playback_finished, track}` once the
+  playback has stopped writing samples.
+
+  Returns `{:error, :not_found}` when nothing is playing on the track.
+  """
+  @spec stop_playback(Specter.t(), t()) :: :ok | {:error, term()}
+  def stop_playback(%Specter{native: ref}, track), do: Native.stop_playback(ref, track)
+
+  @doc """
+  Writes a single encoded sample, such as a video frame or an Opus packet, to the
+  track. This turns the track into a sink for media that is already in memory, for
+  instance frames encoded by an external process.
+
+  The duration, in milliseconds, is used to advance RTP timestamps and should match
+  the time that the sample represents. This function returns once the sample has
+  been written, so callers are responsible for pacing writes in real time.
+
+  Returns `{:error, :invalid_track}` when the track does not exist, and
+  `{:error, reason}` when the sample cannot be written.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      iex> {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      iex> Specter.TrackLocalStaticSample.write_sample(specter, track, <<0, 1, 2>>, 20)
+      :ok
+      ...>
+      iex> Specter.TrackLocalStaticSample.write_sample(specter, "invalid", <<0, 1, 2>>, 20)
+      {:error, :invalid_track}
+  """
+  @spec write_sample(Specter.t(), t(), binary(), non_neg_integer()) :: :ok | {:error, term()}
+  def write_sample(%Specter{native: ref}, track, payload, duration_ms),
+    do: Native.write_track_sample(ref, track, payload, duration_ms)
 end
@@ -0,0 +1,65 @@
+defmodule Specter.TrackLocalStaticRTP do
+  @moduledoc """
+  A representation of webrtc.rs `TrackLocalStaticRTP`.
+
+  Unlike `Specter.TrackLocalStaticSample`, this track accepts RTP packets that have
+  already been prepared, and forwards them without re-packetizing their payload.
+  Combined with packets read from a remote track, this allows media to be relayed
+  between peer connections without decoding and re-encoding it.
+
+  See `Specter.TrackLocalStaticSample` for an overview of track types.
+  """
+  alias Specter.Native
+
+  @typedoc """
+  Represents an instantiated TrackLocalStaticRTP stored in the NIF.
+  """
+  @opaque t() :: String.t()
+
+  @doc """
+  Creates new TrackLocalStaticRTP. A `rid` may be given as an option, making the track
+  one encoding of a simulcast source. See `Specter.PeerConnection.add_encoding/4`.
+  As with `Specter.TrackLocalStaticSample.new/5`, the `stream_id` groups tracks into
+  remote media streams, a malformed codec returns `{:error, :invalid_codec}`, and an
+  unknown mime type returns `{:error, :unknown_mime_type}`.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      iex> {:ok, _track} = Specter.TrackLocalStaticRTP.new(specter, codec, "video", "specter")
+  """
+  @spec new(
+          Specter.t(),
+          Specter.RtpCodecCapability.t(),
+          String.t(),
+          String.t(),
+          Specter.TrackLocalStaticSample.new_options_t()
+        ) :: {:ok, t()} | {:error, term()}
+  def new(%Specter{native: ref}, codec, id, stream_id, opts \\ []) do
+    Native.new_track_local_static_rtp(ref, codec, id, stream_id, Keyword.get(opts, :rid))
+  end
+
+  @doc """
+  Writes a raw RTP packet to the track. The SSRC and payload type of the packet are
+  rewritten to match each peer connection that the track has been added to.
+
+  Returns `{:error, :invalid_track}` when the track does not exist, and
+  `{:error, reason}` when the packet cannot be parsed or written.
+
+  ## Usage
+
+      iex> {:ok, specter} = Specter.init()
+      iex> codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      iex> {:ok, track} = Specter.TrackLocalStaticRTP.new(specter, codec, "video", "specter")
+      iex> packet = <<0x80, 96, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 1, 2>>
+      iex> Specter.TrackLocalStaticRTP.write_rtp(specter, track, packet)
+      :ok
+      ...>
+      iex> Specter.TrackLocalStaticRTP.write_rtp(specter, "invalid", packet)
+      {:error, :invalid_track}
+  """
+  @spec write_rtp(Specter.t(), t(), binary()) :: :ok | {:error, term()}
+  def write_rtp(%Specter{native: ref}, track, packet),
+    do: Native.write_track_rtp(ref, track, packet)
+end
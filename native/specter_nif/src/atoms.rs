@@ -4,30 +4,93 @@ rustler::atoms! {
 
     // errors
     answer_error,
+    busy,
     candidate_error,
+    closed,
+    draining,
+    draining_error,
+    excessive_retries,
+    glare_error,
+    in_use,
+    incompatible_track,
     invalid_atom,
+    invalid_bitrate,
+    invalid_codec,
+    invalid_data_channel_options,
+    invalid_command_buffer_size,
+    invalid_fps,
+    invalid_interval,
+    invalid_ice_candidate,
     invalid_json,
     invalid_local_description,
+    invalid_payload_type,
+    invalid_queue_size,
+    invalid_registry_options,
     invalid_remote_description,
+    invalid_rtcp_packet,
+    invalid_sdp,
+    invalid_session_description,
+    invalid_state,
     invalid_track,
-    lock_fail,
+    json_error,
+    keyframe_error,
+    no_ice_agent,
+    no_offer_needed,
+    no_remote_description,
+    not_connected,
     not_found,
     offer_error,
+    rtcp_error,
+    track_error,
+    transceiver_error,
+    unknown_mime_type,
+    unsupported_codec,
+    unsupported_rtcp_packet,
 
     // config
+    certificate,
+    command_buffer_size,
+    ice_candidate_format,
+    ice_candidate_pool_size,
+    ice_disconnected_timeout,
+    ice_failed_timeout,
+    ice_interfaces,
+    ice_keepalive_interval,
+    ice_multicast_dns_mode,
+    ice_network_types,
     ice_servers,
+    invalid_certificate,
     invalid_configuration,
+    invalid_private_key,
+    invalid_udp_port_range,
+    nat_1to1_candidate_type,
+    nat_1to1_ips,
+    private_key,
+    udp_port_range,
 
     webrtc_error,
 
     // send
     add_ice_candidate,
+    add_track,
+    add_transceiver,
+    clear_receive_bitrate_cap,
     connection_state,
+    connection_state_change,
     current_local_description,
     current_remote_description,
+    create_data_channel,
+    data_channel_buffered_low,
     data_channel_created,
+    drain_peer_connection,
+    dtls_transport_state_change,
+    forward_track,
+    glare,
+    gathering_complete,
     ice_candidate,
     ice_connection_state,
+    ice_connection_state_change,
+    ice_gathering_complete,
     ice_gathering_state,
     local_description,
     peer_connection_closed,
@@ -35,14 +98,63 @@ rustler::atoms! {
     peer_connection_ready,
     pending_local_description,
     pending_remote_description,
+    playback_error,
     playback_finished,
+    receivers,
+    record_to_file,
+    recording_error,
+    recording_finished,
     remote_description,
+    request_keyframe,
+    restart_ice,
+    rtp,
     rtp_sender,
+    rtp_transceiver,
+    selected_candidate_pair,
+    sender_stats,
+    senders,
     signaling_state,
+    snapshot,
     set_local_description,
+    set_receive_bitrate_cap,
     set_remote_description,
+    set_rtp_forwarding,
+    start_stats_reporting,
     stats,
+    stop_stats_reporting,
+    track_ended,
+    write_rtcp,
 
     answer,
     offer,
+    pranswer,
+    rollback,
+
+    // data channel options
+    id,
+    max_packet_life_time,
+    max_retransmits,
+    negotiated,
+    ordered,
+    protocol,
+
+    // glare
+    ignored,
+    rolled_back,
+
+    // registry options
+    audio_level,
+    default_interceptors,
+    nack,
+    pli,
+    rtcp_reports,
+    twcc,
+
+    // rtcp
+    fir,
+    remb,
+
+    // session description
+    sdp,
+    type_ = "type",
 }
@@ -0,0 +1,70 @@
+use crate::atoms;
+use rcgen::{CertificateParams, KeyPair};
+use rustler::{Atom, NifMap};
+use std::time::SystemTime;
+use webrtc::dtls::crypto::{Certificate as DtlsCertificate, CryptoPrivateKey};
+use webrtc::dtls_transport::dtls_fingerprint::RTCDtlsFingerprint;
+use webrtc::peer_connection::certificate::RTCCertificate;
+
+/// A DTLS certificate provided as PEM, used by every peer connection in place of
+/// the certificate webrtc.rs would otherwise generate for it. Reusing a certificate
+/// keeps the DTLS fingerprint stable across restarts.
+#[derive(Clone, Debug)]
+pub struct Certificate {
+    pub pem: String,
+    pub rtc_certificate: RTCCertificate,
+}
+
+impl Certificate {
+    /// Builds a certificate from a PEM certificate chain, with the leaf certificate
+    /// first, and its PEM private key.
+    pub fn from_pem(certificate_pem: &str, private_key_pem: &str) -> Result<Self, Atom> {
+        let key_pair =
+            KeyPair::from_pem(private_key_pem).map_err(|_| atoms::invalid_private_key())?;
+        let private_key =
+            CryptoPrivateKey::from_key_pair(&key_pair).map_err(|_| atoms::invalid_private_key())?;
+
+        let certificate = pem::parse_many(certificate_pem)
+            .map_err(|_| atoms::invalid_certificate())?
+            .into_iter()
+            .filter(|block| block.tag() == "CERTIFICATE")
+            .map(|block| block.into_contents().into())
+            .collect();
+
+        let dtls_certificate = DtlsCertificate {
+            certificate,
+            private_key,
+        };
+
+        // webrtc.rs refuses to create peer connections once the certificate expires.
+        let expires = match dtls_certificate.certificate.first() {
+            None => return Err(atoms::invalid_certificate()),
+            Some(leaf) => match CertificateParams::from_ca_cert_der(leaf) {
+                Err(_) => return Err(atoms::invalid_certificate()),
+                Ok(params) => SystemTime::from(params.not_after),
+            },
+        };
+
+        Ok(Certificate {
+            pem: certificate_pem.to_owned(),
+            rtc_certificate: RTCCertificate::from_existing(dtls_certificate, expires),
+        })
+    }
+}
+
+/// The fingerprint of a DTLS certificate, as advertised in `a=fingerprint` of a session
+/// description. `value` is the colon separated hex digest of the certificate.
+#[derive(NifMap)]
+pub struct Fingerprint {
+    algorithm: String,
+    value: String,
+}
+
+impl From<RTCDtlsFingerprint> for Fingerprint {
+    fn from(fingerprint: RTCDtlsFingerprint) -> Self {
+        Fingerprint {
+            algorithm: fingerprint.algorithm,
+            value: fingerprint.value,
+        }
+    }
+}
@@ -1,5 +1,171 @@
-use rustler::NifStruct;
-use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+use crate::atoms;
+use rustler::{Atom, NifMap, NifStruct, NifUnitEnum};
+use std::convert::TryFrom;
+use webrtc::api::media_engine::{
+    MediaEngine, MIME_TYPE_AV1, MIME_TYPE_G722, MIME_TYPE_H264, MIME_TYPE_OPUS, MIME_TYPE_PCMA,
+    MIME_TYPE_PCMU, MIME_TYPE_TELEPHONE_EVENT, MIME_TYPE_VP8, MIME_TYPE_VP9,
+};
+use webrtc::rtp_transceiver::rtp_codec::{
+    RTCRtpCodecCapability, RTCRtpCodecParameters, RTPCodecType,
+};
+use webrtc::rtp_transceiver::RTCPFeedback;
+
+const MIME_TYPE_H265: &str = "video/H265";
+const MIME_TYPE_RTX: &str = "video/rtx";
+const MIME_TYPE_ULPFEC: &str = "video/ulpfec";
+
+// The mime types of the codecs webrtc.rs can negotiate, in their canonical case.
+const KNOWN_MIME_TYPES: [&str; 12] = [
+    MIME_TYPE_OPUS,
+    MIME_TYPE_G722,
+    MIME_TYPE_PCMU,
+    MIME_TYPE_PCMA,
+    MIME_TYPE_TELEPHONE_EVENT,
+    MIME_TYPE_VP8,
+    MIME_TYPE_VP9,
+    MIME_TYPE_H264,
+    MIME_TYPE_H265,
+    MIME_TYPE_AV1,
+    MIME_TYPE_RTX,
+    MIME_TYPE_ULPFEC,
+];
+
+// (mime type, payload type, clock rate, channels, fmtp line) of a default codec.
+type DefaultCodec = (&'static str, u8, u32, u16, &'static str);
+
+// The codecs registered by `MediaEngine::register_default_codecs`, in the same order
+// and with the same payload types, which are registered one by one so that VP9 and
+// AV1 may be left out.
+const DEFAULT_AUDIO_CODECS: [DefaultCodec; 4] = [
+    (MIME_TYPE_OPUS, 111, 48000, 2, "minptime=10;useinbandfec=1"),
+    (MIME_TYPE_G722, 9, 8000, 0, ""),
+    (MIME_TYPE_PCMU, 0, 8000, 0, ""),
+    (MIME_TYPE_PCMA, 8, 8000, 0, ""),
+];
+const DEFAULT_VIDEO_CODECS: [DefaultCodec; 11] = [
+    (MIME_TYPE_VP8, 96, 90000, 0, ""),
+    (MIME_TYPE_VP9, 98, 90000, 0, "profile-id=0"),
+    (MIME_TYPE_VP9, 100, 90000, 0, "profile-id=1"),
+    (
+        MIME_TYPE_H264,
+        102,
+        90000,
+        0,
+        "level-asymmetry-allowed=1;packetization-mode=1;profile-level-id=42001f",
+    ),
+    (
+        MIME_TYPE_H264,
+        127,
+        90000,
+        0,
+        "level-asymmetry-allowed=1;packetization-mode=0;profile-level-id=42001f",
+    ),
+    (
+        MIME_TYPE_H264,
+        125,
+        90000,
+        0,
+        "level-asymmetry-allowed=1;packetization-mode=1;profile-level-id=42e01f",
+    ),
+    (
+        MIME_TYPE_H264,
+        108,
+        90000,
+        0,
+        "level-asymmetry-allowed=1;packetization-mode=0;profile-level-id=42e01f",
+    ),
+    (
+        MIME_TYPE_H264,
+        127,
+        90000,
+        0,
+        "level-asymmetry-allowed=1;packetization-mode=0;profile-level-id=42001f",
+    ),
+    (
+        MIME_TYPE_H264,
+        123,
+        90000,
+        0,
+        "level-asymmetry-allowed=1;packetization-mode=1;profile-level-id=640032",
+    ),
+    (MIME_TYPE_AV1, 41, 90000, 0, "profile-id=0"),
+    (MIME_TYPE_ULPFEC, 116, 90000, 0, ""),
+];
+
+/// Which of the optional video codecs are registered along with the defaults. Both VP9
+/// and AV1 are registered unless turned off.
+#[derive(Clone, Copy)]
+pub struct DefaultCodecs {
+    pub av1: bool,
+    pub vp9: bool,
+}
+
+impl Default for DefaultCodecs {
+    fn default() -> Self {
+        DefaultCodecs {
+            av1: true,
+            vp9: true,
+        }
+    }
+}
+
+impl DefaultCodecs {
+    /// The parameters of the default codecs, in the order they are registered.
+    pub fn parameters(&self) -> Vec<(RTCRtpCodecParameters, RTPCodecType)> {
+        let audio = DEFAULT_AUDIO_CODECS
+            .iter()
+            .map(|codec| (codec_parameters(codec, vec![]), RTPCodecType::Audio));
+
+        let video = DEFAULT_VIDEO_CODECS
+            .iter()
+            .filter(|(mime_type, ..)| self.vp9 || *mime_type != MIME_TYPE_VP9)
+            .filter(|(mime_type, ..)| self.av1 || *mime_type != MIME_TYPE_AV1)
+            .map(|codec| {
+                let feedback = match codec.0 {
+                    MIME_TYPE_ULPFEC => vec![],
+                    _ => video_rtcp_feedback(),
+                };
+                (codec_parameters(codec, feedback), RTPCodecType::Video)
+            });
+
+        audio.chain(video).collect()
+    }
+
+    pub fn register(&self, media_engine: &mut MediaEngine) -> Result<(), webrtc::Error> {
+        for (params, kind) in self.parameters() {
+            media_engine.register_codec(params, kind)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn codec_parameters(
+    (mime_type, payload_type, clock_rate, channels, sdp_fmtp_line): &DefaultCodec,
+    rtcp_feedback: Vec<RTCPFeedback>,
+) -> RTCRtpCodecParameters {
+    RTCRtpCodecParameters {
+        capability: RTCRtpCodecCapability {
+            mime_type: mime_type.to_string(),
+            clock_rate: *clock_rate,
+            channels: *channels,
+            sdp_fmtp_line: sdp_fmtp_line.to_string(),
+            rtcp_feedback,
+        },
+        payload_type: *payload_type,
+        ..Default::default()
+    }
+}
+
+fn video_rtcp_feedback() -> Vec<RTCPFeedback> {
+    [("goog-remb", ""), ("ccm", "fir"), ("nack", ""), ("nack", "pli")]
+        .iter()
+        .map(|(typ, parameter)| RTCPFeedback {
+            typ: typ.to_string(),
+            parameter: parameter.to_string(),
+        })
+        .collect()
+}
 
 #[derive(NifStruct)]
 #[module = "Specter.RtpCodecCapability"]
@@ -10,26 +176,141 @@ pub struct RtpCodecCapability {
     pub sdp_fmtp_line: String,
 }
 
-impl From<&RtpCodecCapability> for RTCRtpCodecCapability {
-    fn from(rtp_codec_capability: &RtpCodecCapability) -> Self {
-        RTCRtpCodecCapability {
-            mime_type: rtp_codec_capability.mime_type.clone(),
+/// Checks the mime type against those known to webrtc.rs, refusing others with
+/// `unknown_mime_type`. Mime types are matched regardless of case, and given the case
+/// webrtc.rs uses, so that `video/h264` negotiates as `video/H264`.
+impl TryFrom<RtpCodecCapability> for RTCRtpCodecCapability {
+    type Error = Atom;
+
+    fn try_from(rtp_codec_capability: RtpCodecCapability) -> Result<Self, Self::Error> {
+        let mime_type = KNOWN_MIME_TYPES
+            .iter()
+            .find(|known| known.eq_ignore_ascii_case(&rtp_codec_capability.mime_type))
+            .ok_or_else(atoms::unknown_mime_type)?;
+
+        Ok(RTCRtpCodecCapability {
+            mime_type: mime_type.to_string(),
             clock_rate: rtp_codec_capability.clock_rate,
             channels: rtp_codec_capability.channels,
-            sdp_fmtp_line: rtp_codec_capability.sdp_fmtp_line.clone(),
+            sdp_fmtp_line: rtp_codec_capability.sdp_fmtp_line,
             ..Default::default()
+        })
+    }
+}
+
+/// The kind of media carried by a codec, track or transceiver.
+#[derive(Clone, Copy, NifUnitEnum)]
+pub enum TrackKind {
+    Audio,
+    Video,
+}
+
+impl TrackKind {
+    /// Returns `None` for `RTPCodecType::Unspecified`, which has no matching kind.
+    pub fn from_codec_type(codec_type: RTPCodecType) -> Option<Self> {
+        match codec_type {
+            RTPCodecType::Audio => Some(TrackKind::Audio),
+            RTPCodecType::Video => Some(TrackKind::Video),
+            RTPCodecType::Unspecified => None,
         }
     }
 }
 
-impl From<RtpCodecCapability> for RTCRtpCodecCapability {
-    fn from(rtp_codec_capability: RtpCodecCapability) -> Self {
-        RTCRtpCodecCapability {
-            mime_type: rtp_codec_capability.mime_type,
-            clock_rate: rtp_codec_capability.clock_rate,
-            channels: rtp_codec_capability.channels,
-            sdp_fmtp_line: rtp_codec_capability.sdp_fmtp_line,
-            ..Default::default()
+impl From<TrackKind> for RTPCodecType {
+    fn from(kind: TrackKind) -> Self {
+        match kind {
+            TrackKind::Audio => RTPCodecType::Audio,
+            TrackKind::Video => RTPCodecType::Video,
+        }
+    }
+}
+
+/// A codec registered on a MediaEngine, as returned by `media_engine_codecs`.
+#[derive(Clone, NifMap)]
+pub struct CodecInfo {
+    pub mime_type: String,
+    pub payload_type: u8,
+    pub clock_rate: u32,
+    pub channels: u16,
+}
+
+/// The codecs registered on a MediaEngine. webrtc.rs keeps these private to the
+/// MediaEngine, so they are recorded alongside it as codecs are registered.
+#[derive(Clone, Default, NifMap)]
+pub struct MediaEngineCodecs {
+    pub audio: Vec<CodecInfo>,
+    pub video: Vec<CodecInfo>,
+}
+
+impl MediaEngineCodecs {
+    pub fn defaults(default_codecs: DefaultCodecs) -> Self {
+        let mut codecs = MediaEngineCodecs::default();
+        for (params, kind) in default_codecs.parameters() {
+            codecs.add(&params, kind);
+        }
+
+        codecs
+    }
+
+    pub fn add(&mut self, params: &RTCRtpCodecParameters, kind: RTPCodecType) {
+        let info = CodecInfo {
+            mime_type: params.capability.mime_type.clone(),
+            payload_type: params.payload_type,
+            clock_rate: params.capability.clock_rate,
+            channels: params.capability.channels,
+        };
+        self.push(info, kind);
+    }
+
+    // Like the MediaEngine, a codec is skipped when one with the same mime type
+    // and payload type has already been registered.
+    fn push(&mut self, info: CodecInfo, kind: RTPCodecType) {
+        let codecs = match kind {
+            RTPCodecType::Audio => &mut self.audio,
+            RTPCodecType::Video => &mut self.video,
+            RTPCodecType::Unspecified => return,
+        };
+
+        if !codecs
+            .iter()
+            .any(|c| c.mime_type == info.mime_type && c.payload_type == info.payload_type)
+        {
+            codecs.push(info);
+        }
+    }
+}
+
+/// The codecs a MediaEngine was configured with, in the order they were registered.
+/// MediaEngines cannot be cloned, and are consumed by `new_api`, so copies are built
+/// by registering the same codecs on a new MediaEngine.
+#[derive(Clone, Default)]
+pub struct MediaEngineSetup {
+    default_codecs: Option<DefaultCodecs>,
+    codecs: Vec<(RTCRtpCodecParameters, RTPCodecType)>,
+}
+
+impl MediaEngineSetup {
+    pub fn new(default_codecs: Option<DefaultCodecs>) -> Self {
+        MediaEngineSetup {
+            default_codecs,
+            codecs: vec![],
         }
     }
+
+    pub fn add(&mut self, params: &RTCRtpCodecParameters, kind: RTPCodecType) {
+        self.codecs.push((params.clone(), kind));
+    }
+
+    pub fn build(&self) -> Result<MediaEngine, webrtc::Error> {
+        let mut media_engine = MediaEngine::default();
+        if let Some(default_codecs) = self.default_codecs {
+            default_codecs.register(&mut media_engine)?;
+        }
+
+        for (params, kind) in &self.codecs {
+            media_engine.register_codec(params.clone(), *kind)?;
+        }
+
+        Ok(media_engine)
+    }
 }
@@ -1,12 +1,99 @@
 use crate::atoms;
+use crate::certificate::Certificate;
+use crate::ice_candidate::IceCandidateFormat;
 use rustler::types::elixir_struct;
-use rustler::{Atom, Encoder, Env, Term};
+use rustler::{Atom, Encoder, Env, NifMap, NifUnitEnum, Term};
+use std::net::IpAddr;
+use std::time::Duration;
+use webrtc::api::setting_engine::SettingEngine;
+use webrtc::ice::mdns::MulticastDnsMode as RTCMulticastDnsMode;
+use webrtc::ice::network_type::NetworkType;
+use webrtc::ice::udp_network::{EphemeralUDP, UDPNetwork};
+use webrtc::ice_transport::ice_candidate_type::RTCIceCandidateType;
 use webrtc::ice_transport::ice_server::RTCIceServer;
 use webrtc::peer_connection::configuration::RTCConfiguration;
 
+// The URL schemes accepted for ICE servers.
+const ICE_SERVER_SCHEMES: [&str; 4] = ["stun:", "stuns:", "turn:", "turns:"];
+
+// The number of commands that may be queued for a peer connection before callers
+// are backpressured, when `command_buffer_size` is not given.
+const DEFAULT_COMMAND_BUFFER_SIZE: usize = 1000;
+
+/// Whether ICE resolves mDNS candidates from the remote peer, and whether it hides the
+/// addresses of its own host candidates behind `.local` names. webrtc.rs only queries
+/// by default.
+#[derive(Clone, Copy, Debug, NifUnitEnum)]
+pub enum MulticastDnsMode {
+    Disabled,
+    QueryOnly,
+    QueryAndGather,
+}
+
+impl From<MulticastDnsMode> for RTCMulticastDnsMode {
+    fn from(mode: MulticastDnsMode) -> Self {
+        match mode {
+            MulticastDnsMode::Disabled => RTCMulticastDnsMode::Disabled,
+            MulticastDnsMode::QueryOnly => RTCMulticastDnsMode::QueryOnly,
+            MulticastDnsMode::QueryAndGather => RTCMulticastDnsMode::QueryAndGather,
+        }
+    }
+}
+
+/// A type of network on which ICE gathers local candidates.
+#[derive(Clone, Copy, Debug, NifUnitEnum)]
+pub enum IceNetworkType {
+    Udp4,
+    Udp6,
+    Tcp4,
+    Tcp6,
+}
+
+impl From<IceNetworkType> for NetworkType {
+    fn from(network_type: IceNetworkType) -> Self {
+        match network_type {
+            IceNetworkType::Udp4 => NetworkType::Udp4,
+            IceNetworkType::Udp6 => NetworkType::Udp6,
+            IceNetworkType::Tcp4 => NetworkType::Tcp4,
+            IceNetworkType::Tcp6 => NetworkType::Tcp6,
+        }
+    }
+}
+
+/// The type of candidate that advertises the public addresses of a 1:1 NAT. `Host`
+/// replaces the private addresses of host candidates, while `Srflx` keeps them and adds
+/// a server reflexive candidate for each public address.
+#[derive(Clone, Copy, Debug, NifUnitEnum)]
+pub enum Nat1To1CandidateType {
+    Host,
+    Srflx,
+}
+
+impl From<Nat1To1CandidateType> for RTCIceCandidateType {
+    fn from(candidate_type: Nat1To1CandidateType) -> Self {
+        match candidate_type {
+            Nat1To1CandidateType::Host => RTCIceCandidateType::Host,
+            Nat1To1CandidateType::Srflx => RTCIceCandidateType::Srflx,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Config {
     pub ice_servers: Vec<String>,
+    pub ice_candidate_format: IceCandidateFormat,
+    pub certificate: Option<Certificate>,
+    pub command_buffer_size: usize,
+    pub ice_candidate_pool_size: u8,
+    pub udp_port_range: Option<(u16, u16)>,
+    pub ice_multicast_dns_mode: MulticastDnsMode,
+    pub ice_network_types: Option<Vec<IceNetworkType>>,
+    pub ice_interfaces: Option<Vec<String>>,
+    pub ice_disconnected_timeout: Option<u64>,
+    pub ice_failed_timeout: Option<u64>,
+    pub ice_keepalive_interval: Option<u64>,
+    pub nat_1to1_ips: Option<Vec<String>>,
+    pub nat_1to1_candidate_type: Nat1To1CandidateType,
 }
 
 impl Config {
@@ -15,28 +102,240 @@ impl Config {
             return Err(atoms::invalid_configuration());
         };
 
-        let ice_servers = match opts.map_get(atoms::ice_servers().to_term(env)) {
+        let ice_servers: Vec<String> = match opts.map_get(atoms::ice_servers().to_term(env)) {
             Err(_) => return Err(atoms::invalid_configuration()),
-            Ok(servers) => servers.decode().unwrap(),
+            Ok(servers) => match servers.decode() {
+                Err(_) => return Err(atoms::invalid_configuration()),
+                Ok(servers) => servers,
+            },
+        };
+
+        if !ice_servers.iter().all(|url| valid_ice_server(url)) {
+            return Err(atoms::invalid_configuration());
+        }
+
+        let ice_candidate_format =
+            match opts.map_get(atoms::ice_candidate_format().to_term(env)) {
+                Err(_) => IceCandidateFormat::Json,
+                Ok(format) => match format.decode() {
+                    Err(_) => return Err(atoms::invalid_configuration()),
+                    Ok(format) => format,
+                },
+            };
+
+        let certificate = match (
+            opts.map_get(atoms::certificate().to_term(env)),
+            opts.map_get(atoms::private_key().to_term(env)),
+        ) {
+            (Err(_), Err(_)) => None,
+            (Ok(certificate), Ok(private_key)) => {
+                let certificate: String = certificate
+                    .decode()
+                    .map_err(|_| atoms::invalid_certificate())?;
+                let private_key: String = private_key
+                    .decode()
+                    .map_err(|_| atoms::invalid_private_key())?;
+
+                Some(Certificate::from_pem(&certificate, &private_key)?)
+            }
+            (Ok(_), Err(_)) => return Err(atoms::invalid_private_key()),
+            (Err(_), Ok(_)) => return Err(atoms::invalid_certificate()),
+        };
+
+        let command_buffer_size = match opts.map_get(atoms::command_buffer_size().to_term(env)) {
+            Err(_) => DEFAULT_COMMAND_BUFFER_SIZE,
+            Ok(size) => match size.decode::<usize>() {
+                Ok(size) if size > 0 => size,
+                _ => return Err(atoms::invalid_configuration()),
+            },
+        };
+
+        // webrtc.rs keeps the pool size as a u8, so larger sizes are refused.
+        let ice_candidate_pool_size =
+            match opts.map_get(atoms::ice_candidate_pool_size().to_term(env)) {
+                Err(_) => 0,
+                Ok(size) => match size.decode::<u8>() {
+                    Err(_) => return Err(atoms::invalid_configuration()),
+                    Ok(size) => size,
+                },
+            };
+
+        // Port 0 would let the OS pick any port, defeating the range.
+        let udp_port_range = match opts.map_get(atoms::udp_port_range().to_term(env)) {
+            Err(_) => None,
+            Ok(range) => match range.decode::<Option<(u16, u16)>>() {
+                Err(_) => return Err(atoms::invalid_configuration()),
+                Ok(Some((min, max))) if min == 0 || min > max => {
+                    return Err(atoms::invalid_udp_port_range())
+                }
+                Ok(range) => range,
+            },
         };
 
-        let config = Config::new(ice_servers);
+        let ice_multicast_dns_mode =
+            match opts.map_get(atoms::ice_multicast_dns_mode().to_term(env)) {
+                Err(_) => MulticastDnsMode::QueryOnly,
+                Ok(mode) => match mode.decode() {
+                    Err(_) => return Err(atoms::invalid_configuration()),
+                    Ok(mode) => mode,
+                },
+            };
 
-        Ok(config)
+        // An empty list would leave ICE with nothing to gather.
+        let ice_network_types = get_non_empty_list(env, opts, atoms::ice_network_types())?;
+        let ice_interfaces = get_non_empty_list(env, opts, atoms::ice_interfaces())?;
+
+        let ice_disconnected_timeout = get_millis(env, opts, atoms::ice_disconnected_timeout())?;
+        let ice_failed_timeout = get_millis(env, opts, atoms::ice_failed_timeout())?;
+        let ice_keepalive_interval = get_millis(env, opts, atoms::ice_keepalive_interval())?;
+
+        let nat_1to1_ips: Option<Vec<String>> =
+            get_non_empty_list(env, opts, atoms::nat_1to1_ips())?;
+
+        if let Some(ips) = &nat_1to1_ips {
+            if !ips.iter().all(|ip| ip.parse::<IpAddr>().is_ok()) {
+                return Err(atoms::invalid_configuration());
+            }
+        }
+
+        let nat_1to1_candidate_type =
+            match opts.map_get(atoms::nat_1to1_candidate_type().to_term(env)) {
+                Err(_) => Nat1To1CandidateType::Host,
+                Ok(candidate_type) => match candidate_type.decode() {
+                    Err(_) => return Err(atoms::invalid_configuration()),
+                    Ok(candidate_type) => candidate_type,
+                },
+            };
+
+        // webrtc.rs refuses to create peer connections that would advertise public
+        // addresses along with mDNS host names, or along with those learned over STUN,
+        // so these are reported here instead.
+        let nat_1to1_conflict = match nat_1to1_candidate_type {
+            Nat1To1CandidateType::Host => {
+                matches!(ice_multicast_dns_mode, MulticastDnsMode::QueryAndGather)
+            }
+            Nat1To1CandidateType::Srflx => ice_servers.iter().any(|url| url.starts_with("stun")),
+        };
+
+        if nat_1to1_ips.is_some() && nat_1to1_conflict {
+            return Err(atoms::invalid_configuration());
+        }
+
+        Ok(Config {
+            ice_servers,
+            ice_candidate_format,
+            certificate,
+            command_buffer_size,
+            ice_candidate_pool_size,
+            udp_port_range,
+            ice_multicast_dns_mode,
+            ice_network_types,
+            ice_interfaces,
+            ice_disconnected_timeout,
+            ice_failed_timeout,
+            ice_keepalive_interval,
+            nat_1to1_ips,
+            nat_1to1_candidate_type,
+        })
     }
 
-    pub fn new(ice_servers: Vec<String>) -> Self {
-        Config { ice_servers }
+    /// The settings given to every API created from this config. These apply to the
+    /// transports of each peer connection, rather than to its `RTCConfiguration`.
+    pub fn setting_engine(&self) -> SettingEngine {
+        let mut setting_engine = SettingEngine::default();
+        setting_engine.set_ice_multicast_dns_mode(self.ice_multicast_dns_mode.into());
+
+        if let Some(network_types) = &self.ice_network_types {
+            let network_types = network_types.iter().map(|&t| NetworkType::from(t)).collect();
+            setting_engine.set_network_types(network_types);
+        }
+
+        if let Some(interfaces) = &self.ice_interfaces {
+            let interfaces = interfaces.clone();
+            setting_engine.set_interface_filter(Box::new(move |interface: &str| {
+                interfaces.iter().any(|allowed| allowed == interface)
+            }));
+        }
+
+        // Timeouts that are not given are left to the defaults of webrtc.rs.
+        setting_engine.set_ice_timeouts(
+            self.ice_disconnected_timeout.map(Duration::from_millis),
+            self.ice_failed_timeout.map(Duration::from_millis),
+            self.ice_keepalive_interval.map(Duration::from_millis),
+        );
+
+        if let Some(ips) = &self.nat_1to1_ips {
+            setting_engine.set_nat_1to1_ips(ips.clone(), self.nat_1to1_candidate_type.into());
+        }
+
+        // The range was checked when the config was parsed.
+        if let Some((min, max)) = self.udp_port_range {
+            if let Ok(ephemeral) = EphemeralUDP::new(min, max) {
+                setting_engine.set_udp_network(UDPNetwork::Ephemeral(ephemeral));
+            }
+        }
+
+        setting_engine
     }
 }
 
+/// Reads an optional list from the config, refusing lists that are empty or hold values
+/// of the wrong type.
+fn get_non_empty_list<'a, T: rustler::Decoder<'a>>(
+    env: Env<'a>,
+    opts: Term<'a>,
+    key: Atom,
+) -> Result<Option<Vec<T>>, Atom> {
+    match opts.map_get(key.to_term(env)) {
+        Err(_) => Ok(None),
+        Ok(list) => match list.decode::<Vec<T>>() {
+            Ok(list) if !list.is_empty() => Ok(Some(list)),
+            _ => Err(atoms::invalid_configuration()),
+        },
+    }
+}
+
+/// Reads an optional duration from the config, in milliseconds. Zero is refused, since
+/// what webrtc.rs makes of a zero duration differs from one timeout to the next.
+fn get_millis<'a>(env: Env<'a>, opts: Term<'a>, key: Atom) -> Result<Option<u64>, Atom> {
+    match opts.map_get(key.to_term(env)) {
+        Err(_) => Ok(None),
+        Ok(millis) => match millis.decode::<Option<u64>>() {
+            Ok(Some(0)) | Err(_) => Err(atoms::invalid_configuration()),
+            Ok(millis) => Ok(millis),
+        },
+    }
+}
+
+/// Checks the scheme of an ICE server URL, so that a typo is reported when the config
+/// is parsed rather than as a peer connection that never gathers candidates.
+fn valid_ice_server(url: &str) -> bool {
+    ICE_SERVER_SCHEMES
+        .iter()
+        .any(|scheme| url.len() > scheme.len() && url.starts_with(scheme))
+}
+
+/// Each configured URL is its own ICE server, rather than one of the URLs of a single
+/// server, so that servers are treated as distinct and may carry their own credentials.
+fn rtc_ice_servers(urls: &[String]) -> Vec<RTCIceServer> {
+    urls.iter()
+        .map(|url| RTCIceServer {
+            urls: vec![url.clone()],
+            ..Default::default()
+        })
+        .collect()
+}
+
 impl From<&Config> for RTCConfiguration {
     fn from(config: &Config) -> Self {
         RTCConfiguration {
-            ice_servers: vec![RTCIceServer {
-                urls: config.ice_servers.clone(),
-                ..Default::default()
-            }],
+            ice_servers: rtc_ice_servers(&config.ice_servers),
+            certificates: config
+                .certificate
+                .iter()
+                .map(|c| c.rtc_certificate.clone())
+                .collect(),
+            ice_candidate_pool_size: config.ice_candidate_pool_size,
             ..Default::default()
         }
     }
@@ -45,15 +344,46 @@ impl From<&Config> for RTCConfiguration {
 impl From<Config> for RTCConfiguration {
     fn from(config: Config) -> Self {
         RTCConfiguration {
-            ice_servers: vec![RTCIceServer {
-                urls: config.ice_servers,
-                ..Default::default()
-            }],
+            ice_servers: rtc_ice_servers(&config.ice_servers),
+            certificates: config
+                .certificate
+                .into_iter()
+                .map(|c| c.rtc_certificate)
+                .collect(),
+            ice_candidate_pool_size: config.ice_candidate_pool_size,
             ..Default::default()
         }
     }
 }
 
+/// The configuration in effect on a live peer connection, as reported by webrtc. Unlike
+/// `Config`, this reflects what webrtc made of the configuration, such as policies it
+/// defaulted.
+#[derive(NifMap)]
+pub struct PeerConnectionConfiguration {
+    ice_servers: Vec<String>,
+    ice_transport_policy: String,
+    bundle_policy: String,
+    rtcp_mux_policy: String,
+    ice_candidate_pool_size: u8,
+}
+
+impl From<&RTCConfiguration> for PeerConnectionConfiguration {
+    fn from(config: &RTCConfiguration) -> Self {
+        PeerConnectionConfiguration {
+            ice_servers: config
+                .ice_servers
+                .iter()
+                .flat_map(|server| server.urls.iter().cloned())
+                .collect(),
+            ice_transport_policy: config.ice_transport_policy.to_string(),
+            bundle_policy: config.bundle_policy.to_string(),
+            rtcp_mux_policy: config.rtcp_mux_policy.to_string(),
+            ice_candidate_pool_size: config.ice_candidate_pool_size,
+        }
+    }
+}
+
 impl Encoder for Config {
     fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
         let config = elixir_struct::make_ex_struct(env, "Elixir.Specter.Config").unwrap();
@@ -71,5 +401,70 @@ impl Encoder for Config {
         config
             .map_put(atoms::ice_servers().to_term(env), ice_servers)
             .unwrap()
+            .map_put(
+                atoms::ice_candidate_format().to_term(env),
+                self.ice_candidate_format.encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::certificate().to_term(env),
+                self.certificate.as_ref().map(|c| c.pem.clone()).encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::command_buffer_size().to_term(env),
+                self.command_buffer_size.encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::ice_candidate_pool_size().to_term(env),
+                self.ice_candidate_pool_size.encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::udp_port_range().to_term(env),
+                self.udp_port_range.encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::ice_multicast_dns_mode().to_term(env),
+                self.ice_multicast_dns_mode.encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::ice_network_types().to_term(env),
+                self.ice_network_types.encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::ice_interfaces().to_term(env),
+                self.ice_interfaces.encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::ice_disconnected_timeout().to_term(env),
+                self.ice_disconnected_timeout.encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::ice_failed_timeout().to_term(env),
+                self.ice_failed_timeout.encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::ice_keepalive_interval().to_term(env),
+                self.ice_keepalive_interval.encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::nat_1to1_ips().to_term(env),
+                self.nat_1to1_ips.encode(env),
+            )
+            .unwrap()
+            .map_put(
+                atoms::nat_1to1_candidate_type().to_term(env),
+                self.nat_1to1_candidate_type.encode(env),
+            )
+            .unwrap()
     }
 }
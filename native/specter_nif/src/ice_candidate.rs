@@ -0,0 +1,41 @@
+use rustler::{NifStruct, NifUnitEnum};
+use webrtc::ice_transport::ice_candidate::RTCIceCandidateInit;
+
+/// How ICE candidates gathered by a peer connection are sent to Elixir: as JSON
+/// strings, or as `%Specter.IceCandidate{}` structs.
+#[derive(Clone, Copy, Debug, NifUnitEnum)]
+pub enum IceCandidateFormat {
+    Json,
+    Struct,
+}
+
+#[derive(NifStruct)]
+#[module = "Specter.IceCandidate"]
+pub struct IceCandidate {
+    pub candidate: String,
+    pub sdp_mid: Option<String>,
+    pub sdp_mline_index: Option<u16>,
+    pub username_fragment: Option<String>,
+}
+
+impl From<IceCandidate> for RTCIceCandidateInit {
+    fn from(ice_candidate: IceCandidate) -> Self {
+        RTCIceCandidateInit {
+            candidate: ice_candidate.candidate,
+            sdp_mid: ice_candidate.sdp_mid,
+            sdp_mline_index: ice_candidate.sdp_mline_index,
+            username_fragment: ice_candidate.username_fragment,
+        }
+    }
+}
+
+impl From<RTCIceCandidateInit> for IceCandidate {
+    fn from(init: RTCIceCandidateInit) -> Self {
+        IceCandidate {
+            candidate: init.candidate,
+            sdp_mid: init.sdp_mid,
+            sdp_mline_index: init.sdp_mline_index,
+            username_fragment: init.username_fragment,
+        }
+    }
+}
@@ -1,9 +1,13 @@
 use rustler::{Env, Term};
 
 mod atoms;
+mod certificate;
 mod codec_capability;
 mod config;
+mod ice_candidate;
 mod peer_connection;
+mod registry;
+mod session_description;
 mod state;
 mod task;
 mod track;
@@ -21,36 +25,96 @@ fn on_load(env: Env, _info: Term) -> bool {
 rustler::init!(
     "Elixir.Specter.Native",
     [
+        peer_connection::add_encoding,
         peer_connection::add_ice_candidate,
         peer_connection::add_track,
+        peer_connection::add_transceiver,
+        peer_connection::can_trickle_ice_candidates,
+        peer_connection::clear_receive_bitrate_cap,
         peer_connection::close,
+        peer_connection::close_sync,
+        peer_connection::command_buffer,
         peer_connection::connection_state,
         peer_connection::create_answer,
+        peer_connection::create_answer_sync,
         peer_connection::create_data_channel,
+        peer_connection::create_data_channel_sync,
         peer_connection::create_offer,
+        peer_connection::create_offer_if_needed,
+        peer_connection::create_offer_sync,
+        peer_connection::data_channel_buffered_amount,
+        peer_connection::drain,
+        peer_connection::forward_track,
+        peer_connection::gathering_complete_promise,
+        peer_connection::get_configuration,
         peer_connection::get_current_local_description,
         peer_connection::get_current_remote_description,
+        peer_connection::get_gathered_local_description,
         peer_connection::get_local_description,
         peer_connection::get_remote_description,
         peer_connection::get_pending_local_description,
         peer_connection::get_pending_remote_description,
+        peer_connection::get_outbound_video_stats,
+        peer_connection::get_receivers,
+        peer_connection::get_sender_parameters,
+        peer_connection::get_selected_candidate_pair,
+        peer_connection::get_senders,
         peer_connection::get_stats,
+        peer_connection::get_track_stats,
         peer_connection::ice_connection_state,
         peer_connection::ice_gathering_state,
         peer_connection::new,
+        peer_connection::peer_connection_fingerprint,
+        peer_connection::record_to_file,
+        peer_connection::replace_track,
+        peer_connection::request_keyframe,
+        peer_connection::restart_ice,
+        peer_connection::sctp_transport,
+        peer_connection::sender_active,
+        peer_connection::sender_stats,
+        peer_connection::set_codec_preferences,
+        peer_connection::set_data_channel_buffered_amount_low_threshold,
         peer_connection::set_local_description,
+        peer_connection::set_receive_bitrate_cap,
         peer_connection::set_remote_description,
+        peer_connection::set_remote_sdp,
+        peer_connection::set_rtp_forwarding,
         peer_connection::signaling_state,
+        peer_connection::snapshot,
+        peer_connection::start_stats_reporting,
+        peer_connection::stop_stats_reporting,
+        peer_connection::transceiver_current_direction,
+        peer_connection::transceiver_mid,
+        peer_connection::write_rtcp,
+        session_description::sdp_json_to_struct,
+        session_description::sdp_struct_to_json,
+        state::clone_media_engine,
+        state::destroy_api,
         state::get_config,
         state::init,
+        state::list_apis,
+        state::list_media_engines,
+        state::list_peer_connections,
+        state::list_registries,
+        state::list_tracks,
+        state::media_engine_codecs,
         state::media_engine_exists,
         state::new_api,
+        state::new_api_with_defaults,
         state::new_media_engine,
         state::new_registry,
+        state::new_track_local_static_rtp,
         state::new_track_local_static_sample,
         state::peer_connection_exists,
+        state::register_codec,
         state::registry_exists,
+        track::delete_track,
+        track::play_audio_from_file,
         track::play_from_file_h264,
+        track::play_vp8_from_file,
+        track::stop_playback,
+        track::write_track_rtp,
+        track::write_track_sample,
     ],
     load = on_load
 );
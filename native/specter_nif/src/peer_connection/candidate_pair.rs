@@ -0,0 +1,74 @@
+use rustler::NifMap;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::stats::StatsReportType;
+
+/// One side of a candidate pair. `candidate_type` is one of `host`, `srflx`, `prflx`
+/// or `relay`, and `protocol` is `udp` or `tcp`.
+#[derive(NifMap)]
+pub struct CandidateInfo {
+    candidate_type: String,
+    protocol: String,
+    address: String,
+    port: u16,
+}
+
+impl CandidateInfo {
+    fn from_report(report: &Value) -> Option<Self> {
+        // The network type is given as `udp4`, `tcp6`, etc.
+        let network_type = report["networkType"].as_str()?;
+
+        Some(CandidateInfo {
+            candidate_type: report["candidateType"].as_str()?.to_owned(),
+            protocol: network_type.trim_end_matches(char::is_numeric).to_owned(),
+            address: report["ip"].as_str()?.to_owned(),
+            port: u16::try_from(report["port"].as_u64()?).ok()?,
+        })
+    }
+}
+
+/// The local and remote candidates of the candidate pair selected by ICE.
+#[derive(NifMap)]
+pub struct CandidatePair {
+    local: CandidateInfo,
+    remote: CandidateInfo,
+}
+
+/// Finds the nominated candidate pair in the stats of the peer connection, and looks up
+/// its local and remote candidates. The candidates of the pair held by the ICE transport
+/// are private, so the details are read from the serialized stats reports instead.
+pub async fn selected(pc: &RTCPeerConnection) -> Option<CandidatePair> {
+    let mut pair = None;
+    let mut local_candidates = HashMap::new();
+    let mut remote_candidates = HashMap::new();
+
+    for (id, report) in pc.get_stats().await.reports {
+        match report {
+            StatsReportType::CandidatePair(stats) if stats.nominated => {
+                pair = serde_json::to_value(&stats).ok();
+            }
+            StatsReportType::LocalCandidate(stats) => {
+                if let Ok(stats) = serde_json::to_value(&stats) {
+                    local_candidates.insert(id, stats);
+                }
+            }
+            StatsReportType::RemoteCandidate(stats) => {
+                if let Ok(stats) = serde_json::to_value(&stats) {
+                    remote_candidates.insert(id, stats);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let pair = pair?;
+    let local = local_candidates.get(pair["localCandidateId"].as_str()?)?;
+    let remote = remote_candidates.get(pair["remoteCandidateId"].as_str()?)?;
+
+    Some(CandidatePair {
+        local: CandidateInfo::from_report(local)?,
+        remote: CandidateInfo::from_report(remote)?,
+    })
+}
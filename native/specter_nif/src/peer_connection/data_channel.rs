@@ -0,0 +1,50 @@
+use crate::atoms;
+use rustler::{Atom, Decoder, Env, Term};
+use webrtc::data_channel::data_channel_init::RTCDataChannelInit;
+
+/// Parses the options of a new data channel. Options that are not given, or are nil,
+/// are left to webrtc.rs, which creates an ordered and reliable channel. `id` is only
+/// used for negotiated channels, and must be given with `negotiated: true`.
+pub fn parse_options<'a>(env: Env<'a>, opts: Term<'a>) -> Result<RTCDataChannelInit, Atom> {
+    if !opts.is_map() {
+        return Err(atoms::invalid_data_channel_options());
+    };
+
+    let ordered = get(env, opts, atoms::ordered())?;
+    let max_packet_life_time = get(env, opts, atoms::max_packet_life_time())?;
+    let max_retransmits = get(env, opts, atoms::max_retransmits())?;
+    let protocol = get(env, opts, atoms::protocol())?;
+    let negotiated: Option<bool> = get(env, opts, atoms::negotiated())?;
+    let id: Option<u16> = get(env, opts, atoms::id())?;
+
+    // A channel is either limited by retransmits or by packet lifetime, never by both.
+    if max_packet_life_time.is_some() && max_retransmits.is_some() {
+        return Err(atoms::invalid_data_channel_options());
+    }
+
+    // webrtc.rs takes the id of a negotiated channel in place of the flag. Each is
+    // meaningless without the other.
+    let negotiated = match (negotiated, id) {
+        (Some(true), None) => return Err(atoms::invalid_data_channel_options()),
+        (Some(true), Some(id)) => Some(id),
+        (_, Some(_)) => return Err(atoms::invalid_data_channel_options()),
+        (_, None) => None,
+    };
+
+    Ok(RTCDataChannelInit {
+        ordered,
+        max_packet_life_time,
+        max_retransmits,
+        protocol,
+        negotiated,
+    })
+}
+
+fn get<'a, T: Decoder<'a>>(env: Env<'a>, opts: Term<'a>, key: Atom) -> Result<Option<T>, Atom> {
+    match opts.map_get(key.to_term(env)) {
+        Err(_) => Ok(None),
+        Ok(value) => value
+            .decode()
+            .map_err(|_| atoms::invalid_data_channel_options()),
+    }
+}
@@ -0,0 +1,60 @@
+use super::peer_conn_state::{
+    ConnectionState, IceConnectionState, IceGatheringState, SignalingState,
+};
+use rustler::NifMap;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::stats::StatsReportType;
+
+/// A summary of the health of a peer connection, gathered in one pass. `senders` counts
+/// the senders with a track attached, and `receivers` those receiving at least one
+/// remote track. `round_trip_time` is that of the selected candidate pair, in seconds,
+/// and is `None` until ICE has selected a pair.
+#[derive(NifMap)]
+pub struct Snapshot {
+    connection_state: ConnectionState,
+    ice_connection_state: IceConnectionState,
+    ice_gathering_state: IceGatheringState,
+    signaling_state: SignalingState,
+    senders: usize,
+    receivers: usize,
+    round_trip_time: Option<f64>,
+}
+
+pub async fn snapshot(pc: &RTCPeerConnection) -> Snapshot {
+    let mut senders = 0;
+    let mut receivers = 0;
+
+    // webrtc.rs gives every transceiver both a sender and a receiver, so they are only
+    // counted once in use.
+    for transceiver in pc.get_transceivers().await {
+        if transceiver.sender().await.track().await.is_some() {
+            senders += 1;
+        }
+
+        if !transceiver.receiver().await.tracks().await.is_empty() {
+            receivers += 1;
+        }
+    }
+
+    let round_trip_time = pc
+        .get_stats()
+        .await
+        .reports
+        .into_values()
+        .find_map(|report| match report {
+            StatsReportType::CandidatePair(stats) if stats.nominated => {
+                Some(stats.current_round_trip_time)
+            }
+            _ => None,
+        });
+
+    Snapshot {
+        connection_state: ConnectionState::from(&pc.connection_state()),
+        ice_connection_state: IceConnectionState::from(&pc.ice_connection_state()),
+        ice_gathering_state: IceGatheringState::from(&pc.ice_gathering_state()),
+        signaling_state: SignalingState::from(&pc.signaling_state()),
+        senders,
+        receivers,
+        round_trip_time,
+    }
+}
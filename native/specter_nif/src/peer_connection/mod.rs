@@ -1,42 +1,135 @@
 use crate::atoms;
+use crate::certificate::Fingerprint;
+use crate::codec_capability::{RtpCodecCapability, TrackKind};
+use crate::config::PeerConnectionConfiguration;
+use crate::ice_candidate::{IceCandidate, IceCandidateFormat};
+use crate::session_description::{SdpError, SdpType, SessionDescription};
 use crate::state::Ref;
 use crate::task;
 use crate::util::gen_uuid;
 use log::trace;
-use rustler::{Atom, Encoder, Env, ResourceArc, Term};
+use rustler::{Atom, Encoder, Env, LocalPid, NifMap, ResourceArc, Term};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::mpsc::channel;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, Sender};
+use tokio::sync::oneshot;
 use webrtc::api::API;
+use webrtc::data_channel::data_channel_init::RTCDataChannelInit;
+use webrtc::data_channel::RTCDataChannel;
+use webrtc::dtls_transport::dtls_transport_state::RTCDtlsTransportState;
 use webrtc::ice_transport::ice_candidate::{RTCIceCandidate, RTCIceCandidateInit};
-use webrtc::rtp_transceiver::rtp_sender::RTCRtpSender;
-use webrtc::track::track_local::TrackLocal;
-// use webrtc::peer_connection::sdp::sdp_type::RTCSdpType;
+use webrtc::ice_transport::ice_connection_state::RTCIceConnectionState;
 use webrtc::peer_connection::configuration::RTCConfiguration;
 use webrtc::peer_connection::offer_answer_options::{RTCAnswerOptions, RTCOfferOptions};
+use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+use webrtc::peer_connection::sdp::sdp_type::RTCSdpType;
 use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::peer_connection::signaling_state::RTCSignalingState;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::rtcp::payload_feedbacks::picture_loss_indication::PictureLossIndication;
+use webrtc::rtcp::receiver_estimated_maximum_bitrate::ReceiverEstimatedMaximumBitrate;
+use webrtc::rtp_transceiver::rtp_codec::{
+    RTCRtpCodecCapability, RTCRtpCodecParameters, RTPCodecType,
+};
+use webrtc::rtp_transceiver::rtp_receiver::RTCRtpReceiver;
+use webrtc::rtp_transceiver::rtp_sender::RTCRtpSender;
+use webrtc::rtp_transceiver::rtp_transceiver_direction::RTCRtpTransceiverDirection;
+use webrtc::rtp_transceiver::{RTCRtpTransceiver, RTCRtpTransceiverInit};
+use webrtc::stats::StatsReportType;
+use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
+use webrtc::track::track_local::TrackLocal;
+use webrtc::track::track_remote::TrackRemote;
 
+mod candidate_pair;
+mod data_channel;
+mod health;
 mod peer_conn_state;
+mod remote_track;
+mod rtcp;
+mod rtp_receiver;
+mod rtp_sender;
+mod sctp;
+mod transceiver;
+
+// How often a receive bitrate cap is re-sent to the remote peer as REMB.
+const REMB_INTERVAL: Duration = Duration::from_secs(1);
 
 pub enum Msg {
+    AddEncoding(
+        String,
+        Arc<dyn TrackLocal + Send + Sync>,
+        oneshot::Sender<Option<Result<(), String>>>,
+    ),
     AddIceCandidate(RTCIceCandidateInit),
-    AddTrack(String, Arc<dyn TrackLocal + Send + Sync>),
+    AddTrack(String, String, Arc<dyn TrackLocal + Send + Sync>),
+    AddTransceiver(RTPCodecType, RTCRtpTransceiverDirection),
+    CanTrickleIceCandidates(oneshot::Sender<Option<bool>>),
+    Close(oneshot::Sender<()>),
     CreateAnswer(Option<RTCAnswerOptions>),
-    CreateDataChannel(String),
+    CreateAnswerSync(Option<RTCAnswerOptions>, oneshot::Sender<Result<String, String>>),
+    CreateDataChannel(String, RTCDataChannelInit),
+    CreateDataChannelSync(
+        String,
+        RTCDataChannelInit,
+        oneshot::Sender<Option<Result<String, webrtc::Error>>>,
+    ),
     CreateOffer(Option<RTCOfferOptions>),
+    CreateOfferIfNeeded(Option<RTCOfferOptions>, oneshot::Sender<bool>),
+    CreateOfferSync(Option<RTCOfferOptions>, oneshot::Sender<Result<String, String>>),
+    DataChannelBufferedAmount(String, oneshot::Sender<Option<usize>>),
+    Drain(Duration),
+    ForwardTrack(String, Vec<Weak<TrackLocalStaticRTP>>),
+    GatheringCompletePromise,
+    GetConfiguration(oneshot::Sender<PeerConnectionConfiguration>),
     GetCurrentLocalDescription,
     GetCurrentRemoteDescription,
+    GetFingerprint(oneshot::Sender<Option<Fingerprint>>),
+    GetGatheredLocalDescription,
     GetLocalDescription,
     GetPendingLocalDescription,
     GetPendingRemoteDescription,
+    GetReceivers,
     GetRemoteDescription,
+    GetSelectedCandidatePair,
+    GetOutboundVideoStats(String, oneshot::Sender<Option<OutboundVideoStats>>),
+    GetSenderParameters(String, oneshot::Sender<Option<rtp_sender::SendParameters>>),
+    GetSenderStats(String),
+    GetSctpTransport(oneshot::Sender<sctp::SctpTransportInfo>),
+    GetSenders,
     GetStats,
+    GetTrackStats(StatsTrack, oneshot::Sender<Option<HashMap<String, StatsReportType>>>),
     SetLocalDescription(RTCSessionDescription),
-    SetRemoteDescription(RTCSessionDescription),
+    SetRemoteDescription(RTCSessionDescription, Option<bool>),
     IceConnectionState,
     IceGatheringState,
     SignalingState,
     ConnectionState,
+    ReplaceTrack(
+        String,
+        Option<Arc<dyn TrackLocal + Send + Sync>>,
+        oneshot::Sender<Option<Result<(), webrtc::Error>>>,
+    ),
+    RecordToFile(String, File),
+    RequestKeyframe(String),
+    RestartIce,
+    SenderActive(String, oneshot::Sender<Option<bool>>),
+    SetBufferedAmountLowThreshold(String, usize, oneshot::Sender<Option<()>>),
+    SetCodecPreferences(
+        String,
+        Vec<RTCRtpCodecParameters>,
+        oneshot::Sender<Option<Result<(), webrtc::Error>>>,
+    ),
+    SetReceiveBitrateCap(Option<u64>),
+    SetRtpForwarding(String, bool),
+    SetStatsReporting(Option<Duration>),
+    Snapshot,
+    TransceiverCurrentDirection(String, oneshot::Sender<Option<RTCRtpTransceiverDirection>>),
+    TransceiverMid(String, oneshot::Sender<Option<Option<String>>>),
+    WriteRtcp(rtcp::Rtcp),
 }
 
 /// Create a new RTCPeerConnection.
@@ -44,30 +137,67 @@ pub enum Msg {
 /// Open questions:
 /// - Once this is initialized, how does it run in a thread that doesn't conflict
 ///   with the Erlang scheduler?
+///
+/// `command_buffer_size` overrides the size of the command buffer from the config
+/// for this peer connection only.
 #[rustler::nif(name = "new_peer_connection")]
-fn new(resource: ResourceArc<Ref>, api_uuid: Term) -> Result<String, Atom> {
+fn new(
+    resource: ResourceArc<Ref>,
+    api_uuid: Term,
+    command_buffer_size: Option<usize>,
+) -> Result<String, Atom> {
+    if command_buffer_size == Some(0) {
+        return Err(atoms::invalid_command_buffer_size());
+    }
+
+    let decoded_api_uuid: String = match api_uuid.decode() {
+        Err(_) => return Err(atoms::not_found()),
+        Ok(uuid) => uuid,
+    };
+
+    let uuid = gen_uuid();
+
     let api = {
-        let state_ref = resource.0.lock().unwrap();
-        match state_ref.get_api(api_uuid) {
+        let mut state_ref = resource.lock();
+        let api = match state_ref.get_api(api_uuid) {
             None => return Err(atoms::not_found()),
             Some(a) => Arc::clone(a),
-        }
+        };
+        state_ref.add_peer_connection_api(&uuid, &decoded_api_uuid);
+        api
     };
 
-    let uuid = gen_uuid();
-    spawn_rtc_peer_connection(resource, api, uuid.clone());
+    spawn_rtc_peer_connection(resource, api, uuid.clone(), command_buffer_size);
 
     Ok(uuid)
 }
 
+/// The size of a peer connection's command buffer, and how many more commands fit
+/// in it before commands are refused as `busy`.
+#[derive(NifMap)]
+pub struct CommandBuffer {
+    size: usize,
+    available: usize,
+}
+
+#[rustler::nif(name = "peer_connection_command_buffer")]
+fn command_buffer(resource: ResourceArc<Ref>, pc_uuid: Term) -> Result<CommandBuffer, Atom> {
+    let state = resource.lock();
+
+    match state.get_peer_connection(pc_uuid) {
+        None => Err(atoms::not_found()),
+        Some(tx) => Ok(CommandBuffer {
+            size: tx.max_capacity(),
+            available: tx.capacity(),
+        }),
+    }
+}
+
 /// Close an RTCPeerConnection. This pops out the Sender for the task holding the peer connection,
 /// causing it to go out of scope. That causes a `None` to come out of the Sender's recv block.
 #[rustler::nif(name = "close_peer_connection")]
 fn close<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
-    let mut state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+    let mut state = resource.lock();
 
     let _tx = match state.remove_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
@@ -77,38 +207,92 @@ fn close<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Ter
     (atoms::ok()).encode(env)
 }
 
+/// Close an RTCPeerConnection, blocking until it has been closed. Like `close`, this
+/// removes the peer connection from the state, but it then waits for the task holding
+/// the peer connection to close it and tear down its transports, so that the peer
+/// connection is gone once this returns.
+#[rustler::nif(name = "close_peer_connection_sync", schedule = "DirtyIo")]
+fn close_sync<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
+    let tx = {
+        let mut state = resource.lock();
+
+        match state.remove_peer_connection(pc_uuid) {
+            None => return (atoms::error(), atoms::not_found()).encode(env),
+            Some(tx) => tx,
+        }
+    };
+
+    match call(tx, Msg::Close) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => atoms::ok().encode(env),
+    }
+}
+
 #[rustler::nif]
 fn add_ice_candidate<'a>(
     env: Env<'a>,
     resource: ResourceArc<Ref>,
     pc_uuid: Term<'a>,
-    candidate: String,
+    candidate: Term<'a>,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    let ice_candidate = match serde_json::from_str::<RTCIceCandidateInit>(&candidate) {
-        Err(_) => return (atoms::error(), atoms::invalid_json()).encode(env),
-        Ok(s) => s,
+    let ice_candidate = match decode_ice_candidate(candidate) {
+        Err(reason) => return (atoms::error(), reason).encode(env),
+        Ok(c) => c,
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::AddIceCandidate(ice_candidate)).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
+    match try_send(&tx, Msg::AddIceCandidate(ice_candidate)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Adds a track as another encoding of the RTP sender given by uuid, for simulcast.
+/// The sender must have been created by `add_track` with a track that has a rid, and
+/// the track must have a different rid but the same id, stream id and kind. Blocks
+/// until the peer connection replies.
+#[rustler::nif(schedule = "DirtyIo")]
+fn add_encoding<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    sender_uuid: String,
+    track_uuid: String,
+) -> Term<'a> {
+    let (tx, track) = {
+        let mut state = resource.lock();
+
+        let tx = match state.get_peer_connection(pc_uuid) {
+            None => return (atoms::error(), atoms::not_found()).encode(env),
+            Some(tx) => tx.clone(),
+        };
+
+        match state.get_track_local(&track_uuid) {
+            None => return (atoms::error(), atoms::invalid_track()).encode(env),
+            Some(track) => (tx, track),
         }
-    });
+    };
 
-    (atoms::ok()).encode(env)
+    match call(tx, |reply| Msg::AddEncoding(sender_uuid, track, reply)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(None) => (atoms::error(), atoms::not_found()).encode(env),
+        Ok(Some(Err(reason))) => (atoms::error(), reason).encode(env),
+        Ok(Some(Ok(()))) => atoms::ok().encode(env),
+    }
 }
 
+/// Adds a local track to a peer connection. The uuid of the RTP sender is generated
+/// here and returned right away, so that it can be used before the peer connection
+/// has reported `rtp_sender`. The track is queued before this returns, so calls given
+/// the sender uuid are handled after the track has been added. A sender is only
+/// created once the track has been added, so when the track is refused with
+/// `draining_error` or `track_error` the uuid never refers to one.
 #[rustler::nif]
 fn add_track<'a>(
     env: Env<'a>,
@@ -116,35 +300,66 @@ fn add_track<'a>(
     pc_uuid: Term<'a>,
     track_uuid: Term<'a>,
 ) -> Term<'a> {
-    let mut state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
+    let decoded_track_uuid: String = match track_uuid.decode() {
+        Err(_) => return (atoms::error(), atoms::invalid_track()).encode(env),
+        Ok(uuid) => uuid,
+    };
+
+    let (tx, track) = {
+        let mut state = resource.lock();
+
+        let tx = match state.get_peer_connection(pc_uuid) {
+            None => return (atoms::error(), atoms::not_found()).encode(env),
+            Some(tx) => tx.clone(),
+        };
+
+        match state.get_track_local(&decoded_track_uuid) {
+            None => return (atoms::error(), atoms::invalid_track()).encode(env),
+            Some(track) => (tx, track),
+        }
     };
 
+    let sender_uuid = gen_uuid();
+    let msg = Msg::AddTrack(decoded_track_uuid, sender_uuid.clone(), track);
+
+    match try_send(&tx, msg) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok(), sender_uuid).encode(env),
+    }
+}
+
+/// Adds a transceiver of the given kind and direction, so that its media section is
+/// included in offers before any track is added. Media sections are offered in the
+/// order their transceivers were added.
+#[rustler::nif]
+fn add_transceiver<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    kind: Term<'a>,
+    direction: Term<'a>,
+) -> Term<'a> {
+    let state = resource.lock();
+
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    let decoded_track_uuid = track_uuid.decode().unwrap();
+    let kind = match kind.decode::<TrackKind>() {
+        Err(_) => return (atoms::error(), atoms::invalid_atom()).encode(env),
+        Ok(kind) => RTPCodecType::from(kind),
+    };
+
+    let direction = match direction.decode::<transceiver::TransceiverDirection>() {
+        Err(_) => return (atoms::error(), atoms::invalid_atom()).encode(env),
+        Ok(direction) => RTCRtpTransceiverDirection::from(direction),
+    };
 
-    match state.get_track_local_static_sample(&decoded_track_uuid) {
-        None => return (atoms::error(), atoms::invalid_track()).encode(env),
-        Some(track) => {
-            let track_arc_clone = track.clone();
-            task::spawn(async move {
-                match tx
-                    .send(Msg::AddTrack(decoded_track_uuid, track_arc_clone))
-                    .await
-                {
-                    Ok(_) => (),
-                    Err(_err) => trace!("send error"),
-                }
-            });
-        }
+    match try_send(&tx, Msg::AddTransceiver(kind, direction)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
     }
-
-    (atoms::ok()).encode(env)
 }
 
 #[rustler::nif]
@@ -154,10 +369,7 @@ fn create_answer<'a>(
     pc_uuid: Term<'a>,
     voice_activity_detection: bool,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
@@ -168,43 +380,85 @@ fn create_answer<'a>(
         voice_activity_detection,
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::CreateAnswer(Some(answer_opts))).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
+    match try_send(&tx, Msg::CreateAnswer(Some(answer_opts))) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
 
-    (atoms::ok()).encode(env)
+/// Create an answer as `create_answer` does, but return its JSON instead of sending it
+/// back, blocking until the peer connection replies.
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_answer_sync<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    voice_activity_detection: bool,
+) -> Term<'a> {
+    let answer_opts = RTCAnswerOptions {
+        voice_activity_detection,
+    };
+
+    let resp = get_sender(&resource, pc_uuid)
+        .and_then(|tx| call(tx, |reply| Msg::CreateAnswerSync(Some(answer_opts), reply)));
+
+    encode_description_reply(env, resp)
 }
 
-/// Implemented without options to facilitate the creation of offers with ufrag and pwd,
-/// so that these offers may be given to other peer connections without errors.
+/// Creates a data channel, which also facilitates the creation of offers with ufrag and
+/// pwd, so that these offers may be given to other peer connections without errors.
+/// See `data_channel::parse_options` for the options.
 #[rustler::nif]
 fn create_data_channel<'a>(
     env: Env<'a>,
     resource: ResourceArc<Ref>,
     pc_uuid: Term<'a>,
     label: String,
+    opts: Term<'a>,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
+    let init = match data_channel::parse_options(env, opts) {
+        Err(reason) => return (atoms::error(), reason).encode(env),
+        Ok(init) => init,
     };
 
+    let state = resource.lock();
+
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::CreateDataChannel(label)).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
+    match try_send(&tx, Msg::CreateDataChannel(label, init)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
 
-    (atoms::ok()).encode(env)
+/// Creates a data channel as `create_data_channel` does, but returns the uuid that
+/// identifies it in later calls, such as `data_channel_buffered_amount`, blocking until
+/// the peer connection replies. Returns `draining` once the peer connection is draining.
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_data_channel_sync<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    label: String,
+    opts: Term<'a>,
+) -> Term<'a> {
+    let init = match data_channel::parse_options(env, opts) {
+        Err(reason) => return (atoms::error(), reason).encode(env),
+        Ok(init) => init,
+    };
+
+    let resp = get_sender(&resource, pc_uuid)
+        .and_then(|tx| call(tx, |reply| Msg::CreateDataChannelSync(label, init, reply)));
+
+    match resp {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(None) => (atoms::error(), atoms::draining()).encode(env),
+        Ok(Some(Err(err))) => (atoms::error(), err.to_string()).encode(env),
+        Ok(Some(Ok(data_channel_uuid))) => (atoms::ok(), data_channel_uuid).encode(env),
+    }
 }
 
 /// Create an offer. Note that media tracks and data channels must be given to these
@@ -217,10 +471,7 @@ fn create_offer<'a>(
     voice_activity_detection: bool,
     ice_restart: bool,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
@@ -232,14 +483,108 @@ fn create_offer<'a>(
         voice_activity_detection,
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::CreateOffer(Some(offer_opts))).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
+    match try_send(&tx, Msg::CreateOffer(Some(offer_opts))) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
 
-    (atoms::ok()).encode(env)
+/// Create an offer as `create_offer` does, but only when the signaling state is
+/// `stable`. Otherwise an offer or answer is already under way, and creating another
+/// would risk glare, so `{:ok, :no_offer_needed}` is returned instead. The check is made
+/// by the peer connection task right before creating the offer, so that it cannot race
+/// with descriptions being set. Blocks until the peer connection replies, while the
+/// offer itself is sent back as with `create_offer`.
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_offer_if_needed<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    voice_activity_detection: bool,
+    ice_restart: bool,
+) -> Term<'a> {
+    let offer_opts = RTCOfferOptions {
+        ice_restart,
+        voice_activity_detection,
+    };
+
+    let resp = get_sender(&resource, pc_uuid)
+        .and_then(|tx| call(tx, |reply| Msg::CreateOfferIfNeeded(Some(offer_opts), reply)));
+
+    match resp {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(true) => atoms::ok().encode(env),
+        Ok(false) => (atoms::ok(), atoms::no_offer_needed()).encode(env),
+    }
+}
+
+/// Create an offer as `create_offer` does, but return its JSON instead of sending it
+/// back, blocking until the peer connection replies.
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_offer_sync<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    voice_activity_detection: bool,
+    ice_restart: bool,
+) -> Term<'a> {
+    let offer_opts = RTCOfferOptions {
+        ice_restart,
+        voice_activity_detection,
+    };
+
+    let resp = get_sender(&resource, pc_uuid)
+        .and_then(|tx| call(tx, |reply| Msg::CreateOfferSync(Some(offer_opts), reply)));
+
+    encode_description_reply(env, resp)
+}
+
+/// Drains a peer connection before closing it. Media already flowing on existing
+/// tracks keeps flowing, but new tracks and data channels are refused. Once the
+/// grace period has elapsed the peer connection is closed as by `close`, sending
+/// `peer_connection_closed`.
+#[rustler::nif(name = "drain_peer_connection")]
+fn drain<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    grace_period_ms: u64,
+) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    let grace_period = Duration::from_millis(grace_period_ms);
+
+    match try_send(&tx, Msg::Drain(grace_period)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Sends `gathering_complete` once ICE gathering has finished. This is mostly useful
+/// for non-trickle ICE, where the local description should only be sent to the remote
+/// peer once it includes all candidates.
+#[rustler::nif]
+fn gathering_complete_promise<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::GatheringCompletePromise) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
 }
 
 /// Note that this is nil until the peer connection has successfully negotiated its connection.
@@ -249,24 +594,17 @@ fn get_current_local_description<'a>(
     resource: ResourceArc<Ref>,
     pc_uuid: Term<'a>,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::GetCurrentLocalDescription).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
-
-    (atoms::ok()).encode(env)
+    match try_send(&tx, Msg::GetCurrentLocalDescription) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
 }
 
 /// Note that this is nil until the peer connection has successfully negotiated its connection.
@@ -276,24 +614,39 @@ fn get_current_remote_description<'a>(
     resource: ResourceArc<Ref>,
     pc_uuid: Term<'a>,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::GetCurrentRemoteDescription).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
+    match try_send(&tx, Msg::GetCurrentRemoteDescription) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
 
-    (atoms::ok()).encode(env)
+/// Waits for ICE gathering to finish, then sends back the local description, which
+/// then includes all candidates. This saves non-trickle ICE from waiting for
+/// `gathering_complete` before asking for the local description.
+#[rustler::nif(name = "gathered_local_description")]
+fn get_gathered_local_description<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::GetGatheredLocalDescription) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
 }
 
 /// Sends back either the current or pending session description.
@@ -303,24 +656,17 @@ fn get_local_description<'a>(
     resource: ResourceArc<Ref>,
     pc_uuid: Term<'a>,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::GetLocalDescription).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
-
-    (atoms::ok()).encode(env)
+    match try_send(&tx, Msg::GetLocalDescription) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
 }
 
 /// Sends back either the current or pending session description.
@@ -330,24 +676,17 @@ fn get_remote_description<'a>(
     resource: ResourceArc<Ref>,
     pc_uuid: Term<'a>,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::GetRemoteDescription).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
-
-    (atoms::ok()).encode(env)
+    match try_send(&tx, Msg::GetRemoteDescription) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
 }
 
 /// Note that this may be nil after ICE negotiates.
@@ -357,24 +696,17 @@ fn get_pending_local_description<'a>(
     resource: ResourceArc<Ref>,
     pc_uuid: Term<'a>,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::GetPendingLocalDescription).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
-
-    (atoms::ok()).encode(env)
+    match try_send(&tx, Msg::GetPendingLocalDescription) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
 }
 
 /// Note that this may be nil after ICE negotiates.
@@ -384,106 +716,179 @@ fn get_pending_remote_description<'a>(
     resource: ResourceArc<Ref>,
     pc_uuid: Term<'a>,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::GetPendingRemoteDescription).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
-
-    (atoms::ok()).encode(env)
+    match try_send(&tx, Msg::GetPendingRemoteDescription) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
 }
 
+/// Sends back the local and remote candidates of the candidate pair selected by ICE,
+/// or `nil` until one has been selected.
 #[rustler::nif]
-fn get_stats<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+fn get_selected_candidate_pair<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+) -> Term<'a> {
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::GetStats).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
-
-    (atoms::ok()).encode(env)
+    match try_send(&tx, Msg::GetSelectedCandidatePair) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
 }
 
-/// Receives an offer or an answer pertaining to a specific peer connection,
-/// and sets it as the local session description.
+/// Sends back the states of the peer connection, how many senders and receivers are in
+/// use, and the round trip time of the selected candidate pair, as a single map. This
+/// is cheaper for dashboards than asking for each of these separately.
 #[rustler::nif]
-fn set_local_description<'a>(
-    env: Env<'a>,
-    resource: ResourceArc<Ref>,
-    pc_uuid: Term<'a>,
-    sdp: String,
-) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+fn snapshot<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    let session_description = match serde_json::from_str::<RTCSessionDescription>(&sdp) {
-        Err(_) => return (atoms::error(), atoms::invalid_json()).encode(env),
-        Ok(s) => s,
-    };
+    match try_send(&tx, Msg::Snapshot) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
 
-    task::spawn(async move {
-        match tx.send(Msg::SetLocalDescription(session_description)).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
+/// Sends back a summary of the RTP receiver of every transceiver, including the remote
+/// tracks that each one is receiving.
+#[rustler::nif]
+fn get_receivers<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
+    let state = resource.lock();
 
-    (atoms::ok()).encode(env)
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::GetReceivers) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Sends back a summary of every RTP sender created by `add_track`.
+#[rustler::nif]
+fn get_senders<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::GetSenders) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Sends back the bytes and packets sent by the given RTP sender, without the rest of
+/// the stats report. The stats are `None` when the peer connection has no such sender.
+#[rustler::nif]
+fn sender_stats<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    sender_uuid: String,
+) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::GetSenderStats(sender_uuid)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+#[rustler::nif]
+fn get_stats<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::GetStats) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Receives an offer or an answer pertaining to a specific peer connection,
+/// and sets it as the local session description. The SDP is parsed up front, so that
+/// SDP munged after `create_offer` is refused with the line it fails to parse at,
+/// rather than with a bare `invalid_local_description` once it has been applied.
+#[rustler::nif]
+fn set_local_description<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    sdp: String,
+) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    let session_description = match serde_json::from_str::<RTCSessionDescription>(&sdp) {
+        Err(_) => return (atoms::error(), atoms::invalid_json()).encode(env),
+        Ok(s) => s,
+    };
+
+    // A rollback has no SDP to check.
+    if session_description.sdp_type != RTCSdpType::Rollback {
+        if let Err(err) = SdpError::check(&session_description.sdp) {
+            return (atoms::error(), (atoms::invalid_sdp(), err)).encode(env);
+        }
+    }
+
+    match try_send(&tx, Msg::SetLocalDescription(session_description)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
 }
 
 /// Receives an offer or an answer from a remote entity, and sets it on an
 /// existing RTCPeerConnection.
+///
+/// When `polite` is given, an offer received while a local offer is pending (glare)
+/// is resolved as in perfect negotiation: a polite peer rolls back its own offer and
+/// accepts the remote one, while an impolite peer ignores the remote offer.
 #[rustler::nif]
 fn set_remote_description<'a>(
     env: Env<'a>,
     resource: ResourceArc<Ref>,
     pc_uuid: Term<'a>,
     sdp: String,
+    polite: Option<bool>,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
-
-    // let sdp_type = match sdp_type {
-    //     t if t == atoms::answer() => RTCSdpType::Answer,
-    //     t if t == atoms::offer() => RTCSdpType::Offer,
-    //     _ => return (atoms::error(), atoms::invalid_atom()).encode(env),
-    // };
-    // let session_description = RTCSessionDescription {
-    //     sdp_type,
-    //     sdp,
-    //     ..Default::default()
-    // };
+    let state = resource.lock();
 
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
@@ -495,17 +900,53 @@ fn set_remote_description<'a>(
         Ok(s) => s,
     };
 
-    task::spawn(async move {
-        match tx
-            .send(Msg::SetRemoteDescription(session_description))
-            .await
-        {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
+    match try_send(&tx, Msg::SetRemoteDescription(session_description, polite)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
 
-    (atoms::ok()).encode(env)
+/// Like `set_remote_description`, but receives the type of the description as an atom
+/// and the raw SDP, for signaling servers that do not use the JSON representation.
+/// The type may also be `rollback`, to discard a pending remote offer.
+#[rustler::nif]
+fn set_remote_sdp<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    sdp_type: Atom,
+    sdp: String,
+    polite: Option<bool>,
+) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    // A rollback carries no SDP of its own to parse, so it has no constructor.
+    let session_description = match sdp_type {
+        t if t == atoms::answer() => RTCSessionDescription::answer(sdp).ok(),
+        t if t == atoms::offer() => RTCSessionDescription::offer(sdp).ok(),
+        t if t == atoms::pranswer() => RTCSessionDescription::pranswer(sdp).ok(),
+        t if t == atoms::rollback() => RTCSessionDescription::try_from(&SessionDescription {
+            sdp_type: SdpType::Rollback,
+            sdp,
+        })
+        .ok(),
+        _ => return (atoms::error(), atoms::invalid_atom()).encode(env),
+    };
+
+    let session_description = match session_description {
+        None => return (atoms::error(), atoms::invalid_session_description()).encode(env),
+        Some(s) => s,
+    };
+
+    match try_send(&tx, Msg::SetRemoteDescription(session_description, polite)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
 }
 
 #[rustler::nif]
@@ -514,118 +955,1058 @@ fn ice_connection_state<'a>(
     resource: ResourceArc<Ref>,
     pc_uuid: Term<'a>,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
     };
 
+    match try_send(&tx, Msg::IceConnectionState) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+#[rustler::nif]
+fn ice_gathering_state<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+) -> Term<'a> {
+    let state = resource.lock();
+
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
-    task::spawn(async move {
-        match tx.send(Msg::IceConnectionState).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
+    match try_send(&tx, Msg::IceGatheringState) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+#[rustler::nif]
+fn signaling_state<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::SignalingState) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+#[rustler::nif]
+fn connection_state<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::ConnectionState) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Forwards the RTP packets of the remote track with the given id to local RTP tracks,
+/// given by uuid, which are usually sent by other peer connections. This is the read
+/// and write loop of an SFU. Forwarding may start before the remote track arrives, and
+/// replaces the targets of the track. An empty list of targets stops forwarding. A
+/// target that is deleted, and no longer sent by any peer connection, is skipped.
+#[rustler::nif]
+fn forward_track<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    track_id: String,
+    target_uuids: Vec<String>,
+) -> Term<'a> {
+    let (tx, targets) = {
+        let mut state = resource.lock();
+
+        let tx = match state.get_peer_connection(pc_uuid) {
+            None => return (atoms::error(), atoms::not_found()).encode(env),
+            Some(tx) => tx.clone(),
+        };
+
+        let mut targets = Vec::with_capacity(target_uuids.len());
+        for uuid in &target_uuids {
+            match state.get_track_local_static_rtp(uuid) {
+                None => return (atoms::error(), atoms::invalid_track()).encode(env),
+                Some(track) => targets.push(Arc::downgrade(track)),
+            }
         }
-    });
 
-    (atoms::ok()).encode(env)
+        (tx, targets)
+    };
+
+    match try_send(&tx, Msg::ForwardTrack(track_id, targets)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
 }
 
+/// Records the remote track with the given id to a file. H264 is written as Annex-B,
+/// VP8 as IVF and Opus as Ogg, depending on the codec of the track, and video is
+/// recorded from its next keyframe. Recording may start before the track arrives, and
+/// replaces a recording of the track already in progress. Once the track ends, the file
+/// is closed and `recording_finished` is sent. The file is created here, so that a bad
+/// path is reported right away.
 #[rustler::nif]
-fn ice_gathering_state<'a>(
+fn record_to_file<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    track_id: String,
+    path: String,
+) -> Term<'a> {
+    let tx = {
+        let state = resource.lock();
+
+        match state.get_peer_connection(pc_uuid) {
+            None => return (atoms::error(), atoms::not_found()).encode(env),
+            Some(tx) => tx.clone(),
+        }
+    };
+
+    let file = match File::create(&path) {
+        Err(err) => return (atoms::error(), err.to_string()).encode(env),
+        Ok(file) => file,
+    };
+
+    match try_send(&tx, Msg::RecordToFile(track_id, file)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Replaces the track sent by the RTP sender given by uuid, without renegotiating. A nil
+/// track detaches the current track, so that the sender stops sending. The new track must
+/// be of the same kind as the sender, and once negotiated its codec must be one the remote
+/// peer accepted, or `incompatible_track` is returned.
+#[rustler::nif(schedule = "DirtyIo")]
+fn replace_track<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    sender_uuid: String,
+    track_uuid: Option<String>,
+) -> Term<'a> {
+    let (tx, track) = {
+        let mut state = resource.lock();
+
+        let tx = match state.get_peer_connection(pc_uuid) {
+            None => return (atoms::error(), atoms::not_found()).encode(env),
+            Some(tx) => tx.clone(),
+        };
+
+        match track_uuid {
+            None => (tx, None),
+            Some(track_uuid) => match state.get_track_local(&track_uuid) {
+                None => return (atoms::error(), atoms::invalid_track()).encode(env),
+                Some(track) => (tx, Some(track)),
+            },
+        }
+    };
+
+    match call(tx, |reply| Msg::ReplaceTrack(sender_uuid, track, reply)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(None) => (atoms::error(), atoms::not_found()).encode(env),
+        Ok(Some(Err(webrtc::Error::ErrRTPSenderNewTrackHasIncorrectKind)))
+        | Ok(Some(Err(webrtc::Error::ErrUnsupportedCodec))) => {
+            (atoms::error(), atoms::incompatible_track()).encode(env)
+        }
+        Ok(Some(Err(err))) => (atoms::error(), err.to_string()).encode(env),
+        Ok(Some(Ok(()))) => atoms::ok().encode(env),
+    }
+}
+
+/// Asks the remote peer for a keyframe on one of its tracks, by sending a Picture Loss
+/// Indication (PLI) for the SSRC of the remote track with the given id.
+#[rustler::nif]
+fn request_keyframe<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    track_id: String,
+) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::RequestKeyframe(track_id)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Caps the bitrate that the remote peer should send to this peer connection, by
+/// periodically sending REMB packets for all incoming media. Setting a new cap
+/// replaces the previous one.
+#[rustler::nif]
+fn set_receive_bitrate_cap<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    bps: u64,
+) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    if bps == 0 {
+        return (atoms::error(), atoms::invalid_bitrate()).encode(env);
+    }
+
+    match try_send(&tx, Msg::SetReceiveBitrateCap(Some(bps))) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Starts or stops sending the RTP packets of the remote track with the given id to
+/// Elixir, as `rtp` messages carrying the marshaled packet. Packets are not forwarded
+/// unless asked for, since a video track can easily send hundreds per second. Forwarding
+/// may be enabled before the track arrives.
+#[rustler::nif]
+fn set_rtp_forwarding<'a>(
     env: Env<'a>,
     resource: ResourceArc<Ref>,
     pc_uuid: Term<'a>,
+    track_id: String,
+    enabled: bool,
 ) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
     };
 
+    match try_send(&tx, Msg::SetRtpForwarding(track_id, enabled)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Writes an RTCP packet to the remote peer, such as feedback about the media received
+/// from it. The packet is decoded before it is queued, so that packets that cannot be
+/// written are refused right away.
+#[rustler::nif]
+fn write_rtcp<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    packet: Term<'a>,
+) -> Term<'a> {
+    let state = resource.lock();
+
     let tx = match state.get_peer_connection(pc_uuid) {
         None => return (atoms::error(), atoms::not_found()).encode(env),
         Some(tx) => tx.clone(),
     };
 
+    let rtcp = match rtcp::Rtcp::decode(packet) {
+        Err(reason) => return (atoms::error(), reason).encode(env),
+        Ok(rtcp) => rtcp,
+    };
+
+    match try_send(&tx, Msg::WriteRtcp(rtcp)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Restarts ICE on the next offer created by the peer connection, as though it were
+/// created with `ice_restart`. webrtc.rs only restarts ICE while creating an offer, so
+/// the restart takes effect once the offer has been negotiated with the remote peer.
+#[rustler::nif]
+fn restart_ice<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::RestartIce) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Sends the stats of the peer connection as `stats` every `interval_ms` milliseconds,
+/// sparing Elixir a call for each report. Starting again replaces the interval.
+#[rustler::nif]
+fn start_stats_reporting<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    interval_ms: u64,
+) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    if interval_ms == 0 {
+        return (atoms::error(), atoms::invalid_interval()).encode(env);
+    }
+
+    let interval = Duration::from_millis(interval_ms);
+    match try_send(&tx, Msg::SetStatsReporting(Some(interval))) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Stops sending stats started by `start_stats_reporting`.
+#[rustler::nif]
+fn stop_stats_reporting<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::SetStatsReporting(None)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Stops sending REMB packets for a cap set by `set_receive_bitrate_cap`.
+#[rustler::nif]
+fn clear_receive_bitrate_cap<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+) -> Term<'a> {
+    let state = resource.lock();
+
+    let tx = match state.get_peer_connection(pc_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(tx) => tx.clone(),
+    };
+
+    match try_send(&tx, Msg::SetReceiveBitrateCap(None)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(()) => (atoms::ok()).encode(env),
+    }
+}
+
+/// Returns whether the given RTP sender currently has a track attached. Unlike most
+/// functions in this module, the answer is awaited and returned directly rather than
+/// being sent to the owning process.
+#[rustler::nif(schedule = "DirtyIo")]
+fn sender_active<'a>(
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    sender_uuid: String,
+) -> Result<bool, Atom> {
+    let tx = get_sender(&resource, pc_uuid)?;
+
+    match call(tx, |reply| Msg::SenderActive(sender_uuid, reply))? {
+        None => Err(atoms::not_found()),
+        Some(active) => Ok(active),
+    }
+}
+
+/// Returns the number of bytes queued to be sent on the given data channel.
+#[rustler::nif(schedule = "DirtyIo")]
+fn data_channel_buffered_amount<'a>(
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    data_channel_uuid: String,
+) -> Result<usize, Atom> {
+    let tx = get_sender(&resource, pc_uuid)?;
+
+    match call(tx, |reply| Msg::DataChannelBufferedAmount(data_channel_uuid, reply))? {
+        None => Err(atoms::not_found()),
+        Some(amount) => Ok(amount),
+    }
+}
+
+/// Sets the number of bytes below which the queue of the given data channel must drop
+/// for `data_channel_buffered_low` to be sent.
+#[rustler::nif(schedule = "DirtyIo")]
+fn set_data_channel_buffered_amount_low_threshold<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    data_channel_uuid: String,
+    threshold: usize,
+) -> Term<'a> {
+    let tx = match get_sender(&resource, pc_uuid) {
+        Err(reason) => return (atoms::error(), reason).encode(env),
+        Ok(tx) => tx,
+    };
+
+    match call(tx, |reply| {
+        Msg::SetBufferedAmountLowThreshold(data_channel_uuid, threshold, reply)
+    }) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(None) => (atoms::error(), atoms::not_found()).encode(env),
+        Ok(Some(())) => atoms::ok().encode(env),
+    }
+}
+
+/// Sets the codecs negotiated for the transceiver with the given mid, in order of
+/// preference. Payload types are taken from the media engine, so only the mime type,
+/// clock rate, channels and fmtp line of each codec are used. Returns `unknown_mime_type`
+/// when any codec has a mime type unknown to webrtc.rs, and `unsupported_codec` when any
+/// does not match one registered on the media engine for the kind of the transceiver. A
+/// transceiver only has a mid once a description has been set, so the preferences apply
+/// on the next negotiation.
+#[rustler::nif(schedule = "DirtyIo")]
+fn set_codec_preferences<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    mid: String,
+    codecs: Vec<RtpCodecCapability>,
+) -> Term<'a> {
+    let tx = match get_sender(&resource, pc_uuid) {
+        Err(reason) => return (atoms::error(), reason).encode(env),
+        Ok(tx) => tx,
+    };
+
+    let codecs = codecs
+        .into_iter()
+        .map(|codec| {
+            RTCRtpCodecCapability::try_from(codec).map(|capability| RTCRtpCodecParameters {
+                capability,
+                ..Default::default()
+            })
+        })
+        .collect::<Result<Vec<_>, Atom>>();
+
+    let codecs = match codecs {
+        Err(reason) => return (atoms::error(), reason).encode(env),
+        Ok(codecs) => codecs,
+    };
+
+    match call(tx, |reply| Msg::SetCodecPreferences(mid, codecs, reply)) {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(None) => (atoms::error(), atoms::not_found()).encode(env),
+        Ok(Some(Err(webrtc::Error::ErrRTPTransceiverCodecUnsupported))) => {
+            (atoms::error(), atoms::unsupported_codec()).encode(env)
+        }
+        Ok(Some(Err(err))) => (atoms::error(), err.to_string()).encode(env),
+        Ok(Some(Ok(()))) => atoms::ok().encode(env),
+    }
+}
+
+/// Returns the direction negotiated for the transceiver with the given mid, which may
+/// differ from the direction it was added with once the remote peer has answered, for
+/// instance when the remote declines to send. Returns `nil` until the transceiver has
+/// been negotiated, and `not_found` when no transceiver has the mid.
+#[rustler::nif(schedule = "DirtyIo")]
+fn transceiver_current_direction(
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term,
+    mid: String,
+) -> Result<Option<transceiver::TransceiverDirection>, Atom> {
+    let tx = get_sender(&resource, pc_uuid)?;
+
+    match call(tx, |reply| Msg::TransceiverCurrentDirection(mid, reply))? {
+        None => Err(atoms::not_found()),
+        Some(direction) => Ok(transceiver::TransceiverDirection::from_rtc_direction(direction)),
+    }
+}
+
+/// Returns the mid of the transceiver with the given uuid, as sent back by
+/// `add_transceiver`, so that it may be given to the functions that look transceivers
+/// up by mid. Returns `nil` until a description including the transceiver has been
+/// set, and `not_found` when the peer connection did not add a transceiver by the uuid.
+#[rustler::nif(schedule = "DirtyIo")]
+fn transceiver_mid(
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term,
+    transceiver_uuid: String,
+) -> Result<Option<String>, Atom> {
+    let tx = get_sender(&resource, pc_uuid)?;
+
+    match call(tx, |reply| Msg::TransceiverMid(transceiver_uuid, reply))? {
+        None => Err(atoms::not_found()),
+        Some(mid) => Ok(mid),
+    }
+}
+
+/// Video metrics from the outbound-rtp stats of a single RTP sender. Each field is
+/// `None` when the stats report does not include it.
+#[derive(Default, NifMap)]
+pub struct OutboundVideoStats {
+    frames_per_second: Option<f64>,
+    frames_sent: Option<u64>,
+    frame_width: Option<u64>,
+    frame_height: Option<u64>,
+}
+
+/// The totals from the outbound-rtp stats of a single RTP sender, summed over its
+/// encodings. Both are 0 until the sender has sent any packets.
+#[derive(Default, NifMap)]
+pub struct SenderStats {
+    bytes_sent: u64,
+    packets_sent: u64,
+}
+
+/// Returns whether the remote peer can accept trickled ICE candidates, as announced by
+/// `a=ice-options:trickle` in its session description, or `None` until a remote
+/// description has been set. webrtc.rs does not implement `canTrickleIceCandidates`, so
+/// this follows the WebRTC specification.
+#[rustler::nif(schedule = "DirtyIo")]
+fn can_trickle_ice_candidates(
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term,
+) -> Result<Option<bool>, Atom> {
+    let tx = get_sender(&resource, pc_uuid)?;
+
+    call(tx, Msg::CanTrickleIceCandidates)
+}
+
+/// Returns the configuration in effect on a peer connection, such as the ICE servers it
+/// ended up with.
+#[rustler::nif(name = "get_peer_connection_configuration", schedule = "DirtyIo")]
+fn get_configuration(
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term,
+) -> Result<PeerConnectionConfiguration, Atom> {
+    let tx = get_sender(&resource, pc_uuid)?;
+
+    call(tx, Msg::GetConfiguration)
+}
+
+/// Returns the fingerprint of the local DTLS certificate, which is either the certificate
+/// given in the config or the one webrtc generated for the peer connection.
+#[rustler::nif(schedule = "DirtyIo")]
+fn peer_connection_fingerprint(
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term,
+) -> Result<Fingerprint, Atom> {
+    let tx = get_sender(&resource, pc_uuid)?;
+
+    match call(tx, Msg::GetFingerprint)? {
+        None => Err(atoms::not_found()),
+        Some(fingerprint) => Ok(fingerprint),
+    }
+}
+
+/// Returns the state of the SCTP transport carrying data channels, how many data
+/// channels it allows, and the largest message that can be sent on one of them.
+#[rustler::nif(schedule = "DirtyIo")]
+fn sctp_transport(
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term,
+) -> Result<sctp::SctpTransportInfo, Atom> {
+    let tx = get_sender(&resource, pc_uuid)?;
+    call(tx, Msg::GetSctpTransport)
+}
+
+/// Returns the frame rate, frame count and resolution of the video being sent by the
+/// given RTP sender.
+#[rustler::nif(schedule = "DirtyIo")]
+fn get_outbound_video_stats<'a>(
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    sender_uuid: String,
+) -> Result<OutboundVideoStats, Atom> {
+    let tx = get_sender(&resource, pc_uuid)?;
+
+    match call(tx, |reply| Msg::GetOutboundVideoStats(sender_uuid, reply))? {
+        None => Err(atoms::not_found()),
+        Some(stats) => Ok(stats),
+    }
+}
+
+/// A track whose stats are looked up by `get_track_stats`: a local track, which is
+/// matched to the senders sending it, or a remote track, given by its id.
+pub enum StatsTrack {
+    Local(Arc<dyn TrackLocal + Send + Sync>),
+    Remote(String),
+}
+
+/// Returns the inbound-rtp and outbound-rtp stats of a single track as JSON, keyed by
+/// id as in the full report sent back by `get_stats`. The track is either a local
+/// track, or a remote track given by its id. SSRCs are resolved from the senders or
+/// receivers of the track, so that stats need not be correlated by hand. Returns `nil`
+/// when the track has no stats yet, and `not_found` when the peer connection neither
+/// sends nor receives it.
+#[rustler::nif(schedule = "DirtyIo")]
+fn get_track_stats<'a>(
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    track_uuid: String,
+) -> Result<Option<String>, Atom> {
+    let (tx, track) = {
+        let mut state = resource.lock();
+
+        let tx = match state.get_peer_connection(pc_uuid) {
+            None => return Err(atoms::not_found()),
+            Some(tx) => tx.clone(),
+        };
+
+        match state.get_track_local(&track_uuid) {
+            None => (tx, StatsTrack::Remote(track_uuid)),
+            Some(track) => (tx, StatsTrack::Local(track)),
+        }
+    };
+
+    match call(tx, |reply| Msg::GetTrackStats(track, reply))? {
+        None => Err(atoms::not_found()),
+        Some(reports) if reports.is_empty() => Ok(None),
+        Some(reports) => match serde_json::to_string(&reports) {
+            Err(_) => Err(atoms::json_error()),
+            Ok(json) => Ok(Some(json)),
+        },
+    }
+}
+
+/// Returns the encodings, codecs and header extensions of the given RTP sender.
+#[rustler::nif(schedule = "DirtyIo")]
+fn get_sender_parameters<'a>(
+    resource: ResourceArc<Ref>,
+    pc_uuid: Term<'a>,
+    sender_uuid: String,
+) -> Result<rtp_sender::SendParameters, Atom> {
+    let tx = get_sender(&resource, pc_uuid)?;
+
+    match call(tx, |reply| Msg::GetSenderParameters(sender_uuid, reply))? {
+        None => Err(atoms::not_found()),
+        Some(params) => Ok(params),
+    }
+}
+
+//
+// PRIVATE
+//
+
+/// Looks up the channel for the given peer connection, releasing the state lock
+/// before returning.
+fn get_sender(resource: &ResourceArc<Ref>, pc_uuid: Term) -> Result<Sender<Msg>, Atom> {
+    let state = resource.lock();
+
+    match state.get_peer_connection(pc_uuid) {
+        None => Err(atoms::not_found()),
+        Some(tx) => Ok(tx.clone()),
+    }
+}
+
+/// Queues a command for a peer connection task without waiting for room in its
+/// command buffer. A full buffer is refused with `busy`, so that a caller sending
+/// commands faster than the peer connection handles them is told to back off.
+fn try_send(tx: &Sender<Msg>, msg: Msg) -> Result<(), Atom> {
+    match tx.try_send(msg) {
+        Ok(()) => Ok(()),
+        Err(TrySendError::Full(_)) => Err(atoms::busy()),
+        Err(TrySendError::Closed(_)) => Err(atoms::not_found()),
+    }
+}
+
+/// Sends a message to a peer connection task, and blocks until it replies over a
+/// oneshot channel. This must only be called from dirty NIFs.
+fn call<T>(tx: Sender<Msg>, msg: impl FnOnce(oneshot::Sender<T>) -> Msg) -> Result<T, Atom> {
+    let (reply_tx, reply_rx) = oneshot::channel::<T>();
+
+    if tx.blocking_send(msg(reply_tx)).is_err() {
+        return Err(atoms::not_found());
+    }
+
+    match reply_rx.blocking_recv() {
+        Err(_) => Err(atoms::not_found()),
+        Ok(reply) => Ok(reply),
+    }
+}
+
+/// Finds the outbound-rtp report for the sender's SSRC, and reads its video fields.
+/// The fields are read from the serialized report, since not every version of the
+/// stats structs carries them.
+async fn outbound_video_stats(pc: &RTCPeerConnection, sender: &RTCRtpSender) -> OutboundVideoStats {
+    let ssrcs: Vec<u32> = sender
+        .get_parameters()
+        .await
+        .encodings
+        .iter()
+        .map(|encoding| encoding.ssrc)
+        .collect();
+
+    let report = pc
+        .get_stats()
+        .await
+        .reports
+        .into_iter()
+        .find_map(|(_id, report)| match report {
+            StatsReportType::OutboundRTP(stats) if ssrcs.contains(&stats.ssrc) => {
+                serde_json::to_value(&stats).ok()
+            }
+            _ => None,
+        });
+
+    match report {
+        None => OutboundVideoStats::default(),
+        Some(report) => OutboundVideoStats {
+            frames_per_second: report["framesPerSecond"].as_f64(),
+            frames_sent: report["framesSent"].as_u64(),
+            frame_width: report["frameWidth"].as_u64(),
+            frame_height: report["frameHeight"].as_u64(),
+        },
+    }
+}
+
+/// Sums the bytes and packets sent over the outbound-rtp reports of the sender's SSRCs,
+/// of which there is one per simulcast encoding.
+async fn sender_totals(pc: &RTCPeerConnection, sender: &RTCRtpSender) -> SenderStats {
+    let ssrcs: Vec<u32> = sender
+        .get_parameters()
+        .await
+        .encodings
+        .iter()
+        .map(|encoding| encoding.ssrc)
+        .collect();
+
+    pc.get_stats()
+        .await
+        .reports
+        .into_iter()
+        .fold(SenderStats::default(), |totals, (_id, report)| match report {
+            StatsReportType::OutboundRTP(stats) if ssrcs.contains(&stats.ssrc) => SenderStats {
+                bytes_sent: totals.bytes_sent + stats.bytes_sent,
+                packets_sent: totals.packets_sent + stats.packets_sent,
+            },
+            _ => totals,
+        })
+}
+
+/// Finds the SSRCs of a local track from the senders sending it, or of a remote track
+/// from the receivers receiving it. Returns `None` when the track is neither sent nor
+/// received.
+async fn track_ssrcs(
+    pc: &RTCPeerConnection,
+    senders: &HashMap<String, Arc<RTCRtpSender>>,
+    track: StatsTrack,
+) -> Option<Vec<u32>> {
+    let mut ssrcs = vec![];
+
+    match track {
+        StatsTrack::Local(track) => {
+            for sender in senders.values() {
+                // Tracks are compared by address, since their ids are chosen by the
+                // caller and need not be unique.
+                let sends_track = match sender.track().await {
+                    None => false,
+                    Some(sent) => {
+                        Arc::as_ptr(&sent) as *const () == Arc::as_ptr(&track) as *const ()
+                    }
+                };
+
+                if sends_track {
+                    let encodings = sender.get_parameters().await.encodings;
+                    ssrcs.extend(encodings.iter().map(|encoding| encoding.ssrc));
+                }
+            }
+        }
+        StatsTrack::Remote(track_id) => {
+            for transceiver in pc.get_transceivers().await {
+                for remote in transceiver.receiver().await.tracks().await {
+                    if remote.id() == track_id {
+                        ssrcs.push(remote.ssrc());
+                    }
+                }
+            }
+        }
+    }
+
+    if ssrcs.is_empty() {
+        None
+    } else {
+        Some(ssrcs)
+    }
+}
+
+/// Picks the inbound-rtp and outbound-rtp reports of the given SSRCs out of the stats
+/// of the peer connection.
+async fn rtp_stats(pc: &RTCPeerConnection, ssrcs: &[u32]) -> HashMap<String, StatsReportType> {
+    pc.get_stats()
+        .await
+        .reports
+        .into_iter()
+        .filter(|(_id, report)| match report {
+            StatsReportType::InboundRTP(stats) => ssrcs.contains(&stats.ssrc),
+            StatsReportType::OutboundRTP(stats) => ssrcs.contains(&stats.ssrc),
+            _ => false,
+        })
+        .collect()
+}
+
+/// Whether a session description lists `trickle` in any of its `a=ice-options`
+/// attributes, at either the session or the media level.
+fn sdp_can_trickle(sdp: &str) -> bool {
+    sdp.lines()
+        .filter_map(|line| line.trim_end().strip_prefix("a=ice-options:"))
+        .any(|options| options.split_whitespace().any(|option| option == "trickle"))
+}
+
+/// Turns on `ice_restart` in the options of an offer while a restart is pending.
+fn with_pending_ice_restart(
+    opts: Option<RTCOfferOptions>,
+    ice_restart_pending: bool,
+) -> Option<RTCOfferOptions> {
+    match opts {
+        None if ice_restart_pending => Some(RTCOfferOptions {
+            ice_restart: true,
+            ..Default::default()
+        }),
+        None => None,
+        Some(opts) => Some(RTCOfferOptions {
+            ice_restart: opts.ice_restart || ice_restart_pending,
+            ..opts
+        }),
+    }
+}
+
+/// Rolls back a pending local offer, returning the peer connection to a stable
+/// signaling state.
+async fn rollback_local_description(pc: &RTCPeerConnection) -> Result<(), String> {
+    // webrtc.rs requires SDP when setting any local description, even a rollback.
+    let sdp = match pc.pending_local_description().await {
+        None => return Err("no pending local description".to_string()),
+        Some(desc) => desc.sdp,
+    };
+
+    let rollback = RTCSessionDescription::try_from(&SessionDescription {
+        sdp_type: SdpType::Rollback,
+        sdp,
+    })
+    .map_err(|_| "invalid rollback description".to_string())?;
+
+    pc.set_local_description(rollback)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Collects the SSRCs of every remote track currently being received.
+async fn remote_ssrcs(pc: &RTCPeerConnection) -> Vec<u32> {
+    let mut ssrcs = vec![];
+
+    for transceiver in pc.get_transceivers().await {
+        for track in transceiver.receiver().await.tracks().await {
+            ssrcs.push(track.ssrc());
+        }
+    }
+
+    ssrcs
+}
+
+/// Finds the SSRC of the remote track with the given id.
+async fn remote_track_ssrc(pc: &RTCPeerConnection, track_id: &str) -> Option<u32> {
+    for transceiver in pc.get_transceivers().await {
+        for track in transceiver.receiver().await.tracks().await {
+            if track.id() == track_id {
+                return Some(track.ssrc());
+            }
+        }
+    }
+
+    None
+}
+
+/// Sends a REMB packet capping the remote's bitrate every `REMB_INTERVAL`, until
+/// the returned task is aborted.
+fn spawn_remb_loop(pc: Arc<RTCPeerConnection>, bps: u64) -> tokio::task::JoinHandle<()> {
     task::spawn(async move {
-        match tx.send(Msg::IceGatheringState).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
+        let mut ticker = tokio::time::interval(REMB_INTERVAL);
+        loop {
+            let _ = ticker.tick().await;
+
+            let ssrcs = remote_ssrcs(&pc).await;
+            if ssrcs.is_empty() {
+                continue;
+            }
+
+            let remb = ReceiverEstimatedMaximumBitrate {
+                sender_ssrc: 0,
+                bitrate: bps as f32,
+                ssrcs,
+            };
+
+            if let Err(err) = pc.write_rtcp(&[Box::new(remb)]).await {
+                trace!("unable to send REMB: {}", err);
+            }
+        }
+    })
+}
+
+/// Sends the stats of the peer connection to Elixir every `interval`, starting right
+/// away, until the returned task is aborted.
+fn spawn_stats_loop(
+    pc: Arc<RTCPeerConnection>,
+    pid: LocalPid,
+    pc_uuid: Arc<str>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    task::spawn(async move {
+        let mut msg_env = rustler::env::OwnedEnv::new();
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            let _ = ticker.tick().await;
+            let stats = pc.get_stats().await;
+
+            msg_env
+                .send_and_clear(&pid, |env| {
+                    let json = serde_json::to_string(&stats);
+                    encode_json(env, atoms::stats(), &pc_uuid, json)
+                })
+                .unwrap_or(());
         }
-    });
+    })
+}
 
-    (atoms::ok()).encode(env)
+/// Encodes `{tag, pc_uuid, json}` for a value serialized to JSON. Serialization
+/// failures are sent as `{:json_error, pc_uuid, tag, reason}`, rather than crashing
+/// the task holding the peer connection.
+fn encode_json<'a>(
+    env: Env<'a>,
+    tag: Atom,
+    pc_uuid: &str,
+    json: serde_json::Result<String>,
+) -> Term<'a> {
+    match json {
+        Err(err) => (atoms::json_error(), pc_uuid, tag, err.to_string()).encode(env),
+        Ok(json) => (tag, pc_uuid, json).encode(env),
+    }
 }
 
-#[rustler::nif]
-fn signaling_state<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+/// Encodes the offer created by `create_offer` or `create_offer_if_needed`, or the error
+/// creating it, as the message sent back to Elixir.
+fn encode_offer<'a>(
+    env: Env<'a>,
+    pc_uuid: &str,
+    resp: Result<RTCSessionDescription, webrtc::Error>,
+) -> Term<'a> {
+    match resp {
+        Err(err) => {
+            (atoms::offer_error(), pc_uuid, description_error(&err), err.to_string()).encode(env)
+        }
+        Ok(offer) => encode_json(env, atoms::offer(), pc_uuid, serde_json::to_string(&offer)),
+    }
+}
 
-    let tx = match state.get_peer_connection(pc_uuid) {
-        None => return (atoms::error(), atoms::not_found()).encode(env),
-        Some(tx) => tx.clone(),
-    };
+/// Encodes the reply to `create_answer_sync` or `create_offer_sync`. The peer connection
+/// not being found is reported as an atom, while errors from webrtc or from serializing
+/// the session description are reported as strings.
+fn encode_description_reply<'a>(
+    env: Env<'a>,
+    resp: Result<Result<String, String>, Atom>,
+) -> Term<'a> {
+    match resp {
+        Err(reason) => (atoms::error(), reason).encode(env),
+        Ok(Err(reason)) => (atoms::error(), reason).encode(env),
+        Ok(Ok(json)) => (atoms::ok(), json).encode(env),
+    }
+}
 
-    task::spawn(async move {
-        match tx.send(Msg::SignalingState).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
+/// Serializes a session description created by webrtc, or the error creating it.
+fn description_json(resp: Result<RTCSessionDescription, webrtc::Error>) -> Result<String, String> {
+    let desc = resp.map_err(|err| err.to_string())?;
+    serde_json::to_string(&desc).map_err(|err| err.to_string())
+}
 
-    (atoms::ok()).encode(env)
+/// Classifies the errors webrtc may return when creating an offer or answer, so that
+/// they can be matched on. Others are reported as `webrtc_error`, and their message
+/// is always sent along.
+fn description_error(err: &webrtc::Error) -> Atom {
+    match err {
+        webrtc::Error::ErrConnectionClosed => atoms::closed(),
+        webrtc::Error::ErrExcessiveRetries => atoms::excessive_retries(),
+        webrtc::Error::ErrICEAgentNotExist => atoms::no_ice_agent(),
+        webrtc::Error::ErrIncorrectSignalingState => atoms::invalid_state(),
+        webrtc::Error::ErrNoRemoteDescription => atoms::no_remote_description(),
+        _ => atoms::webrtc_error(),
+    }
 }
 
-#[rustler::nif]
-fn connection_state<'a>(env: Env<'a>, resource: ResourceArc<Ref>, pc_uuid: Term<'a>) -> Term<'a> {
-    let state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
-    };
+/// Candidates are given either as JSON strings or as `%Specter.IceCandidate{}` structs.
+fn decode_ice_candidate(candidate: Term) -> Result<RTCIceCandidateInit, Atom> {
+    if candidate.is_binary() {
+        let json: String = candidate.decode().map_err(|_| atoms::invalid_json())?;
+        return serde_json::from_str::<RTCIceCandidateInit>(&json)
+            .map_err(|_| atoms::invalid_json());
+    }
 
-    let tx = match state.get_peer_connection(pc_uuid) {
-        None => return (atoms::error(), atoms::not_found()).encode(env),
-        Some(tx) => tx.clone(),
-    };
+    match candidate.decode::<IceCandidate>() {
+        Err(_) => Err(atoms::invalid_ice_candidate()),
+        Ok(c) => Ok(RTCIceCandidateInit::from(c)),
+    }
+}
 
+/// Closes a draining peer connection once its grace period has elapsed, by dropping
+/// its channel from the state as `close` does. Does nothing if the peer connection
+/// has already been closed.
+fn spawn_drain_timer(resource: ResourceArc<Ref>, uuid: String, grace_period: Duration) {
     task::spawn(async move {
-        match tx.send(Msg::ConnectionState).await {
-            Ok(_) => (),
-            Err(_err) => trace!("send error"),
-        }
-    });
+        tokio::time::sleep(grace_period).await;
 
-    (atoms::ok()).encode(env)
+        let mut state = resource.lock();
+        let _tx = state.remove_peer_connection_by_uuid(&uuid);
+    });
 }
 
-//
-// PRIVATE
-//
-
-fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: String) {
+fn spawn_rtc_peer_connection(
+    resource: ResourceArc<Ref>,
+    api: Arc<API>,
+    uuid: String,
+    command_buffer_size: Option<usize>,
+) {
     task::spawn(async move {
         let mut msg_env = rustler::env::OwnedEnv::new();
-        // Convert String to static str, so that we know it exists for the duration of
-        // this thread. Manually dropped before this thread exits.
-        let pc_uuid: &str = Box::leak(uuid.clone().into_boxed_str());
+        // The uuid is owned by this task, and borrowed for messages sent from it.
+        // Callbacks may outlive the task, so each holds its own reference count.
+        let pc_uuid: &str = &uuid;
+        let callback_uuid: Arc<str> = Arc::from(pc_uuid);
 
-        let (pc, pid) = {
-            let state = resource.0.lock().unwrap();
+        let (pc, pid, ice_candidate_format, command_buffer_size) = {
+            let state = resource.lock();
             let rtc_config = RTCConfiguration::from(&state.config.clone());
-            (api.new_peer_connection(rtc_config), state.pid)
+            (
+                api.new_peer_connection(rtc_config),
+                state.pid,
+                state.config.ice_candidate_format,
+                command_buffer_size.unwrap_or(state.config.command_buffer_size),
+            )
         };
 
         let pc = match pc.await {
-            Err(_) => {
+            Err(err) => {
+                {
+                    // Forget the API this peer connection was created from.
+                    let mut state = resource.lock();
+                    let _tx = state.remove_peer_connection_by_uuid(&uuid);
+                }
                 msg_env
                     .send_and_clear(&pid, |env| {
-                        (atoms::peer_connection_error(), &pc_uuid).encode(env)
+                        (atoms::peer_connection_error(), &pc_uuid, err.to_string()).encode(env)
                     })
                     .unwrap();
                 return;
@@ -634,8 +2015,8 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
         };
 
         let mut rx = {
-            let (tx, rx) = channel::<Msg>(1000);
-            let mut state = resource.0.lock().unwrap();
+            let (tx, rx) = channel::<Msg>(command_buffer_size);
+            let mut state = resource.lock();
             state.add_peer_connection(&uuid, tx);
             msg_env
                 .send_and_clear(&state.pid, |env| {
@@ -646,29 +2027,154 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
             rx
         };
 
+        let candidate_uuid = callback_uuid.clone();
         pc.on_ice_candidate(Box::new(move |c: Option<RTCIceCandidate>| {
+            let pc_uuid = candidate_uuid.clone();
             Box::pin(async move {
                 let mut msg_env = rustler::env::OwnedEnv::new();
-                if let Some(c) = c {
-                    let candidate = c.to_json().unwrap();
-                    let json = serde_json::to_string(&candidate).unwrap();
-
-                    msg_env
+                match c {
+                    Some(c) => {
+                        let candidate = match c.to_json() {
+                            Err(err) => {
+                                msg_env
+                                    .send_and_clear(&pid, |env| {
+                                        (
+                                            atoms::json_error(),
+                                            &*pc_uuid,
+                                            atoms::ice_candidate(),
+                                            err.to_string(),
+                                        )
+                                            .encode(env)
+                                    })
+                                    .unwrap_or(());
+                                return;
+                            }
+                            Ok(candidate) => candidate,
+                        };
+
+                        match ice_candidate_format {
+                            IceCandidateFormat::Json => {
+                                let json = serde_json::to_string(&candidate);
+
+                                msg_env
+                                    .send_and_clear(&pid, |env| {
+                                        encode_json(env, atoms::ice_candidate(), &pc_uuid, json)
+                                    })
+                                    .unwrap_or(());
+                            }
+                            IceCandidateFormat::Struct => {
+                                let candidate = IceCandidate::from(candidate);
+
+                                msg_env
+                                    .send_and_clear(&pid, |env| {
+                                        (atoms::ice_candidate(), &*pc_uuid, candidate).encode(env)
+                                    })
+                                    .unwrap_or(());
+                            }
+                        }
+                    }
+                    // A `None` candidate signals that ICE gathering has finished.
+                    None => msg_env
                         .send_and_clear(&pid, |env| {
-                            // (atoms::ice_candidate(), &uuid, json).encode(env)
-                            (atoms::ice_candidate(), &pc_uuid, json).encode(env)
+                            (atoms::ice_gathering_complete(), &*pc_uuid).encode(env)
                         })
-                        .unwrap_or(());
+                        .unwrap_or(()),
                 }
             })
         }));
 
+        let state_uuid = callback_uuid.clone();
+        pc.on_peer_connection_state_change(Box::new(move |s: RTCPeerConnectionState| {
+            let pc_uuid = state_uuid.clone();
+            Box::pin(async move {
+                let mut msg_env = rustler::env::OwnedEnv::new();
+                let state = peer_conn_state::ConnectionState::from(&s);
+
+                msg_env
+                    .send_and_clear(&pid, |env| {
+                        (atoms::connection_state_change(), &*pc_uuid, state).encode(env)
+                    })
+                    .unwrap_or(());
+            })
+        }));
+
+        let ice_state_uuid = callback_uuid.clone();
+        pc.on_ice_connection_state_change(Box::new(move |s: RTCIceConnectionState| {
+            let pc_uuid = ice_state_uuid.clone();
+            Box::pin(async move {
+                let mut msg_env = rustler::env::OwnedEnv::new();
+                let state = peer_conn_state::IceConnectionState::from(&s);
+
+                msg_env
+                    .send_and_clear(&pid, |env| {
+                        (atoms::ice_connection_state_change(), &*pc_uuid, state).encode(env)
+                    })
+                    .unwrap_or(());
+            })
+        }));
+
+        // With bundling, every transceiver and the data channels share one DTLS
+        // transport, which is reached through SCTP even when no data channel is used.
+        let dtls_state_uuid = callback_uuid.clone();
+        pc.sctp()
+            .transport()
+            .on_state_change(Box::new(move |s: RTCDtlsTransportState| {
+                let pc_uuid = dtls_state_uuid.clone();
+                Box::pin(async move {
+                    let mut msg_env = rustler::env::OwnedEnv::new();
+                    let state = peer_conn_state::DtlsTransportState::from(&s);
+
+                    msg_env
+                        .send_and_clear(&pid, |env| {
+                            (atoms::dtls_transport_state_change(), &*pc_uuid, state).encode(env)
+                        })
+                        .unwrap_or(());
+                })
+            }));
+
+        let remote_track_sinks = Arc::new(Mutex::new(remote_track::Sinks::default()));
+
+        let track_uuid = callback_uuid.clone();
+        let track_sinks = remote_track_sinks.clone();
+        pc.on_track(Box::new(
+            move |track: Arc<TrackRemote>,
+                  _receiver: Arc<RTCRtpReceiver>,
+                  _transceiver: Arc<RTCRtpTransceiver>| {
+                let pc_uuid = track_uuid.clone();
+                let sinks = track_sinks.clone();
+                Box::pin(async move {
+                    // Reading happens in its own task, since webrtc.rs holds on to this
+                    // handler until the returned future completes.
+                    task::spawn(remote_track::read(track, pid, pc_uuid, sinks));
+                })
+            },
+        ));
+
+        let mut data_channels: HashMap<String, Arc<RTCDataChannel>> = HashMap::new();
         let mut rtp_senders: HashMap<String, Arc<RTCRtpSender>> = HashMap::new();
+        let mut rtp_transceivers: HashMap<String, Arc<RTCRtpTransceiver>> = HashMap::new();
+        let mut remb_loop: Option<tokio::task::JoinHandle<()>> = None;
+        let mut stats_loop: Option<tokio::task::JoinHandle<()>> = None;
+        let mut draining = false;
+        // Set by `restart_ice`, and cleared once an offer restarting ICE is created.
+        let mut ice_restart_pending = false;
+        // The sequence number of the last FIR written with `write_rtcp`.
+        let mut fir_sequence_number: u8 = 0;
+        let mut close_reply: Option<oneshot::Sender<()>> = None;
         // Block on messages being received on the channel for this peer connection.
         // When all senders go out of scope, the receiver will receive `None` and
         // break out of the loop.
         loop {
             match rx.recv().await {
+                Some(Msg::AddEncoding(sender_uuid, track, reply)) => {
+                    let resp = match rtp_senders.get(&sender_uuid) {
+                        None => None,
+                        Some(sender) => {
+                            Some(sender.add_encoding(track).await.map_err(|err| err.to_string()))
+                        }
+                    };
+                    let _ = reply.send(resp);
+                }
                 Some(Msg::AddIceCandidate(candidate)) => {
                     let lock = pc.clone();
                     let resp = lock.add_ice_candidate(candidate).await;
@@ -684,10 +2190,30 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
                         })
                         .unwrap();
                 }
-                Some(Msg::AddTrack(track_uuid, track)) => {
+                Some(Msg::AddTrack(track_uuid, sender_uuid, track)) => {
+                    if draining {
+                        msg_env
+                            .send_and_clear(&pid, |env| {
+                                (atoms::draining_error(), &pc_uuid, atoms::add_track()).encode(env)
+                            })
+                            .unwrap();
+                        continue;
+                    }
+
                     let lock = pc.clone();
-                    let sender = lock.add_track(track).await.unwrap();
-                    let sender_uuid = gen_uuid();
+                    let sender = match lock.add_track(track).await {
+                        // Adding a track whose codec was never registered on the media
+                        // engine is recoverable, so the error is sent back instead.
+                        Err(err) => {
+                            msg_env
+                                .send_and_clear(&pid, |env| {
+                                    (atoms::track_error(), &pc_uuid, err.to_string()).encode(env)
+                                })
+                                .unwrap();
+                            continue;
+                        }
+                        Ok(sender) => sender,
+                    };
 
                     rtp_senders.insert(sender_uuid.clone(), sender);
                     msg_env
@@ -696,57 +2222,226 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
                         })
                         .unwrap();
                 }
+                Some(Msg::AddTransceiver(kind, direction)) => {
+                    if draining {
+                        msg_env
+                            .send_and_clear(&pid, |env| {
+                                (atoms::draining_error(), &pc_uuid, atoms::add_transceiver())
+                                    .encode(env)
+                            })
+                            .unwrap();
+                        continue;
+                    }
+
+                    let lock = pc.clone();
+                    let init = RTCRtpTransceiverInit {
+                        direction,
+                        send_encodings: vec![],
+                    };
+                    let resp = lock.add_transceiver_from_kind(kind, Some(init)).await;
+
+                    match resp {
+                        Err(err) => msg_env
+                            .send_and_clear(&pid, |env| {
+                                (atoms::transceiver_error(), &pc_uuid, err.to_string()).encode(env)
+                            })
+                            .unwrap(),
+                        Ok(transceiver) => {
+                            // The mid is only assigned during negotiation, so the
+                            // transceiver is identified by its own uuid instead.
+                            let transceiver_uuid = gen_uuid();
+                            rtp_transceivers.insert(transceiver_uuid.clone(), transceiver);
+
+                            msg_env
+                                .send_and_clear(&pid, |env| {
+                                    (atoms::rtp_transceiver(), &pc_uuid, transceiver_uuid)
+                                        .encode(env)
+                                })
+                                .unwrap();
+                        }
+                    }
+                }
+                Some(Msg::Close(reply)) => {
+                    close_reply = Some(reply);
+                    break;
+                }
+                Some(Msg::CanTrickleIceCandidates(reply)) => {
+                    let can_trickle = pc
+                        .remote_description()
+                        .await
+                        .map(|desc| sdp_can_trickle(&desc.sdp));
+                    let _ = reply.send(can_trickle);
+                }
                 Some(Msg::CreateAnswer(opts)) => {
                     let lock = pc.clone();
                     let resp = lock.create_answer(opts).await;
 
                     msg_env
                         .send_and_clear(&pid, |env| match resp {
-                            Err(err) => {
-                                (atoms::answer_error(), &pc_uuid, err.to_string()).encode(env)
-                            }
-                            Ok(answer) => (
-                                atoms::answer(),
+                            Err(err) => (
+                                atoms::answer_error(),
                                 &pc_uuid,
-                                serde_json::to_string(&answer).unwrap(),
+                                description_error(&err),
+                                err.to_string(),
                             )
                                 .encode(env),
+                            Ok(answer) => encode_json(
+                                env,
+                                atoms::answer(),
+                                pc_uuid,
+                                serde_json::to_string(&answer),
+                            ),
                         })
                         .unwrap();
                 }
-                Some(Msg::CreateDataChannel(label)) => {
+                Some(Msg::CreateAnswerSync(opts, reply)) => {
+                    let resp = pc.create_answer(opts).await;
+                    let _ = reply.send(description_json(resp));
+                }
+                Some(Msg::CreateDataChannel(label, init)) => {
+                    if draining {
+                        msg_env
+                            .send_and_clear(&pid, |env| {
+                                (
+                                    atoms::draining_error(),
+                                    &pc_uuid,
+                                    atoms::create_data_channel(),
+                                )
+                                    .encode(env)
+                            })
+                            .unwrap();
+                        continue;
+                    }
+
                     let lock = pc.clone();
-                    let resp = lock.create_data_channel(&label, None).await;
+                    let resp = lock.create_data_channel(&label, Some(init)).await;
 
                     msg_env
                         .send_and_clear(&pid, |env| match resp {
-                            Err(err) => {
-                                (atoms::offer_error(), &pc_uuid, err.to_string()).encode(env)
-                            }
+                            Err(err) => (
+                                atoms::offer_error(),
+                                &pc_uuid,
+                                description_error(&err),
+                                err.to_string(),
+                            )
+                                .encode(env),
                             Ok(_data_channel) => {
                                 (atoms::data_channel_created(), &pc_uuid).encode(env)
                             }
                         })
                         .unwrap();
                 }
+                Some(Msg::CreateDataChannelSync(label, init, reply)) => {
+                    if draining {
+                        let _ = reply.send(None);
+                        continue;
+                    }
+
+                    let resp = match pc.create_data_channel(&label, Some(init)).await {
+                        Err(err) => Err(err),
+                        Ok(data_channel) => {
+                            let data_channel_uuid = gen_uuid();
+                            let low_uuids =
+                                (callback_uuid.clone(), Arc::<str>::from(&*data_channel_uuid));
+                            data_channel
+                                .on_buffered_amount_low(Box::new(move || {
+                                    let (pc_uuid, data_channel_uuid) = low_uuids.clone();
+                                    Box::pin(async move {
+                                        let mut msg_env = rustler::env::OwnedEnv::new();
+                                        msg_env
+                                            .send_and_clear(&pid, |env| {
+                                                (
+                                                    atoms::data_channel_buffered_low(),
+                                                    &*pc_uuid,
+                                                    &*data_channel_uuid,
+                                                )
+                                                    .encode(env)
+                                            })
+                                            .unwrap_or(());
+                                    })
+                                }))
+                                .await;
+                            data_channels.insert(data_channel_uuid.clone(), data_channel);
+                            Ok(data_channel_uuid)
+                        }
+                    };
+                    let _ = reply.send(Some(resp));
+                }
                 Some(Msg::CreateOffer(opts)) => {
+                    let opts = with_pending_ice_restart(opts, ice_restart_pending);
                     let lock = pc.clone();
                     let resp = lock.create_offer(opts).await;
+                    if resp.is_ok() {
+                        ice_restart_pending = false;
+                    }
 
                     msg_env
-                        .send_and_clear(&pid, |env| match resp {
-                            Err(err) => {
-                                (atoms::offer_error(), &pc_uuid, err.to_string()).encode(env)
-                            }
-                            Ok(offer) => (
-                                atoms::offer(),
-                                &pc_uuid,
-                                serde_json::to_string(&offer).unwrap(),
-                            )
-                                .encode(env),
+                        .send_and_clear(&pid, |env| encode_offer(env, pc_uuid, resp))
+                        .unwrap();
+                }
+                Some(Msg::CreateOfferIfNeeded(opts, reply)) => {
+                    let needed = pc.signaling_state() == RTCSignalingState::Stable;
+                    let _ = reply.send(needed);
+                    if !needed {
+                        continue;
+                    }
+
+                    let opts = with_pending_ice_restart(opts, ice_restart_pending);
+                    let resp = pc.create_offer(opts).await;
+                    if resp.is_ok() {
+                        ice_restart_pending = false;
+                    }
+
+                    msg_env
+                        .send_and_clear(&pid, |env| encode_offer(env, pc_uuid, resp))
+                        .unwrap();
+                }
+                Some(Msg::CreateOfferSync(opts, reply)) => {
+                    let opts = with_pending_ice_restart(opts, ice_restart_pending);
+                    let resp = pc.create_offer(opts).await;
+                    if resp.is_ok() {
+                        ice_restart_pending = false;
+                    }
+                    let _ = reply.send(description_json(resp));
+                }
+                Some(Msg::DataChannelBufferedAmount(data_channel_uuid, reply)) => {
+                    let amount = match data_channels.get(&data_channel_uuid) {
+                        None => None,
+                        Some(data_channel) => Some(data_channel.buffered_amount().await),
+                    };
+                    let _ = reply.send(amount);
+                }
+                Some(Msg::Drain(grace_period)) => {
+                    // Only the first drain starts the timer, so that repeated calls
+                    // cannot extend the grace period.
+                    if !draining {
+                        draining = true;
+                        spawn_drain_timer(resource.clone(), uuid.clone(), grace_period);
+                    }
+
+                    msg_env
+                        .send_and_clear(&pid, |env| {
+                            (atoms::ok(), &pc_uuid, atoms::drain_peer_connection()).encode(env)
                         })
                         .unwrap();
                 }
+                Some(Msg::GatheringCompletePromise) => {
+                    let lock = pc.clone();
+                    let mut gather_complete = lock.gathering_complete_promise().await;
+
+                    // Wait outside of the message loop, so that the peer connection
+                    // can keep handling messages while gathering.
+                    let gathering_uuid = callback_uuid.clone();
+                    task::spawn(async move {
+                        let _ = gather_complete.recv().await;
+
+                        rustler::env::OwnedEnv::new()
+                            .send_and_clear(&pid, |env| {
+                                (atoms::gathering_complete(), &*gathering_uuid).encode(env)
+                            })
+                            .unwrap_or(());
+                    });
+                }
                 Some(Msg::GetCurrentLocalDescription) => {
                     let lock = pc.clone();
                     let resp = lock.current_local_description().await;
@@ -759,15 +2454,43 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
                                 rustler::types::atom::nil(),
                             )
                                 .encode(env),
-                            Some(desc) => (
+                            Some(desc) => encode_json(
+                                env,
                                 atoms::current_local_description(),
-                                &pc_uuid,
-                                serde_json::to_string(&desc).unwrap(),
-                            )
-                                .encode(env),
+                                pc_uuid,
+                                serde_json::to_string(&desc),
+                            ),
                         })
                         .unwrap();
                 }
+                Some(Msg::GetGatheredLocalDescription) => {
+                    let lock = pc.clone();
+                    let mut gather_complete = lock.gathering_complete_promise().await;
+
+                    // As with `GatheringCompletePromise`, wait outside of the message loop.
+                    let gathering_uuid = callback_uuid.clone();
+                    task::spawn(async move {
+                        let _ = gather_complete.recv().await;
+                        let resp = lock.local_description().await;
+
+                        rustler::env::OwnedEnv::new()
+                            .send_and_clear(&pid, |env| match resp {
+                                None => (
+                                    atoms::local_description(),
+                                    &*gathering_uuid,
+                                    rustler::types::atom::nil(),
+                                )
+                                    .encode(env),
+                                Some(desc) => encode_json(
+                                    env,
+                                    atoms::local_description(),
+                                    &gathering_uuid,
+                                    serde_json::to_string(&desc),
+                                ),
+                            })
+                            .unwrap_or(());
+                    });
+                }
                 Some(Msg::GetLocalDescription) => {
                     let lock = pc.clone();
                     let resp = lock.local_description().await;
@@ -780,12 +2503,12 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
                                 rustler::types::atom::nil(),
                             )
                                 .encode(env),
-                            Some(desc) => (
+                            Some(desc) => encode_json(
+                                env,
                                 atoms::local_description(),
-                                &pc_uuid,
-                                serde_json::to_string(&desc).unwrap(),
-                            )
-                                .encode(env),
+                                pc_uuid,
+                                serde_json::to_string(&desc),
+                            ),
                         })
                         .unwrap();
                 }
@@ -801,12 +2524,12 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
                                 rustler::types::atom::nil(),
                             )
                                 .encode(env),
-                            Some(desc) => (
+                            Some(desc) => encode_json(
+                                env,
                                 atoms::pending_local_description(),
-                                &pc_uuid,
-                                serde_json::to_string(&desc).unwrap(),
-                            )
-                                .encode(env),
+                                pc_uuid,
+                                serde_json::to_string(&desc),
+                            ),
                         })
                         .unwrap();
                 }
@@ -822,12 +2545,12 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
                                 rustler::types::atom::nil(),
                             )
                                 .encode(env),
-                            Some(desc) => (
+                            Some(desc) => encode_json(
+                                env,
                                 atoms::current_remote_description(),
-                                &pc_uuid,
-                                serde_json::to_string(&desc).unwrap(),
-                            )
-                                .encode(env),
+                                pc_uuid,
+                                serde_json::to_string(&desc),
+                            ),
                         })
                         .unwrap();
                 }
@@ -843,12 +2566,12 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
                                 rustler::types::atom::nil(),
                             )
                                 .encode(env),
-                            Some(desc) => (
+                            Some(desc) => encode_json(
+                                env,
                                 atoms::remote_description(),
-                                &pc_uuid,
-                                serde_json::to_string(&desc).unwrap(),
-                            )
-                                .encode(env),
+                                pc_uuid,
+                                serde_json::to_string(&desc),
+                            ),
                         })
                         .unwrap();
                 }
@@ -864,12 +2587,12 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
                                 rustler::types::atom::nil(),
                             )
                                 .encode(env),
-                            Some(desc) => (
+                            Some(desc) => encode_json(
+                                env,
                                 atoms::pending_remote_description(),
-                                &pc_uuid,
-                                serde_json::to_string(&desc).unwrap(),
-                            )
-                                .encode(env),
+                                pc_uuid,
+                                serde_json::to_string(&desc),
+                            ),
                         })
                         .unwrap();
                 }
@@ -879,12 +2602,8 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
 
                     msg_env
                         .send_and_clear(&pid, |env| {
-                            (
-                                atoms::stats(),
-                                &pc_uuid,
-                                serde_json::to_string(&stats).unwrap(),
-                            )
-                                .encode(env)
+                            let json = serde_json::to_string(&stats);
+                            encode_json(env, atoms::stats(), pc_uuid, json)
                         })
                         .unwrap();
                 }
@@ -906,8 +2625,41 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
                         })
                         .unwrap();
                 }
-                Some(Msg::SetRemoteDescription(session)) => {
+                Some(Msg::SetRemoteDescription(session, polite)) => {
                     let lock = pc.clone();
+                    let glare = session.sdp_type == RTCSdpType::Offer
+                        && lock.signaling_state() == RTCSignalingState::HaveLocalOffer;
+
+                    if glare {
+                        match polite {
+                            None => (),
+                            Some(false) => {
+                                msg_env
+                                    .send_and_clear(&pid, |env| {
+                                        (atoms::glare(), &pc_uuid, atoms::ignored()).encode(env)
+                                    })
+                                    .unwrap();
+                                continue;
+                            }
+                            Some(true) => {
+                                if let Err(err) = rollback_local_description(&lock).await {
+                                    msg_env
+                                        .send_and_clear(&pid, |env| {
+                                            (atoms::glare_error(), &pc_uuid, err).encode(env)
+                                        })
+                                        .unwrap();
+                                    continue;
+                                }
+
+                                msg_env
+                                    .send_and_clear(&pid, |env| {
+                                        (atoms::glare(), &pc_uuid, atoms::rolled_back()).encode(env)
+                                    })
+                                    .unwrap();
+                            }
+                        }
+                    }
+
                     let resp = lock.set_remote_description(session).await;
 
                     msg_env
@@ -944,6 +2696,15 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
                         })
                         .unwrap();
                 }
+                Some(Msg::Snapshot) => {
+                    let summary = health::snapshot(&pc).await;
+
+                    msg_env
+                        .send_and_clear(&pid, |env| {
+                            (atoms::snapshot(), &pc_uuid, summary).encode(env)
+                        })
+                        .unwrap();
+                }
                 Some(Msg::SignalingState) => {
                     let lock = pc.clone();
                     let resp = lock.signaling_state();
@@ -964,15 +2725,322 @@ fn spawn_rtc_peer_connection(resource: ResourceArc<Ref>, api: Arc<API>, uuid: St
                         })
                         .unwrap();
                 }
+                Some(Msg::GetOutboundVideoStats(sender_uuid, reply)) => {
+                    let stats = match rtp_senders.get(&sender_uuid) {
+                        None => None,
+                        Some(sender) => Some(outbound_video_stats(&pc, sender).await),
+                    };
+                    let _ = reply.send(stats);
+                }
+                Some(Msg::GetTrackStats(track, reply)) => {
+                    let reports = match track_ssrcs(&pc, &rtp_senders, track).await {
+                        None => None,
+                        Some(ssrcs) => Some(rtp_stats(&pc, &ssrcs).await),
+                    };
+                    let _ = reply.send(reports);
+                }
+                Some(Msg::GetReceivers) => {
+                    let mut receivers = vec![];
+                    for transceiver in pc.get_transceivers().await {
+                        receivers.push(rtp_receiver::ReceiverInfo::new(&transceiver).await);
+                    }
+
+                    msg_env
+                        .send_and_clear(&pid, |env| {
+                            (atoms::receivers(), &pc_uuid, receivers).encode(env)
+                        })
+                        .unwrap();
+                }
+                Some(Msg::GetSelectedCandidatePair) => {
+                    let pair = candidate_pair::selected(&pc).await;
+
+                    msg_env
+                        .send_and_clear(&pid, |env| {
+                            (atoms::selected_candidate_pair(), &pc_uuid, pair).encode(env)
+                        })
+                        .unwrap();
+                }
+                Some(Msg::GetConfiguration(reply)) => {
+                    let config = pc.get_configuration().await;
+                    let _ = reply.send(PeerConnectionConfiguration::from(&config));
+                }
+                Some(Msg::GetFingerprint(reply)) => {
+                    // Of a configured certificate chain, the leaf certificate comes first.
+                    let fingerprint = pc
+                        .sctp()
+                        .transport()
+                        .get_local_parameters()
+                        .ok()
+                        .and_then(|params| params.fingerprints.into_iter().next())
+                        .map(Fingerprint::from);
+                    let _ = reply.send(fingerprint);
+                }
+                Some(Msg::GetSctpTransport(reply)) => {
+                    let remote_sdp = pc.remote_description().await.map(|desc| desc.sdp);
+                    let info = sctp::SctpTransportInfo::new(&pc.sctp(), remote_sdp.as_deref());
+                    let _ = reply.send(info);
+                }
+                Some(Msg::GetSenderParameters(sender_uuid, reply)) => {
+                    let params = match rtp_senders.get(&sender_uuid) {
+                        None => None,
+                        Some(sender) => Some(rtp_sender::SendParameters::from(
+                            &sender.get_parameters().await,
+                        )),
+                    };
+                    let _ = reply.send(params);
+                }
+                Some(Msg::GetSenderStats(sender_uuid)) => {
+                    let stats = match rtp_senders.get(&sender_uuid) {
+                        None => None,
+                        Some(sender) => Some(sender_totals(&pc, sender).await),
+                    };
+
+                    msg_env
+                        .send_and_clear(&pid, |env| {
+                            (atoms::sender_stats(), &pc_uuid, &sender_uuid, stats).encode(env)
+                        })
+                        .unwrap();
+                }
+                Some(Msg::GetSenders) => {
+                    let mut senders = vec![];
+                    for (sender_uuid, sender) in rtp_senders.iter() {
+                        senders.push(rtp_sender::SenderInfo::new(sender_uuid, sender).await);
+                    }
+
+                    msg_env
+                        .send_and_clear(&pid, |env| {
+                            (atoms::senders(), &pc_uuid, senders).encode(env)
+                        })
+                        .unwrap();
+                }
+                Some(Msg::SenderActive(sender_uuid, reply)) => {
+                    let active = match rtp_senders.get(&sender_uuid) {
+                        None => None,
+                        Some(sender) => Some(sender.track().await.is_some()),
+                    };
+                    let _ = reply.send(active);
+                }
+                Some(Msg::SetBufferedAmountLowThreshold(data_channel_uuid, threshold, reply)) => {
+                    let resp = match data_channels.get(&data_channel_uuid) {
+                        None => None,
+                        Some(data_channel) => {
+                            data_channel.set_buffered_amount_low_threshold(threshold).await;
+                            Some(())
+                        }
+                    };
+                    let _ = reply.send(resp);
+                }
+                Some(Msg::SetCodecPreferences(mid, codecs, reply)) => {
+                    let transceiver = pc
+                        .get_transceivers()
+                        .await
+                        .into_iter()
+                        .find(|t| t.mid().as_deref() == Some(mid.as_str()));
+                    let resp = match transceiver {
+                        None => None,
+                        Some(transceiver) => Some(transceiver.set_codec_preferences(codecs).await),
+                    };
+                    let _ = reply.send(resp);
+                }
+                Some(Msg::TransceiverCurrentDirection(mid, reply)) => {
+                    let direction = pc
+                        .get_transceivers()
+                        .await
+                        .into_iter()
+                        .find(|t| t.mid().as_deref() == Some(mid.as_str()))
+                        .map(|transceiver| transceiver.current_direction());
+                    let _ = reply.send(direction);
+                }
+                Some(Msg::TransceiverMid(transceiver_uuid, reply)) => {
+                    let mid = rtp_transceivers
+                        .get(&transceiver_uuid)
+                        .map(|transceiver| transceiver.mid().map(|mid| mid.to_string()));
+                    let _ = reply.send(mid);
+                }
+                Some(Msg::ForwardTrack(track_id, targets)) => {
+                    if let Ok(mut sinks) = remote_track_sinks.lock() {
+                        sinks.forward_to(track_id, targets);
+                    }
+
+                    msg_env
+                        .send_and_clear(&pid, |env| {
+                            (atoms::ok(), &pc_uuid, atoms::forward_track()).encode(env)
+                        })
+                        .unwrap();
+                }
+                Some(Msg::RecordToFile(track_id, file)) => {
+                    if let Ok(mut sinks) = remote_track_sinks.lock() {
+                        sinks.record(track_id, file);
+                    }
+
+                    msg_env
+                        .send_and_clear(&pid, |env| {
+                            (atoms::ok(), &pc_uuid, atoms::record_to_file()).encode(env)
+                        })
+                        .unwrap();
+                }
+                Some(Msg::ReplaceTrack(sender_uuid, track, reply)) => {
+                    let resp = match rtp_senders.get(&sender_uuid) {
+                        None => None,
+                        Some(sender) => Some(sender.replace_track(track).await),
+                    };
+                    let _ = reply.send(resp);
+                }
+                Some(Msg::RequestKeyframe(track_id)) => {
+                    let ssrc = match remote_track_ssrc(&pc, &track_id).await {
+                        None => {
+                            msg_env
+                                .send_and_clear(&pid, |env| {
+                                    (atoms::keyframe_error(), &pc_uuid, atoms::not_found())
+                                        .encode(env)
+                                })
+                                .unwrap();
+                            continue;
+                        }
+                        Some(ssrc) => ssrc,
+                    };
+
+                    // RTCP can only be written once DTLS has set up the SRTCP session,
+                    // which happens as the peer connection connects.
+                    if pc.connection_state() != RTCPeerConnectionState::Connected {
+                        msg_env
+                            .send_and_clear(&pid, |env| {
+                                (atoms::keyframe_error(), &pc_uuid, atoms::not_connected())
+                                    .encode(env)
+                            })
+                            .unwrap();
+                        continue;
+                    }
+
+                    let pli = PictureLossIndication {
+                        sender_ssrc: 0,
+                        media_ssrc: ssrc,
+                    };
+                    let resp = pc.write_rtcp(&[Box::new(pli)]).await;
+
+                    msg_env
+                        .send_and_clear(&pid, |env| match resp {
+                            Err(err) => {
+                                (atoms::keyframe_error(), &pc_uuid, err.to_string()).encode(env)
+                            }
+                            Ok(_) => (atoms::ok(), &pc_uuid, atoms::request_keyframe()).encode(env),
+                        })
+                        .unwrap();
+                }
+                Some(Msg::RestartIce) => {
+                    // Like `restartIce()` in browsers, there is nothing to restart until
+                    // a local description has been set.
+                    if pc.local_description().await.is_some() {
+                        ice_restart_pending = true;
+                    }
+
+                    msg_env
+                        .send_and_clear(&pid, |env| {
+                            (atoms::ok(), &pc_uuid, atoms::restart_ice()).encode(env)
+                        })
+                        .unwrap();
+                }
+                Some(Msg::SetReceiveBitrateCap(bps)) => {
+                    if let Some(previous) = remb_loop.take() {
+                        previous.abort();
+                    }
+
+                    let op = match bps {
+                        Some(bps) => {
+                            remb_loop = Some(spawn_remb_loop(pc.clone(), bps));
+                            atoms::set_receive_bitrate_cap()
+                        }
+                        None => atoms::clear_receive_bitrate_cap(),
+                    };
+
+                    msg_env
+                        .send_and_clear(&pid, |env| (atoms::ok(), &pc_uuid, op).encode(env))
+                        .unwrap();
+                }
+                Some(Msg::SetStatsReporting(interval)) => {
+                    if let Some(previous) = stats_loop.take() {
+                        previous.abort();
+                    }
+
+                    let op = match interval {
+                        Some(interval) => {
+                            let uuid = callback_uuid.clone();
+                            stats_loop = Some(spawn_stats_loop(pc.clone(), pid, uuid, interval));
+                            atoms::start_stats_reporting()
+                        }
+                        None => atoms::stop_stats_reporting(),
+                    };
+
+                    msg_env
+                        .send_and_clear(&pid, |env| (atoms::ok(), &pc_uuid, op).encode(env))
+                        .unwrap();
+                }
+                Some(Msg::SetRtpForwarding(track_id, enabled)) => {
+                    if let Ok(mut sinks) = remote_track_sinks.lock() {
+                        sinks.set_forwarding(track_id, enabled);
+                    }
+
+                    msg_env
+                        .send_and_clear(&pid, |env| {
+                            (atoms::ok(), &pc_uuid, atoms::set_rtp_forwarding()).encode(env)
+                        })
+                        .unwrap();
+                }
+                Some(Msg::WriteRtcp(rtcp)) => {
+                    // As with `RequestKeyframe`, there is no SRTCP session to write to
+                    // until the peer connection has connected.
+                    if pc.connection_state() != RTCPeerConnectionState::Connected {
+                        msg_env
+                            .send_and_clear(&pid, |env| {
+                                (atoms::rtcp_error(), &pc_uuid, atoms::not_connected())
+                                    .encode(env)
+                            })
+                            .unwrap();
+                        continue;
+                    }
+
+                    let packets = rtcp.into_packets(&mut fir_sequence_number);
+                    let resp = pc.write_rtcp(&packets).await;
+
+                    msg_env
+                        .send_and_clear(&pid, |env| match resp {
+                            Err(err) => {
+                                (atoms::rtcp_error(), &pc_uuid, err.to_string()).encode(env)
+                            }
+                            Ok(_) => (atoms::ok(), &pc_uuid, atoms::write_rtcp()).encode(env),
+                        })
+                        .unwrap();
+                }
                 None => break,
             };
         }
 
-        let state = resource.0.lock().unwrap();
-        msg_env
-            .send_and_clear(&state.pid, |env| {
-                (atoms::peer_connection_closed(), &pc_uuid).encode(env)
-            })
-            .unwrap();
+        // The REMB and stats loops hold references to the peer connection.
+        if let Some(remb_loop) = remb_loop.take() {
+            remb_loop.abort();
+        }
+
+        if let Some(stats_loop) = stats_loop.take() {
+            stats_loop.abort();
+        }
+
+        // Close the peer connection before reporting it closed, so that its ICE and
+        // DTLS transports have been torn down by the time Elixir hears about it.
+        if let Err(err) = pc.close().await {
+            trace!("error closing peer connection: {}", err);
+        }
+
+        {
+            let state = resource.lock();
+            msg_env
+                .send_and_clear(&state.pid, |env| {
+                    (atoms::peer_connection_closed(), &pc_uuid).encode(env)
+                })
+                .unwrap();
+        }
+
+        if let Some(reply) = close_reply {
+            let _ = reply.send(());
+        }
     });
 }
@@ -1,8 +1,10 @@
 use rustler::NifUnitEnum;
+use webrtc::dtls_transport::dtls_transport_state::RTCDtlsTransportState;
 use webrtc::ice_transport::ice_connection_state::RTCIceConnectionState;
 use webrtc::ice_transport::ice_gathering_state::RTCIceGatheringState;
 use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
 use webrtc::peer_connection::signaling_state::RTCSignalingState;
+use webrtc::sctp_transport::sctp_transport_state::RTCSctpTransportState;
 
 #[derive(NifUnitEnum)]
 pub enum IceConnectionState {
@@ -99,3 +101,45 @@ impl From<&RTCPeerConnectionState> for ConnectionState {
         }
     }
 }
+
+#[derive(NifUnitEnum)]
+pub enum DtlsTransportState {
+    Closed,
+    Connected,
+    Connecting,
+    Failed,
+    New,
+    Unspecified,
+}
+
+impl From<&RTCDtlsTransportState> for DtlsTransportState {
+    fn from(state: &RTCDtlsTransportState) -> Self {
+        match state {
+            RTCDtlsTransportState::Closed => DtlsTransportState::Closed,
+            RTCDtlsTransportState::Connected => DtlsTransportState::Connected,
+            RTCDtlsTransportState::Connecting => DtlsTransportState::Connecting,
+            RTCDtlsTransportState::Failed => DtlsTransportState::Failed,
+            RTCDtlsTransportState::New => DtlsTransportState::New,
+            RTCDtlsTransportState::Unspecified => DtlsTransportState::Unspecified,
+        }
+    }
+}
+
+#[derive(NifUnitEnum)]
+pub enum SctpTransportState {
+    Closed,
+    Connected,
+    Connecting,
+    Unspecified,
+}
+
+impl From<&RTCSctpTransportState> for SctpTransportState {
+    fn from(state: &RTCSctpTransportState) -> Self {
+        match state {
+            RTCSctpTransportState::Closed => SctpTransportState::Closed,
+            RTCSctpTransportState::Connected => SctpTransportState::Connected,
+            RTCSctpTransportState::Connecting => SctpTransportState::Connecting,
+            RTCSctpTransportState::Unspecified => SctpTransportState::Unspecified,
+        }
+    }
+}
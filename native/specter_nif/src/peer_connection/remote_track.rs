@@ -0,0 +1,285 @@
+use crate::atoms;
+use log::trace;
+use rustler::{Binary, Encoder, LocalPid, NewBinary};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use webrtc::api::media_engine::{MIME_TYPE_H264, MIME_TYPE_OPUS, MIME_TYPE_VP8};
+use webrtc::media::io::h264_writer::H264Writer;
+use webrtc::media::io::ivf_reader::IVFFileHeader;
+use webrtc::media::io::ivf_writer::IVFWriter;
+use webrtc::media::io::ogg_writer::OggWriter;
+use webrtc::media::io::Writer;
+use webrtc::rtp::extension::audio_level_extension::AudioLevelExtension;
+use webrtc::rtp::packet::Packet;
+use webrtc::rtp_transceiver::rtp_codec::RTPCodecType;
+use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
+use webrtc::track::track_local::TrackLocalWriter;
+use webrtc::track::track_remote::TrackRemote;
+use webrtc::util::{Marshal, Unmarshal};
+
+// How often the audio level of a track is sent, at most.
+const AUDIO_LEVEL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where the packets read from remote tracks go, other than being dropped. Tracks are
+/// given by id, so that a sink may be set up before its track arrives.
+#[derive(Default)]
+pub struct Sinks {
+    forwarded: HashSet<String>,
+    // Local tracks are held weakly, so that forwarding to a track stops once it has been
+    // deleted and is no longer sent by any peer connection.
+    forwarding_targets: HashMap<String, Vec<Weak<TrackLocalStaticRTP>>>,
+    // The format of a recording depends on the codec of its track, which is only known
+    // once the track is read, so files wait here until then.
+    pending_recordings: HashMap<String, File>,
+    recordings: HashMap<String, Box<dyn Writer + Send>>,
+}
+
+impl Sinks {
+    pub fn set_forwarding(&mut self, track_id: String, enabled: bool) {
+        if enabled {
+            self.forwarded.insert(track_id);
+        } else {
+            self.forwarded.remove(&track_id);
+        }
+    }
+
+    /// Writes the packets of the track to the given local tracks, replacing any targets
+    /// already set. No targets stops forwarding.
+    pub fn forward_to(&mut self, track_id: String, targets: Vec<Weak<TrackLocalStaticRTP>>) {
+        if targets.is_empty() {
+            self.forwarding_targets.remove(&track_id);
+        } else {
+            self.forwarding_targets.insert(track_id, targets);
+        }
+    }
+
+    /// Records the track to the given file, replacing any recording already in progress.
+    pub fn record(&mut self, track_id: String, file: File) {
+        if let Some(mut previous) = self.recordings.remove(&track_id) {
+            let _ = previous.close();
+        }
+
+        self.pending_recordings.insert(track_id, file);
+    }
+}
+
+/// The loudest audio level read from a track since it was last sent, so that levels
+/// reach Elixir at most every `AUDIO_LEVEL_INTERVAL` rather than with every packet.
+struct AudioLevels {
+    extension_id: u8,
+    last_sent: Instant,
+    loudest: Option<u8>,
+    voice_activity: bool,
+}
+
+impl AudioLevels {
+    /// Levels are only read from audio tracks that negotiated the audio level header
+    /// extension, which is registered by the `audio_level` registry option.
+    fn new(track: &TrackRemote) -> Option<Self> {
+        if track.kind() != RTPCodecType::Audio {
+            return None;
+        }
+
+        let extension_id = track
+            .params()
+            .header_extensions
+            .iter()
+            .find(|extension| extension.uri == webrtc::sdp::extmap::AUDIO_LEVEL_URI)
+            .and_then(|extension| u8::try_from(extension.id).ok())?;
+
+        Some(AudioLevels {
+            extension_id,
+            last_sent: Instant::now(),
+            loudest: None,
+            voice_activity: false,
+        })
+    }
+
+    /// Reads the level of a packet, and returns the loudest level and whether voice was
+    /// detected once the interval has elapsed. Levels are given in -dBov, from 0 for
+    /// the loudest to 127 for silence.
+    fn read(&mut self, packet: &Packet) -> Option<(u8, bool)> {
+        if let Some(mut extension) = packet.header.get_extension(self.extension_id) {
+            if let Ok(level) = AudioLevelExtension::unmarshal(&mut extension) {
+                self.loudest = Some(self.loudest.map_or(level.level, |l| l.min(level.level)));
+                self.voice_activity |= level.voice;
+            }
+        }
+
+        if self.last_sent.elapsed() < AUDIO_LEVEL_INTERVAL {
+            return None;
+        }
+
+        let level = self.loudest.take()?;
+        let voice_activity = std::mem::take(&mut self.voice_activity);
+        self.last_sent = Instant::now();
+        Some((level, voice_activity))
+    }
+}
+
+/// Picks the writer of a recording from the codec of its track: Annex-B for H264, IVF
+/// for VP8, and Ogg for Opus.
+fn recording_writer(track: &TrackRemote, file: File) -> Result<Box<dyn Writer + Send>, String> {
+    let capability = track.codec().capability;
+    let mime_type = capability.mime_type;
+
+    if mime_type.eq_ignore_ascii_case(MIME_TYPE_H264) {
+        Ok(Box::new(H264Writer::new(file)))
+    } else if mime_type.eq_ignore_ascii_case(MIME_TYPE_VP8) {
+        // The size and frame rate are only advisory, since players read them from the
+        // frames themselves.
+        let header = IVFFileHeader {
+            signature: *b"DKIF",
+            version: 0,
+            header_size: 32,
+            four_cc: *b"VP80",
+            width: 640,
+            height: 480,
+            timebase_denominator: 30,
+            timebase_numerator: 1,
+            num_frames: 900,
+            unused: 0,
+        };
+
+        match IVFWriter::new(file, &header) {
+            Err(err) => Err(err.to_string()),
+            Ok(writer) => Ok(Box::new(writer)),
+        }
+    } else if mime_type.eq_ignore_ascii_case(MIME_TYPE_OPUS) {
+        // The Ogg writer derives the granule position of each page from the RTP
+        // timestamps, so the recording keeps the timing of the samples as sent.
+        match OggWriter::new(file, capability.clock_rate, capability.channels) {
+            Err(err) => Err(err.to_string()),
+            Ok(writer) => Ok(Box::new(writer)),
+        }
+    } else {
+        Err(format!("unsupported codec: {}", mime_type))
+    }
+}
+
+/// Reads a remote track until it ends, then sends `track_ended`. webrtc.rs only reveals
+/// that a remote track has ended by failing to read from it. Each packet is written to
+/// the track's recording and forwarding targets, if any, and sent to Elixir as `rtp`
+/// while the track is forwarded. Others are dropped. A recording that cannot be started
+/// sends `recording_error` and is abandoned. Audio tracks that negotiated the audio
+/// level header extension periodically send `audio_level`.
+pub async fn read(
+    track: Arc<TrackRemote>,
+    pid: LocalPid,
+    pc_uuid: Arc<str>,
+    sinks: Arc<Mutex<Sinks>>,
+) {
+    let track_id = track.id();
+    let mut msg_env = rustler::env::OwnedEnv::new();
+    let mut audio_levels = AudioLevels::new(&track);
+
+    while let Ok((packet, _attributes)) = track.read_rtp().await {
+        let mut recording_error = None;
+        let mut targets = vec![];
+
+        let forwarding = match sinks.lock() {
+            Err(_) => false,
+            Ok(mut sinks) => {
+                if let Some(file) = sinks.pending_recordings.remove(&track_id) {
+                    match recording_writer(&track, file) {
+                        Err(reason) => recording_error = Some(reason),
+                        Ok(writer) => {
+                            sinks.recordings.insert(track_id.clone(), writer);
+                        }
+                    }
+                }
+
+                if let Some(recording) = sinks.recordings.get_mut(&track_id) {
+                    if let Err(err) = recording.write_rtp(&packet) {
+                        trace!("error recording rtp packet: {}", err);
+                    }
+                }
+
+                // Targets that have gone away are skipped, and forgotten.
+                if let Some(weak_targets) = sinks.forwarding_targets.get_mut(&track_id) {
+                    weak_targets.retain(|target| match target.upgrade() {
+                        None => false,
+                        Some(target) => {
+                            targets.push(target);
+                            true
+                        }
+                    });
+                }
+
+                sinks.forwarded.contains(&track_id)
+            }
+        };
+
+        for target in targets {
+            if let Err(err) = target.write_rtp(&packet).await {
+                trace!("error forwarding rtp packet: {}", err);
+            }
+        }
+
+        if let Some((level, voice_activity)) =
+            audio_levels.as_mut().and_then(|levels| levels.read(&packet))
+        {
+            msg_env
+                .send_and_clear(&pid, |env| {
+                    (atoms::audio_level(), &*pc_uuid, &track_id, level, voice_activity)
+                        .encode(env)
+                })
+                .unwrap_or(());
+        }
+
+        if let Some(reason) = recording_error {
+            msg_env
+                .send_and_clear(&pid, |env| {
+                    (atoms::recording_error(), &*pc_uuid, &track_id, reason).encode(env)
+                })
+                .unwrap_or(());
+        }
+
+        if !forwarding {
+            continue;
+        }
+
+        match packet.marshal() {
+            Err(err) => trace!("error marshaling rtp packet: {}", err),
+            Ok(bytes) => msg_env
+                .send_and_clear(&pid, |env| {
+                    let mut binary = NewBinary::new(env, bytes.len());
+                    binary.as_mut_slice().copy_from_slice(&bytes);
+                    (atoms::rtp(), &*pc_uuid, &track_id, Binary::from(binary)).encode(env)
+                })
+                .unwrap_or(()),
+        }
+    }
+
+    // A recording whose track ended before any packet was read leaves an empty file.
+    let (pending, recording) = match sinks.lock() {
+        Err(_) => (None, None),
+        Ok(mut sinks) => (
+            sinks.pending_recordings.remove(&track_id),
+            sinks.recordings.remove(&track_id),
+        ),
+    };
+
+    let recorded = pending.is_some() || recording.is_some();
+
+    if let Some(mut recording) = recording {
+        if let Err(err) = recording.close() {
+            trace!("error closing recording: {}", err);
+        }
+    }
+
+    if recorded {
+        msg_env
+            .send_and_clear(&pid, |env| {
+                (atoms::recording_finished(), &*pc_uuid, &track_id).encode(env)
+            })
+            .unwrap_or(());
+    }
+
+    msg_env
+        .send_and_clear(&pid, |env| (atoms::track_ended(), &*pc_uuid, &track_id).encode(env))
+        .unwrap_or(());
+}
@@ -0,0 +1,80 @@
+use crate::atoms;
+use rustler::{Atom, Binary, Term};
+use webrtc::rtcp::packet::Packet;
+use webrtc::rtcp::payload_feedbacks::full_intra_request::{FirEntry, FullIntraRequest};
+use webrtc::rtcp::payload_feedbacks::picture_loss_indication::PictureLossIndication;
+use webrtc::rtcp::raw_packet::RawPacket;
+use webrtc::rtcp::receiver_estimated_maximum_bitrate::ReceiverEstimatedMaximumBitrate;
+
+pub type Packets = Vec<Box<dyn Packet + Send + Sync>>;
+
+/// An RTCP packet to be written by a peer connection, either given as one of the
+/// common feedback messages, or already serialized.
+pub enum Rtcp {
+    Pli(u32),
+    Fir(u32),
+    Remb(u64, Vec<u32>),
+    Serialized(Packets),
+}
+
+impl Rtcp {
+    /// Decodes `{:pli, ssrc}`, `{:fir, ssrc}`, `{:remb, bps, ssrcs}` or a binary holding
+    /// a compound RTCP packet. Serialized packets of a type that webrtc.rs does not
+    /// know are refused, rather than being sent as is.
+    pub fn decode(term: Term) -> Result<Self, Atom> {
+        if let Ok(binary) = term.decode::<Binary>() {
+            let packets = webrtc::rtcp::packet::unmarshal(&mut binary.as_slice())
+                .map_err(|_| atoms::invalid_rtcp_packet())?;
+
+            if packets
+                .iter()
+                .any(|packet| packet.as_any().is::<RawPacket>())
+            {
+                return Err(atoms::unsupported_rtcp_packet());
+            }
+
+            return Ok(Rtcp::Serialized(packets));
+        }
+
+        if let Ok((tag, bps, ssrcs)) = term.decode::<(Atom, u64, Vec<u32>)>() {
+            if tag == atoms::remb() {
+                return Ok(Rtcp::Remb(bps, ssrcs));
+            }
+        }
+
+        match term.decode::<(Atom, u32)>() {
+            Ok((tag, ssrc)) if tag == atoms::pli() => Ok(Rtcp::Pli(ssrc)),
+            Ok((tag, ssrc)) if tag == atoms::fir() => Ok(Rtcp::Fir(ssrc)),
+            _ => Err(atoms::unsupported_rtcp_packet()),
+        }
+    }
+
+    /// Builds the packets to write. Each FIR takes the next of the sequence numbers
+    /// kept by the peer connection, so that the remote can tell new requests from
+    /// retransmitted ones.
+    pub fn into_packets(self, fir_sequence_number: &mut u8) -> Packets {
+        match self {
+            Rtcp::Pli(media_ssrc) => vec![Box::new(PictureLossIndication {
+                sender_ssrc: 0,
+                media_ssrc,
+            })],
+            Rtcp::Fir(media_ssrc) => {
+                *fir_sequence_number = fir_sequence_number.wrapping_add(1);
+                vec![Box::new(FullIntraRequest {
+                    sender_ssrc: 0,
+                    media_ssrc,
+                    fir: vec![FirEntry {
+                        ssrc: media_ssrc,
+                        sequence_number: *fir_sequence_number,
+                    }],
+                })]
+            }
+            Rtcp::Remb(bps, ssrcs) => vec![Box::new(ReceiverEstimatedMaximumBitrate {
+                sender_ssrc: 0,
+                bitrate: bps as f32,
+                ssrcs,
+            })],
+            Rtcp::Serialized(packets) => packets,
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::codec_capability::TrackKind;
+use rustler::NifMap;
+use webrtc::rtp_transceiver::RTCRtpTransceiver;
+use webrtc::track::track_remote::TrackRemote;
+
+/// A remote track being received by an RTP receiver. With simulcast, a receiver has
+/// one track per `rid`.
+#[derive(NifMap)]
+pub struct RemoteTrackInfo {
+    id: String,
+    stream_id: String,
+    rid: String,
+    ssrc: u32,
+    mime_type: String,
+}
+
+impl From<&TrackRemote> for RemoteTrackInfo {
+    fn from(track: &TrackRemote) -> Self {
+        RemoteTrackInfo {
+            id: track.id(),
+            stream_id: track.stream_id(),
+            rid: track.rid().to_owned(),
+            ssrc: track.ssrc(),
+            mime_type: track.codec().capability.mime_type,
+        }
+    }
+}
+
+/// A summary of the RTP receiver of a transceiver. The `mid` is `None` until the
+/// transceiver has been negotiated.
+#[derive(NifMap)]
+pub struct ReceiverInfo {
+    mid: Option<String>,
+    kind: Option<TrackKind>,
+    tracks: Vec<RemoteTrackInfo>,
+}
+
+impl ReceiverInfo {
+    pub async fn new(transceiver: &RTCRtpTransceiver) -> Self {
+        let receiver = transceiver.receiver().await;
+        let tracks = receiver
+            .tracks()
+            .await
+            .iter()
+            .map(|track| RemoteTrackInfo::from(track.as_ref()))
+            .collect();
+
+        ReceiverInfo {
+            mid: transceiver.mid().map(|mid| mid.to_string()),
+            kind: TrackKind::from_codec_type(receiver.kind()),
+            tracks,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::codec_capability::TrackKind;
+use rustler::NifMap;
+use webrtc::rtp_transceiver::rtp_codec::{RTCRtpCodecParameters, RTCRtpHeaderExtensionParameters};
+use webrtc::rtp_transceiver::rtp_sender::RTCRtpSender;
+use webrtc::rtp_transceiver::{RTCRtpEncodingParameters, RTCRtpSendParameters};
+
+/// A summary of an RTP sender, identified by the uuid sent back from `add_track`.
+/// Track fields are `None` when the sender has no track attached. The track and stream
+/// ids make up the `a=msid` of the sender's media section. The SSRC is that of the
+/// sender's first encoding, which is its only one unless it sends simulcast.
+#[derive(NifMap)]
+pub struct SenderInfo {
+    id: String,
+    track_id: Option<String>,
+    stream_id: Option<String>,
+    kind: Option<TrackKind>,
+    mime_type: Option<String>,
+    ssrc: Option<u32>,
+}
+
+impl SenderInfo {
+    pub async fn new(id: &str, sender: &RTCRtpSender) -> Self {
+        let track = sender.track().await;
+        let parameters = sender.get_parameters().await;
+        let mime_type = parameters
+            .rtp_parameters
+            .codecs
+            .first()
+            .map(|codec| codec.capability.mime_type.clone());
+        let ssrc = parameters.encodings.first().map(|encoding| encoding.ssrc);
+
+        SenderInfo {
+            id: id.to_owned(),
+            track_id: track.as_ref().map(|track| track.id().to_owned()),
+            stream_id: track.as_ref().map(|track| track.stream_id().to_owned()),
+            kind: track.and_then(|track| TrackKind::from_codec_type(track.kind())),
+            mime_type,
+            ssrc,
+        }
+    }
+}
+
+#[derive(NifMap)]
+pub struct CodecParameters {
+    mime_type: String,
+    clock_rate: u32,
+    channels: u16,
+    sdp_fmtp_line: String,
+    payload_type: u8,
+}
+
+impl From<&RTCRtpCodecParameters> for CodecParameters {
+    fn from(codec: &RTCRtpCodecParameters) -> Self {
+        CodecParameters {
+            mime_type: codec.capability.mime_type.clone(),
+            clock_rate: codec.capability.clock_rate,
+            channels: codec.capability.channels,
+            sdp_fmtp_line: codec.capability.sdp_fmtp_line.clone(),
+            payload_type: codec.payload_type,
+        }
+    }
+}
+
+#[derive(NifMap)]
+pub struct EncodingParameters {
+    rid: String,
+    ssrc: u32,
+    payload_type: u8,
+}
+
+impl From<&RTCRtpEncodingParameters> for EncodingParameters {
+    fn from(encoding: &RTCRtpEncodingParameters) -> Self {
+        EncodingParameters {
+            rid: encoding.rid.to_string(),
+            ssrc: encoding.ssrc,
+            payload_type: encoding.payload_type,
+        }
+    }
+}
+
+#[derive(NifMap)]
+pub struct HeaderExtensionParameters {
+    uri: String,
+    id: i64,
+}
+
+impl From<&RTCRtpHeaderExtensionParameters> for HeaderExtensionParameters {
+    fn from(extension: &RTCRtpHeaderExtensionParameters) -> Self {
+        HeaderExtensionParameters {
+            uri: extension.uri.clone(),
+            id: extension.id as i64,
+        }
+    }
+}
+
+/// An Elixir-friendly representation of `RTCRtpSendParameters`.
+#[derive(NifMap)]
+pub struct SendParameters {
+    encodings: Vec<EncodingParameters>,
+    codecs: Vec<CodecParameters>,
+    header_extensions: Vec<HeaderExtensionParameters>,
+}
+
+impl From<&RTCRtpSendParameters> for SendParameters {
+    fn from(params: &RTCRtpSendParameters) -> Self {
+        SendParameters {
+            encodings: params
+                .encodings
+                .iter()
+                .map(EncodingParameters::from)
+                .collect(),
+            codecs: params
+                .rtp_parameters
+                .codecs
+                .iter()
+                .map(CodecParameters::from)
+                .collect(),
+            header_extensions: params
+                .rtp_parameters
+                .header_extensions
+                .iter()
+                .map(HeaderExtensionParameters::from)
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use super::peer_conn_state::SctpTransportState;
+use rustler::NifMap;
+use webrtc::sctp_transport::RTCSctpTransport;
+
+// The largest message webrtc.rs will send. This is not negotiated: webrtc.rs starts
+// every SCTP association with the default limit of webrtc-sctp, and does not advertise
+// `a=max-message-size` in its own descriptions.
+const LOCAL_MAX_MESSAGE_SIZE: u32 = 65536;
+
+// The limit a remote peer is assumed to accept when its description does not include
+// `a=max-message-size`, as per RFC 8841.
+const DEFAULT_REMOTE_MAX_MESSAGE_SIZE: u32 = 65536;
+
+/// The SCTP transport carrying the data channels of a peer connection. `max_channels`
+/// is the number of streams, and so of data channels, that may be open at once.
+#[derive(NifMap)]
+pub struct SctpTransportInfo {
+    state: SctpTransportState,
+    max_channels: u16,
+    max_message_size: u32,
+}
+
+impl SctpTransportInfo {
+    /// Describes the transport, capping the largest message that can be sent by what
+    /// the remote description accepts once one has been set.
+    pub fn new(transport: &RTCSctpTransport, remote_sdp: Option<&str>) -> Self {
+        SctpTransportInfo {
+            state: SctpTransportState::from(&transport.state()),
+            max_channels: transport.max_channels(),
+            max_message_size: max_message_size(remote_sdp),
+        }
+    }
+}
+
+/// The largest message that can be sent, following the WebRTC specification: the
+/// smaller of the local limit and the remote `a=max-message-size`, where a remote limit
+/// of 0 means that the remote accepts messages of any size.
+fn max_message_size(remote_sdp: Option<&str>) -> u32 {
+    let remote = match remote_sdp {
+        None => return LOCAL_MAX_MESSAGE_SIZE,
+        Some(sdp) => sdp
+            .lines()
+            .filter_map(|line| line.trim_end().strip_prefix("a=max-message-size:"))
+            .find_map(|size| size.trim().parse::<u64>().ok())
+            .unwrap_or_else(|| u64::from(DEFAULT_REMOTE_MAX_MESSAGE_SIZE)),
+    };
+
+    match remote {
+        0 => LOCAL_MAX_MESSAGE_SIZE,
+        remote => u64::from(LOCAL_MAX_MESSAGE_SIZE).min(remote) as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::max_message_size;
+
+    #[test]
+    fn uses_the_local_limit_without_a_remote_description() {
+        assert_eq!(max_message_size(None), 65536);
+    }
+
+    #[test]
+    fn defaults_the_remote_limit_when_not_announced() {
+        assert_eq!(max_message_size(Some("v=0\r\nm=application 9\r\n")), 65536);
+    }
+
+    #[test]
+    fn caps_the_local_limit_by_the_remote_limit() {
+        let sdp = "m=application 9\r\na=max-message-size:16384\r\n";
+        assert_eq!(max_message_size(Some(sdp)), 16384);
+
+        let sdp = "m=application 9\r\na=max-message-size:262144\r\n";
+        assert_eq!(max_message_size(Some(sdp)), 65536);
+    }
+
+    #[test]
+    fn treats_a_remote_limit_of_zero_as_unlimited() {
+        let sdp = "m=application 9\r\na=max-message-size:0\r\n";
+        assert_eq!(max_message_size(Some(sdp)), 65536);
+    }
+}
@@ -0,0 +1,35 @@
+use rustler::NifUnitEnum;
+use webrtc::rtp_transceiver::rtp_transceiver_direction::RTCRtpTransceiverDirection;
+
+#[derive(Clone, Copy, NifUnitEnum)]
+pub enum TransceiverDirection {
+    Inactive,
+    Recvonly,
+    Sendonly,
+    Sendrecv,
+}
+
+impl TransceiverDirection {
+    /// Returns `None` for `RTCRtpTransceiverDirection::Unspecified`, which is the current
+    /// direction of a transceiver until it has been negotiated, and once it has stopped.
+    pub fn from_rtc_direction(direction: RTCRtpTransceiverDirection) -> Option<Self> {
+        match direction {
+            RTCRtpTransceiverDirection::Inactive => Some(TransceiverDirection::Inactive),
+            RTCRtpTransceiverDirection::Recvonly => Some(TransceiverDirection::Recvonly),
+            RTCRtpTransceiverDirection::Sendonly => Some(TransceiverDirection::Sendonly),
+            RTCRtpTransceiverDirection::Sendrecv => Some(TransceiverDirection::Sendrecv),
+            RTCRtpTransceiverDirection::Unspecified => None,
+        }
+    }
+}
+
+impl From<TransceiverDirection> for RTCRtpTransceiverDirection {
+    fn from(direction: TransceiverDirection) -> Self {
+        match direction {
+            TransceiverDirection::Inactive => RTCRtpTransceiverDirection::Inactive,
+            TransceiverDirection::Recvonly => RTCRtpTransceiverDirection::Recvonly,
+            TransceiverDirection::Sendonly => RTCRtpTransceiverDirection::Sendonly,
+            TransceiverDirection::Sendrecv => RTCRtpTransceiverDirection::Sendrecv,
+        }
+    }
+}
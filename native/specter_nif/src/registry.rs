@@ -0,0 +1,102 @@
+use crate::atoms;
+use rustler::{Atom, Env, Term};
+use webrtc::api::interceptor_registry as interceptor;
+use webrtc::api::media_engine::MediaEngine;
+use webrtc::interceptor::nack::generator::Generator;
+use webrtc::interceptor::nack::responder::Responder;
+use webrtc::interceptor::registry::Registry;
+use webrtc::rtp_transceiver::rtp_codec::{RTCRtpHeaderExtensionCapability, RTPCodecType};
+use webrtc::rtp_transceiver::RTCPFeedback;
+
+/// Selects which interceptors are added to a Registry. Options that are not given
+/// default to the value of `default_interceptors`, which is true unless given. With
+/// `default_interceptors: false` and no other options, the Registry is left bare.
+/// `audio_level` is not an interceptor, and is only enabled when given.
+#[derive(Clone, Debug)]
+pub struct RegistryOptions {
+    pub audio_level: bool,
+    pub nack: bool,
+    pub pli: bool,
+    pub rtcp_reports: bool,
+    pub twcc: bool,
+}
+
+impl RegistryOptions {
+    pub fn parse<'a>(env: Env<'a>, opts: Term<'a>) -> Result<RegistryOptions, Atom> {
+        if !opts.is_map() {
+            return Err(atoms::invalid_registry_options());
+        };
+
+        let get = |key: Atom, default: bool| -> Result<bool, Atom> {
+            match opts.map_get(key.to_term(env)) {
+                Err(_) => Ok(default),
+                Ok(value) => value
+                    .decode()
+                    .map_err(|_| atoms::invalid_registry_options()),
+            }
+        };
+
+        let default = get(atoms::default_interceptors(), true)?;
+
+        Ok(RegistryOptions {
+            audio_level: get(atoms::audio_level(), false)?,
+            nack: get(atoms::nack(), default)?,
+            pli: get(atoms::pli(), default)?,
+            rtcp_reports: get(atoms::rtcp_reports(), default)?,
+            twcc: get(atoms::twcc(), default)?,
+        })
+    }
+
+    /// Builds a Registry from the individual interceptors, rather than from the default
+    /// bundle. The RTCP feedback and header extensions needed by each interceptor are
+    /// registered on the MediaEngine along the way.
+    pub fn build(&self, media_engine: &mut MediaEngine) -> Result<Registry, webrtc::Error> {
+        let mut registry = Registry::new();
+
+        if self.nack {
+            media_engine.register_feedback(
+                RTCPFeedback {
+                    typ: "nack".to_owned(),
+                    parameter: "".to_owned(),
+                },
+                RTPCodecType::Video,
+            );
+            registry.add(Box::new(Responder::builder()));
+            registry.add(Box::new(Generator::builder()));
+        }
+
+        // PLI is generated by the receiving application rather than by an interceptor,
+        // so enabling it only advertises support during negotiation.
+        if self.pli {
+            media_engine.register_feedback(
+                RTCPFeedback {
+                    typ: "nack".to_owned(),
+                    parameter: "pli".to_owned(),
+                },
+                RTPCodecType::Video,
+            );
+        }
+
+        if self.rtcp_reports {
+            registry = interceptor::configure_rtcp_reports(registry);
+        }
+
+        if self.twcc {
+            registry = interceptor::configure_twcc_receiver_only(registry, media_engine)?;
+        }
+
+        // Audio levels are read from received packets by the peer connection, so only
+        // the header extension needs registering.
+        if self.audio_level {
+            media_engine.register_header_extension(
+                RTCRtpHeaderExtensionCapability {
+                    uri: webrtc::sdp::extmap::AUDIO_LEVEL_URI.to_owned(),
+                },
+                RTPCodecType::Audio,
+                None,
+            )?;
+        }
+
+        Ok(registry)
+    }
+}
@@ -0,0 +1,172 @@
+use crate::atoms;
+use rustler::types::elixir_struct;
+use rustler::{Atom, Decoder, Encoder, Env, NifMap, NifResult, NifUnitEnum, Term};
+use std::convert::TryFrom;
+use std::io::Cursor;
+use webrtc::peer_connection::sdp::sdp_type::RTCSdpType;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+
+#[derive(Clone, Copy, NifUnitEnum)]
+pub enum SdpType {
+    Answer,
+    Offer,
+    Pranswer,
+    Rollback,
+    Unspecified,
+}
+
+impl From<&RTCSdpType> for SdpType {
+    fn from(sdp_type: &RTCSdpType) -> Self {
+        match sdp_type {
+            RTCSdpType::Answer => SdpType::Answer,
+            RTCSdpType::Offer => SdpType::Offer,
+            RTCSdpType::Pranswer => SdpType::Pranswer,
+            RTCSdpType::Rollback => SdpType::Rollback,
+            RTCSdpType::Unspecified => SdpType::Unspecified,
+        }
+    }
+}
+
+impl From<SdpType> for RTCSdpType {
+    fn from(sdp_type: SdpType) -> Self {
+        match sdp_type {
+            SdpType::Answer => RTCSdpType::Answer,
+            SdpType::Offer => RTCSdpType::Offer,
+            SdpType::Pranswer => RTCSdpType::Pranswer,
+            SdpType::Rollback => RTCSdpType::Rollback,
+            SdpType::Unspecified => RTCSdpType::Unspecified,
+        }
+    }
+}
+
+/// An Elixir-friendly representation of an `RTCSessionDescription`, encoded
+/// as a `%Specter.SessionDescription{}` struct.
+pub struct SessionDescription {
+    pub sdp_type: SdpType,
+    pub sdp: String,
+}
+
+impl SessionDescription {
+    pub fn from_json(json: &str) -> Result<Self, Atom> {
+        match serde_json::from_str::<RTCSessionDescription>(json) {
+            Err(_) => Err(atoms::invalid_json()),
+            Ok(desc) => Ok(SessionDescription::from(&desc)),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, Atom> {
+        let desc = RTCSessionDescription::try_from(self)?;
+        serde_json::to_string(&desc).map_err(|_| atoms::invalid_session_description())
+    }
+}
+
+impl From<&RTCSessionDescription> for SessionDescription {
+    fn from(desc: &RTCSessionDescription) -> Self {
+        SessionDescription {
+            sdp_type: SdpType::from(&desc.sdp_type),
+            sdp: desc.sdp.clone(),
+        }
+    }
+}
+
+/// `RTCSessionDescription` holds a private parsed representation of its SDP, so it
+/// cannot be built field by field. Round-tripping through serde keeps this a pure
+/// conversion, without parsing the SDP itself.
+impl TryFrom<&SessionDescription> for RTCSessionDescription {
+    type Error = Atom;
+
+    fn try_from(desc: &SessionDescription) -> Result<Self, Self::Error> {
+        let json = serde_json::json!({
+            "type": RTCSdpType::from(desc.sdp_type),
+            "sdp": desc.sdp,
+        });
+
+        serde_json::from_value(json).map_err(|_| atoms::invalid_session_description())
+    }
+}
+
+impl Encoder for SessionDescription {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let desc = elixir_struct::make_ex_struct(env, "Elixir.Specter.SessionDescription").unwrap();
+
+        desc.map_put(atoms::type_().to_term(env), self.sdp_type.encode(env))
+            .unwrap()
+            .map_put(atoms::sdp().to_term(env), self.sdp.encode(env))
+            .unwrap()
+    }
+}
+
+impl<'a> Decoder<'a> for SessionDescription {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let env = term.get_env();
+        let sdp_type = term.map_get(atoms::type_().to_term(env))?.decode()?;
+        let sdp = term.map_get(atoms::sdp().to_term(env))?.decode()?;
+
+        Ok(SessionDescription { sdp_type, sdp })
+    }
+}
+
+/// Where the SDP of a session description fails to parse, so that munged SDP can be
+/// fixed: the line, counted from 1, its text, and the index of the media section
+/// holding it, which is `None` for the session section.
+#[derive(NifMap)]
+pub struct SdpError {
+    line: usize,
+    text: String,
+    section: Option<usize>,
+    reason: String,
+}
+
+impl SdpError {
+    /// Parses the SDP as webrtc.rs will when the description is set. The parser reads
+    /// the SDP line by line, so the line at fault is the last one read before it failed.
+    /// The exception is a line without a `<type>=` prefix, which the parser reads on into
+    /// the next line, so such a line is reported first.
+    pub fn check(sdp: &str) -> Result<(), SdpError> {
+        let mut reader = Cursor::new(sdp.as_bytes());
+        let reason = match webrtc::sdp::SessionDescription::unmarshal(&mut reader) {
+            Err(err) => err.to_string(),
+            Ok(_) => return Ok(()),
+        };
+
+        // Line breaks read past the end of the faulty line do not count towards it.
+        let bytes = sdp.as_bytes();
+        let mut end = (reader.position() as usize).min(bytes.len());
+        while end > 0 && matches!(bytes[end - 1], b'\r' | b'\n') {
+            end -= 1;
+        }
+
+        let last_read = bytes[..end].iter().filter(|&&byte| byte == b'\n').count();
+        let index = sdp
+            .lines()
+            .take(last_read + 1)
+            .position(|line| !line.is_empty() && line.as_bytes().get(1) != Some(&b'='))
+            .unwrap_or(last_read);
+        let section = sdp
+            .lines()
+            .take(index + 1)
+            .filter(|line| line.starts_with("m="))
+            .count()
+            .checked_sub(1);
+
+        Err(SdpError {
+            line: index + 1,
+            text: sdp.lines().nth(index).unwrap_or_default().to_owned(),
+            section,
+            reason,
+        })
+    }
+}
+
+#[rustler::nif]
+fn sdp_json_to_struct(json: String) -> Result<SessionDescription, Atom> {
+    SessionDescription::from_json(&json)
+}
+
+#[rustler::nif]
+fn sdp_struct_to_json(desc: Term) -> Result<String, Atom> {
+    match desc.decode::<SessionDescription>() {
+        Err(_) => Err(atoms::invalid_session_description()),
+        Ok(desc) => desc.to_json(),
+    }
+}
@@ -1,33 +1,58 @@
 use crate::atoms;
-use crate::codec_capability::RtpCodecCapability;
+use crate::codec_capability::{
+    DefaultCodecs, MediaEngineCodecs, MediaEngineSetup, RtpCodecCapability, TrackKind,
+};
 use crate::config::Config;
 use crate::peer_connection;
+use crate::registry::RegistryOptions;
 use crate::util::gen_uuid;
 use rustler::types::pid::Pid;
 use rustler::{Atom, Encoder, Env, ResourceArc, Term};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use tokio::sync::mpsc::Sender;
+use tokio::sync::Notify;
 use webrtc::api::interceptor_registry as interceptor;
 use webrtc::api::media_engine::MediaEngine;
 use webrtc::api::{APIBuilder, API};
 use webrtc::interceptor::registry::Registry;
-use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+use webrtc::rtp_transceiver::rtp_codec::{
+    RTCRtpCodecCapability, RTCRtpCodecParameters, RTPCodecType,
+};
+use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
 use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
+use webrtc::track::track_local::TrackLocal;
 
 // The resource which will be wrapped in an ResourceArc and returned to
 // Elixir as a reference.
 pub struct Ref(pub(crate) Arc<Mutex<State>>);
 
+impl Ref {
+    /// Locks the state. A task that panics while holding the lock poisons it, but the
+    /// state is only changed by whole inserts and removals, so it is recovered rather
+    /// than failing every call that follows.
+    pub(crate) fn lock(&self) -> MutexGuard<'_, State> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
 pub struct State {
     pub config: Config,
     pub pid: Pid,
 
     apis: HashMap<String, Arc<API>>,
     media_engines: HashMap<String, MediaEngine>,
+    media_engine_codecs: HashMap<String, MediaEngineCodecs>,
+    media_engine_setups: HashMap<String, MediaEngineSetup>,
     peer_connections: HashMap<String, Sender<peer_connection::Msg>>,
+    // The API each peer connection was created from, from the moment it is requested
+    // until it is closed.
+    peer_connection_apis: HashMap<String, String>,
     registries: HashMap<String, Registry>,
+    local_static_rtp_tracks: HashMap<String, Arc<TrackLocalStaticRTP>>,
     local_static_sample_tracks: HashMap<String, Arc<TrackLocalStaticSample>>,
+    playbacks: HashMap<String, Arc<Notify>>,
 }
 
 impl State {
@@ -37,9 +62,14 @@ impl State {
             pid,
             apis: HashMap::new(),
             media_engines: HashMap::new(),
+            media_engine_codecs: HashMap::new(),
+            media_engine_setups: HashMap::new(),
             peer_connections: HashMap::new(),
+            peer_connection_apis: HashMap::new(),
             registries: HashMap::new(),
+            local_static_rtp_tracks: HashMap::new(),
             local_static_sample_tracks: HashMap::new(),
+            playbacks: HashMap::new(),
         }
     }
 
@@ -51,32 +81,94 @@ impl State {
     }
 
     pub(crate) fn get_api(&self, uuid: Term) -> Option<&Arc<API>> {
-        let id: &String = &uuid.decode().unwrap();
-        self.apis.get(id)
+        let id: String = uuid.decode().ok()?;
+        self.apis.get(&id)
+    }
+
+    pub(crate) fn list_apis(&self) -> Vec<String> {
+        sorted_keys(&self.apis)
+    }
+
+    pub(crate) fn remove_api(&mut self, uuid: &str) -> Option<Arc<API>> {
+        self.apis.remove(uuid)
+    }
+
+    /// Whether any peer connection created from the given API has not yet been closed.
+    pub(crate) fn api_in_use(&self, uuid: &str) -> bool {
+        self.peer_connection_apis.values().any(|api| api == uuid)
     }
 
     //***** MediaEngine
 
-    pub(crate) fn add_media_engine(&mut self, uuid: &str, engine: MediaEngine) -> &mut State {
+    pub(crate) fn add_media_engine(
+        &mut self,
+        uuid: &str,
+        engine: MediaEngine,
+        codecs: MediaEngineCodecs,
+        setup: MediaEngineSetup,
+    ) -> &mut State {
         self.media_engines.insert(uuid.to_owned(), engine);
+        self.media_engine_codecs.insert(uuid.to_owned(), codecs);
+        self.media_engine_setups.insert(uuid.to_owned(), setup);
         self
     }
 
     pub(crate) fn get_media_engine(&mut self, uuid: Term) -> Option<&MediaEngine> {
-        // This could stand some error handling. The match implementation
-        // fails with "creates a temporary which is freed while still in use."
-        let id: &String = &uuid.decode().unwrap();
-        self.media_engines.get(id)
+        let id: String = uuid.decode().ok()?;
+        self.media_engines.get(&id)
     }
 
     pub(crate) fn get_media_engine_mut(&mut self, uuid: Term) -> Option<&mut MediaEngine> {
-        let id: &String = &uuid.decode().unwrap();
-        self.media_engines.get_mut(id)
+        let id: String = uuid.decode().ok()?;
+        self.media_engines.get_mut(&id)
+    }
+
+    pub(crate) fn get_media_engine_codecs(&self, uuid: Term) -> Option<&MediaEngineCodecs> {
+        let id: String = uuid.decode().ok()?;
+        self.media_engine_codecs.get(&id)
+    }
+
+    pub(crate) fn get_media_engine_codecs_mut(
+        &mut self,
+        uuid: Term,
+    ) -> Option<&mut MediaEngineCodecs> {
+        let id: String = uuid.decode().ok()?;
+        self.media_engine_codecs.get_mut(&id)
+    }
+
+    pub(crate) fn get_media_engine_setup(&self, uuid: Term) -> Option<&MediaEngineSetup> {
+        let id: String = uuid.decode().ok()?;
+        self.media_engine_setups.get(&id)
+    }
+
+    pub(crate) fn get_media_engine_setup_mut(
+        &mut self,
+        uuid: Term,
+    ) -> Option<&mut MediaEngineSetup> {
+        let id: String = uuid.decode().ok()?;
+        self.media_engine_setups.get_mut(&id)
+    }
+
+    pub(crate) fn list_media_engines(&self) -> Vec<String> {
+        sorted_keys(&self.media_engines)
     }
 
     pub(crate) fn remove_media_engine(&mut self, uuid: Term) -> Option<MediaEngine> {
-        let id: &String = &uuid.decode().unwrap();
-        self.media_engines.remove(id)
+        let id: String = uuid.decode().ok()?;
+        self.media_engine_codecs.remove(&id);
+        self.media_engine_setups.remove(&id);
+        self.media_engines.remove(&id)
+    }
+
+    //***** Playback
+
+    pub(crate) fn add_playback(&mut self, track_uuid: &str, stop: Arc<Notify>) -> &mut State {
+        self.playbacks.insert(track_uuid.to_owned(), stop);
+        self
+    }
+
+    pub(crate) fn remove_playback(&mut self, track_uuid: &str) -> Option<Arc<Notify>> {
+        self.playbacks.remove(track_uuid)
     }
 
     //***** RTCPeerConnection
@@ -91,16 +183,35 @@ impl State {
     }
 
     pub(crate) fn get_peer_connection(&self, uuid: Term) -> Option<&Sender<peer_connection::Msg>> {
-        let id: &String = &uuid.decode().unwrap();
-        self.peer_connections.get(id)
+        let id: String = uuid.decode().ok()?;
+        self.peer_connections.get(&id)
     }
 
     pub(crate) fn remove_peer_connection(
         &mut self,
         uuid: Term,
     ) -> Option<Sender<peer_connection::Msg>> {
-        let id: &String = &uuid.decode().unwrap();
-        self.peer_connections.remove(id)
+        let id: String = uuid.decode().ok()?;
+        self.peer_connection_apis.remove(&id);
+        self.peer_connections.remove(&id)
+    }
+
+    pub(crate) fn list_peer_connections(&self) -> Vec<String> {
+        sorted_keys(&self.peer_connections)
+    }
+
+    pub(crate) fn remove_peer_connection_by_uuid(
+        &mut self,
+        uuid: &str,
+    ) -> Option<Sender<peer_connection::Msg>> {
+        self.peer_connection_apis.remove(uuid);
+        self.peer_connections.remove(uuid)
+    }
+
+    pub(crate) fn add_peer_connection_api(&mut self, uuid: &str, api_uuid: &str) -> &mut State {
+        self.peer_connection_apis
+            .insert(uuid.to_owned(), api_uuid.to_owned());
+        self
     }
 
     //***** Registry
@@ -111,13 +222,17 @@ impl State {
     }
 
     pub(crate) fn get_registry(&mut self, uuid: Term) -> Option<&Registry> {
-        let id: &String = &uuid.decode().unwrap();
-        self.registries.get(id)
+        let id: String = uuid.decode().ok()?;
+        self.registries.get(&id)
+    }
+
+    pub(crate) fn list_registries(&self) -> Vec<String> {
+        sorted_keys(&self.registries)
     }
 
     pub(crate) fn remove_registry(&mut self, uuid: Term) -> Option<Registry> {
-        let id: &String = &uuid.decode().unwrap();
-        self.registries.remove(id)
+        let id: String = uuid.decode().ok()?;
+        self.registries.remove(&id)
     }
 
     //***** Track
@@ -131,12 +246,69 @@ impl State {
         self
     }
 
+    pub(crate) fn add_track_local_static_rtp(
+        &mut self,
+        uuid: &str,
+        track: Arc<TrackLocalStaticRTP>,
+    ) -> &mut State {
+        self.local_static_rtp_tracks.insert(uuid.to_owned(), track);
+        self
+    }
+
     pub(crate) fn get_track_local_static_sample(
         &mut self,
         uuid: &String,
     ) -> Option<&Arc<TrackLocalStaticSample>> {
         self.local_static_sample_tracks.get(uuid)
     }
+
+    pub(crate) fn get_track_local_static_rtp(
+        &mut self,
+        uuid: &String,
+    ) -> Option<&Arc<TrackLocalStaticRTP>> {
+        self.local_static_rtp_tracks.get(uuid)
+    }
+
+    /// Removes a local track of any type, returning whether it existed.
+    pub(crate) fn remove_track(&mut self, uuid: &str) -> bool {
+        let sample = self.local_static_sample_tracks.remove(uuid);
+        let rtp = self.local_static_rtp_tracks.remove(uuid);
+        sample.is_some() || rtp.is_some()
+    }
+
+    /// Lists local tracks of every type.
+    pub(crate) fn list_tracks(&self) -> Vec<String> {
+        let mut uuids: Vec<String> = self
+            .local_static_sample_tracks
+            .keys()
+            .chain(self.local_static_rtp_tracks.keys())
+            .cloned()
+            .collect();
+        uuids.sort();
+        uuids
+    }
+
+    /// Finds a local track of any type, for callers such as `add_track` that do
+    /// not care how media is written to the track.
+    pub(crate) fn get_track_local(
+        &mut self,
+        uuid: &String,
+    ) -> Option<Arc<dyn TrackLocal + Send + Sync>> {
+        if let Some(track) = self.local_static_sample_tracks.get(uuid) {
+            return Some(track.clone());
+        }
+
+        match self.local_static_rtp_tracks.get(uuid) {
+            None => None,
+            Some(track) => Some(track.clone()),
+        }
+    }
+}
+
+fn sorted_keys<V>(map: &HashMap<String, V>) -> Vec<String> {
+    let mut keys: Vec<String> = map.keys().cloned().collect();
+    keys.sort();
+    keys
 }
 
 pub fn load(env: Env) -> bool {
@@ -161,16 +333,16 @@ fn init<'a>(env: Env<'a>, opts: Term<'a>) -> Term<'a> {
 
 #[rustler::nif(name = "config")]
 fn get_config(env: Env, resource: ResourceArc<Ref>) -> Result<Term, Atom> {
-    let state = match resource.0.lock() {
-        Err(_) => return Err(atoms::lock_fail()),
-        Ok(guard) => guard,
-    };
+    let state = resource.lock();
 
     let config = &state.config;
     Ok(config.encode(env))
 }
 
-/// Create a MediaEngine object to configure the default supported codecs.
+/// Create a MediaEngine object to configure the default supported codecs. When
+/// `default_codecs` is false the engine starts out empty, and only negotiates codecs
+/// added with `register_codec`. Otherwise VP9 is registered unless `vp9` is false, and
+/// AV1 unless `av1` is false.
 ///
 /// Open questions:
 /// - What actually is a MediaEngine?
@@ -178,33 +350,151 @@ fn get_config(env: Env, resource: ResourceArc<Ref>) -> Result<Term, Atom> {
 /// - Do we ever interact with it later, or is it just used to configure
 ///   behaviors of RTCPeerConnections?
 #[rustler::nif]
-fn new_media_engine(resource: ResourceArc<Ref>) -> Result<String, Atom> {
-    let mut state = match resource.0.lock() {
-        Err(_) => return Err(atoms::lock_fail()),
-        Ok(guard) => guard,
-    };
+fn new_media_engine(
+    resource: ResourceArc<Ref>,
+    default_codecs: bool,
+    vp9: bool,
+    av1: bool,
+) -> Result<String, Atom> {
+    let mut state = resource.lock();
+
+    let default_codecs = default_codecs.then_some(DefaultCodecs { av1, vp9 });
 
     let mut m = MediaEngine::default();
-    match m.register_default_codecs() {
-        Err(_error) => return Err(atoms::webrtc_error()),
-        Ok(term) => term,
+    let mut codecs = MediaEngineCodecs::default();
+    if let Some(default_codecs) = default_codecs {
+        if default_codecs.register(&mut m).is_err() {
+            return Err(atoms::webrtc_error());
+        }
+        codecs = MediaEngineCodecs::defaults(default_codecs);
     }
 
     let engine_id = gen_uuid();
-    state.add_media_engine(&engine_id, m);
+    state.add_media_engine(&engine_id, m, codecs, MediaEngineSetup::new(default_codecs));
+    Ok(engine_id)
+}
+
+/// Creates a MediaEngine with the same codecs as the given one, so that one codec
+/// configuration can seed several APIs. RTCP feedback and header extensions added by
+/// `new_registry` are not copied, as each copy is given its own registry.
+#[rustler::nif]
+fn clone_media_engine(resource: ResourceArc<Ref>, media_engine_uuid: Term) -> Result<String, Atom> {
+    let mut state = resource.lock();
+
+    let (setup, codecs) = match (
+        state.get_media_engine_setup(media_engine_uuid),
+        state.get_media_engine_codecs(media_engine_uuid),
+    ) {
+        (Some(setup), Some(codecs)) => (setup.clone(), codecs.clone()),
+        _ => return Err(atoms::not_found()),
+    };
+
+    let m = match setup.build() {
+        Err(_error) => return Err(atoms::webrtc_error()),
+        Ok(m) => m,
+    };
+
+    let engine_id = gen_uuid();
+    state.add_media_engine(&engine_id, m, codecs, setup);
     Ok(engine_id)
 }
 
+/// Lists the audio and video codecs registered on a MediaEngine. Returns `not_found`
+/// once the MediaEngine has been consumed by `new_api`.
+#[rustler::nif]
+fn media_engine_codecs(
+    resource: ResourceArc<Ref>,
+    media_engine_uuid: Term,
+) -> Result<MediaEngineCodecs, Atom> {
+    let state = resource.lock();
+
+    match state.get_media_engine_codecs(media_engine_uuid) {
+        None => Err(atoms::not_found()),
+        Some(codecs) => Ok(codecs.clone()),
+    }
+}
+
+/// Registers a single codec on a MediaEngine, with the given payload type. This must
+/// happen before the MediaEngine is consumed by `new_api`. Returns `unknown_mime_type`
+/// for a codec whose mime type webrtc.rs does not know.
+#[rustler::nif]
+fn register_codec<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    media_engine_uuid: Term<'a>,
+    codec: Term<'a>,
+    payload_type: Term<'a>,
+    kind: Term<'a>,
+) -> Term<'a> {
+    let mut state = resource.lock();
+
+    let codec = match codec.decode::<RtpCodecCapability>() {
+        Err(_) => return (atoms::error(), atoms::invalid_codec()).encode(env),
+        Ok(codec) => codec,
+    };
+
+    let codec = match RTCRtpCodecCapability::try_from(codec) {
+        Err(reason) => return (atoms::error(), reason).encode(env),
+        Ok(codec) => codec,
+    };
+
+    let payload_type: u8 = match payload_type.decode() {
+        Err(_) => return (atoms::error(), atoms::invalid_payload_type()).encode(env),
+        Ok(payload_type) => payload_type,
+    };
+
+    let kind = match kind.decode::<TrackKind>() {
+        Err(_) => return (atoms::error(), atoms::invalid_atom()).encode(env),
+        Ok(kind) => RTPCodecType::from(kind),
+    };
+
+    let media_engine = match state.get_media_engine_mut(media_engine_uuid) {
+        None => return (atoms::error(), atoms::not_found()).encode(env),
+        Some(m) => m,
+    };
+
+    let params = RTCRtpCodecParameters {
+        capability: codec,
+        payload_type,
+        ..Default::default()
+    };
+
+    if media_engine.register_codec(params.clone(), kind).is_err() {
+        return (atoms::error(), atoms::webrtc_error()).encode(env);
+    }
+
+    if let Some(codecs) = state.get_media_engine_codecs_mut(media_engine_uuid) {
+        codecs.add(&params, kind);
+    }
+
+    if let Some(setup) = state.get_media_engine_setup_mut(media_engine_uuid) {
+        setup.add(&params, kind);
+    }
+
+    atoms::ok().encode(env)
+}
+
 /// Create an intercepter registry.
 ///
+/// When given an empty map of options, the default interceptors are registered.
+/// Otherwise the registry is built from the interceptors selected by the options,
+/// see `RegistryOptions`.
+///
 /// Open questions:
 /// - What the heck is an intercepter registry?
 /// - How is it used later?
 #[rustler::nif]
-fn new_registry(resource: ResourceArc<Ref>, media_engine_uuid: Term) -> Result<String, Atom> {
-    let mut state = match resource.0.lock() {
-        Err(_) => return Err(atoms::lock_fail()),
-        Ok(guard) => guard,
+fn new_registry<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    media_engine_uuid: Term<'a>,
+    opts: Term<'a>,
+) -> Result<String, Atom> {
+    let mut state = resource.lock();
+
+    let options = match opts.map_size() {
+        Ok(0) => None,
+        _ => Some(RegistryOptions::parse(env, opts)?),
     };
 
     let media_engine = match state.get_media_engine_mut(media_engine_uuid) {
@@ -212,8 +502,12 @@ fn new_registry(resource: ResourceArc<Ref>, media_engine_uuid: Term) -> Result<S
         Some(m) => m,
     };
 
-    let mut registry = Registry::new();
-    registry = match interceptor::register_default_interceptors(registry, media_engine) {
+    let registry = match options {
+        None => interceptor::register_default_interceptors(Registry::new(), media_engine),
+        Some(options) => options.build(media_engine),
+    };
+
+    let registry = match registry {
         Err(_error) => return Err(atoms::webrtc_error()),
         Ok(term) => term,
     };
@@ -223,7 +517,8 @@ fn new_registry(resource: ResourceArc<Ref>, media_engine_uuid: Term) -> Result<S
     Ok(registry_id)
 }
 
-/// Create a new API. This is directly used when creating RTCPeerConnections.
+/// Create a new API. This is directly used when creating RTCPeerConnections. The API
+/// takes its settings, such as the UDP port range, from the config.
 ///
 /// Open questions:
 /// - This is used to create RTCPeerConnections. Is it used for anything else?
@@ -235,10 +530,7 @@ fn new_api<'a>(
     media_engine_uuid: Term<'a>,
     registry_uuid: Term<'a>,
 ) -> Result<String, Atom> {
-    let mut state = match resource.0.lock() {
-        Err(_) => return Err(atoms::lock_fail()),
-        Ok(guard) => guard,
-    };
+    let mut state = resource.lock();
 
     let media_engine = match state.remove_media_engine(media_engine_uuid) {
         None => return Err(atoms::not_found()),
@@ -253,6 +545,37 @@ fn new_api<'a>(
     let api = APIBuilder::new()
         .with_media_engine(media_engine)
         .with_interceptor_registry(registry)
+        .with_setting_engine(state.config.setting_engine())
+        .build();
+
+    let api_id = gen_uuid();
+    state.add_api(&api_id, api);
+    Ok(api_id)
+}
+
+/// Creates an API from a MediaEngine with the default codecs and a Registry with the
+/// default interceptors, as `new_media_engine`, `new_registry` and `new_api` would
+/// when given no options. Neither the MediaEngine nor the Registry is added to the
+/// State hashmap, since they are consumed right away.
+#[rustler::nif]
+fn new_api_with_defaults(resource: ResourceArc<Ref>) -> Result<String, Atom> {
+    let mut state = resource.lock();
+
+    let mut media_engine = MediaEngine::default();
+    if DefaultCodecs::default().register(&mut media_engine).is_err() {
+        return Err(atoms::webrtc_error());
+    }
+
+    let registry =
+        match interceptor::register_default_interceptors(Registry::new(), &mut media_engine) {
+            Err(_error) => return Err(atoms::webrtc_error()),
+            Ok(registry) => registry,
+        };
+
+    let api = APIBuilder::new()
+        .with_media_engine(media_engine)
+        .with_interceptor_registry(registry)
+        .with_setting_engine(state.config.setting_engine())
         .build();
 
     let api_id = gen_uuid();
@@ -260,29 +583,116 @@ fn new_api<'a>(
     Ok(api_id)
 }
 
+/// Creates a track that packetizes samples. Given a `rid`, the track is one encoding
+/// of a simulcast source, to be added to the sender of another encoding with
+/// `add_encoding`.
 #[rustler::nif]
 fn new_track_local_static_sample<'a>(
     resource: ResourceArc<Ref>,
     codec: Term<'a>,
     id: Term<'a>,
     stream_id: Term<'a>,
+    rid: Option<String>,
 ) -> Result<String, Atom> {
-    let mut state = match resource.0.lock() {
-        Err(_) => return Err(atoms::lock_fail()),
-        Ok(guard) => guard,
+    let (codec, id, stream_id) = decode_track_options(codec, id, stream_id)?;
+    let track = match rid {
+        None => TrackLocalStaticSample::new(codec, id, stream_id),
+        Some(rid) => TrackLocalStaticSample::new_with_rid(codec, id, rid, stream_id),
     };
+    let track_id = gen_uuid();
+    resource.lock().add_track_local_static_sample(&track_id, Arc::new(track));
+    Ok(track_id)
+}
 
-    let codec: RtpCodecCapability = codec.decode().unwrap();
-    let track = TrackLocalStaticSample::new(
-        RTCRtpCodecCapability::from(codec),
-        id.decode().unwrap(),
-        stream_id.decode().unwrap(),
-    );
+/// Creates a track that forwards RTP packets. See `new_track_local_static_sample` for
+/// the `rid`.
+#[rustler::nif]
+fn new_track_local_static_rtp<'a>(
+    resource: ResourceArc<Ref>,
+    codec: Term<'a>,
+    id: Term<'a>,
+    stream_id: Term<'a>,
+    rid: Option<String>,
+) -> Result<String, Atom> {
+    let (codec, id, stream_id) = decode_track_options(codec, id, stream_id)?;
+    let track = match rid {
+        None => TrackLocalStaticRTP::new(codec, id, stream_id),
+        Some(rid) => TrackLocalStaticRTP::new_with_rid(codec, id, rid, stream_id),
+    };
     let track_id = gen_uuid();
-    state.add_track_local_static_sample(&track_id, Arc::new(track));
+    resource.lock().add_track_local_static_rtp(&track_id, Arc::new(track));
     Ok(track_id)
 }
 
+/// Decodes the codec, id and stream id of a new local track. A malformed codec is
+/// refused with `invalid_codec`, one with an unknown mime type with `unknown_mime_type`,
+/// and an id or stream id that is not a string with `invalid_track`.
+fn decode_track_options(
+    codec: Term,
+    id: Term,
+    stream_id: Term,
+) -> Result<(RTCRtpCodecCapability, String, String), Atom> {
+    let codec = match codec.decode::<RtpCodecCapability>() {
+        Err(_) => return Err(atoms::invalid_codec()),
+        Ok(codec) => RTCRtpCodecCapability::try_from(codec)?,
+    };
+
+    match (id.decode(), stream_id.decode()) {
+        (Ok(id), Ok(stream_id)) => Ok((codec, id, stream_id)),
+        _ => Err(atoms::invalid_track()),
+    }
+}
+
+/// Returns the UUIDs of all APIs in the State hashmap.
+#[rustler::nif]
+fn list_apis(resource: ResourceArc<Ref>) -> Result<Vec<String>, Atom> {
+    Ok(resource.lock().list_apis())
+}
+
+/// Returns the UUIDs of all MediaEngines in the State hashmap. See `media_engine_exists`
+/// for Notes on when a MediaEngine leaves the State hashmap.
+#[rustler::nif]
+fn list_media_engines(resource: ResourceArc<Ref>) -> Result<Vec<String>, Atom> {
+    Ok(resource.lock().list_media_engines())
+}
+
+/// Returns the UUIDs of all RTCPeerConnections in the State hashmap, including those
+/// that are draining.
+#[rustler::nif]
+fn list_peer_connections(resource: ResourceArc<Ref>) -> Result<Vec<String>, Atom> {
+    Ok(resource.lock().list_peer_connections())
+}
+
+/// Returns the UUIDs of all Registries in the State hashmap. See `media_engine_exists`
+/// for Notes.
+#[rustler::nif]
+fn list_registries(resource: ResourceArc<Ref>) -> Result<Vec<String>, Atom> {
+    Ok(resource.lock().list_registries())
+}
+
+/// Returns the UUIDs of all local tracks in the State hashmap, of any type.
+#[rustler::nif]
+fn list_tracks(resource: ResourceArc<Ref>) -> Result<Vec<String>, Atom> {
+    Ok(resource.lock().list_tracks())
+}
+
+/// Removes an API from the State hashmap. Peer connections hold their own reference to
+/// the API they were created from, but an API is not removed while any of them is open,
+/// so that callers do not lose track of it.
+#[rustler::nif]
+fn destroy_api<'a>(env: Env<'a>, resource: ResourceArc<Ref>, api_uuid: String) -> Term<'a> {
+    let mut state = resource.lock();
+
+    if state.api_in_use(&api_uuid) {
+        return (atoms::error(), atoms::in_use()).encode(env);
+    }
+
+    match state.remove_api(&api_uuid) {
+        None => (atoms::error(), atoms::not_found()).encode(env),
+        Some(_api) => atoms::ok().encode(env),
+    }
+}
+
 /// Returns true or false depending on whether the State hashmap owns a MediaEngine
 /// for the given UUID.
 ///
@@ -291,10 +701,7 @@ fn new_track_local_static_sample<'a>(
 /// in the State hashmap.
 #[rustler::nif]
 fn media_engine_exists(resource: ResourceArc<Ref>, media_engine_uuid: Term) -> Result<bool, Atom> {
-    let mut state = match resource.0.lock() {
-        Err(_) => return Err(atoms::lock_fail()),
-        Ok(guard) => guard,
-    };
+    let mut state = resource.lock();
 
     match state.get_media_engine(media_engine_uuid) {
         None => Ok(false),
@@ -306,10 +713,7 @@ fn media_engine_exists(resource: ResourceArc<Ref>, media_engine_uuid: Term) -> R
 /// for the given UUID.
 #[rustler::nif]
 fn peer_connection_exists(resource: ResourceArc<Ref>, pc_uuid: Term) -> Result<bool, Atom> {
-    let state = match resource.0.lock() {
-        Err(_) => return Err(atoms::lock_fail()),
-        Ok(guard) => guard,
-    };
+    let state = resource.lock();
 
     match state.get_peer_connection(pc_uuid) {
         None => Ok(false),
@@ -323,10 +727,7 @@ fn peer_connection_exists(resource: ResourceArc<Ref>, pc_uuid: Term) -> Result<b
 /// See `media_engine_exists` for Notes.
 #[rustler::nif]
 fn registry_exists(resource: ResourceArc<Ref>, registry_uuid: Term) -> Result<bool, Atom> {
-    let mut state = match resource.0.lock() {
-        Err(_) => return Err(atoms::lock_fail()),
-        Ok(guard) => guard,
-    };
+    let mut state = resource.lock();
 
     match state.get_registry(registry_uuid) {
         None => Ok(false),
@@ -1,73 +1,567 @@
-use crate::state::Ref;
+use crate::state::{Ref, State};
 use crate::{atoms, task};
-use rustler::{Encoder, Env, ResourceArc, Term};
+use rustler::env::OwnedEnv;
+use rustler::{Binary, Encoder, Env, ResourceArc, Term};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Read};
+use std::sync::Arc;
+use tokio::sync::mpsc::channel;
+use tokio::sync::{oneshot, Notify};
 use tokio::time::Duration;
+use webrtc::api::media_engine::{MIME_TYPE_OPUS, MIME_TYPE_VP8};
 use webrtc::media::io::h264_reader::H264Reader;
+use webrtc::media::io::ivf_reader::IVFReader;
+use webrtc::media::io::ogg_reader::OggReader;
 use webrtc::media::Sample;
+use webrtc::track::track_local::TrackLocalWriter;
 
+// Opus pages are written with a 20ms duration, matching the default ptime
+// negotiated by browsers.
+const OGG_PAGE_DURATION: Duration = Duration::from_millis(20);
+
+/// Plays an H264 file into a track. Parsing and writing are decoupled by a bounded
+/// queue of `queue_size` samples: a reader task parses NALs ahead of time, while a
+/// writer task drains the queue on a fixed ticker. Slow parsing on a contended host
+/// then no longer skews the timing of written samples.
+///
+/// Both the ticker and the duration of each sample are derived from `fps`, so that
+/// RTP timestamps advance at the same rate that samples are written.
 #[rustler::nif]
 pub fn play_from_file_h264<'a>(
     env: Env<'a>,
     resource: ResourceArc<Ref>,
     track_uuid: Term<'a>,
-    path: Term<'a>,
+    path: String,
+    queue_size: usize,
+    fps: u32,
 ) -> Term<'a> {
-    let mut state = match resource.0.lock() {
-        Err(_) => return (atoms::error(), atoms::lock_fail()).encode(env),
-        Ok(guard) => guard,
+    let mut state = resource.lock();
+
+    if queue_size == 0 {
+        return (atoms::error(), atoms::invalid_queue_size()).encode(env);
+    }
+
+    let sample_duration = match timebase_duration(1, fps) {
+        None => return (atoms::error(), atoms::invalid_fps()).encode(env),
+        Some(duration) => duration,
     };
 
-    let decoded_track_uuid: String = track_uuid.decode().unwrap();
-    let track = state
-        .get_track_local_static_sample(&decoded_track_uuid)
-        .unwrap()
-        .clone();
+    let decoded_track_uuid: String = match track_uuid.decode() {
+        Err(_) => return (atoms::error(), atoms::invalid_track()).encode(env),
+        Ok(uuid) => uuid,
+    };
+    let track = match state.get_track_local_static_sample(&decoded_track_uuid) {
+        None => return (atoms::error(), atoms::invalid_track()).encode(env),
+        Some(track) => track.clone(),
+    };
 
-    let pid = state.pid;
-    let mut msg_env = rustler::env::OwnedEnv::new();
-    let decoded_path: String = path.decode().unwrap();
+    let stop = start_playback(&mut state, &decoded_track_uuid);
+    let resource = resource.clone();
+    let (sample_tx, mut sample_rx) = channel::<Sample>(queue_size);
 
     // this code is taken from webrtc.rs
     // https://github.com/webrtc-rs/examples/blob/5a0e2861c66a45fca93aadf9e70a5b045b26dc9e/examples/play-from-disk-h264/play-from-disk-h264.rs#L171
     task::spawn(async move {
-        // Open a H264 file and start reading using our H264Reader
-        let file = File::open(&decoded_path).unwrap();
+        // Open a H264 file before starting the writer, so that a missing file only
+        // sends a `playback_error`.
+        let file = match File::open(&path) {
+            Err(err) => {
+                fail_playback(&resource, &decoded_track_uuid, &stop, err.to_string());
+                return;
+            }
+            Ok(file) => file,
+        };
+
+        task::spawn(async move {
+            // It is important to use a time.Ticker instead of time.Sleep because
+            // * avoids accumulating skew, just calling time.Sleep didn't compensate for the time spent parsing the data
+            // * works around latency issues with Sleep
+            let mut ticker = tokio::time::interval(sample_duration);
+
+            // The reader drops its end of the queue at EOF, so this drains whatever
+            // is left before finishing. Dropping the receiver when stopped early, or
+            // when a sample cannot be written, in turn hangs up the reader.
+            loop {
+                let sample = tokio::select! {
+                    _ = stop.notified() => break,
+                    sample = sample_rx.recv() => match sample {
+                        None => break,
+                        Some(sample) => sample,
+                    },
+                };
+
+                if let Err(err) = track.write_sample(&sample).await {
+                    fail_playback(&resource, &decoded_track_uuid, &stop, err.to_string());
+                    return;
+                }
+
+                let _ = ticker.tick().await;
+            }
+
+            log::debug!("All video frames sent\r");
+            finish_playback(&resource, &decoded_track_uuid, &stop);
+        });
+
+        // Start reading using our H264Reader
         let reader = BufReader::new(file);
         let mut h264 = H264Reader::new(reader, 1_048_576);
 
-        log::debug!("Play video from file {}\r", decoded_path);
+        log::debug!("Play video from file {}\r", path);
+
+        while let Some(sample) = next_h264_sample(&mut h264, sample_duration) {
+            // The writer hangs up when playback ends early.
+            if sample_tx.send(sample).await.is_err() {
+                break;
+            }
+        }
+    });
+    atoms::ok().encode(env)
+}
+
+#[rustler::nif]
+pub fn play_audio_from_file<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    track_uuid: Term<'a>,
+    path: String,
+) -> Term<'a> {
+    let mut state = resource.lock();
+
+    let decoded_track_uuid: String = match track_uuid.decode() {
+        Err(_) => return (atoms::error(), atoms::invalid_track()).encode(env),
+        Ok(uuid) => uuid,
+    };
+    let track = match state.get_track_local_static_sample(&decoded_track_uuid) {
+        None => return (atoms::error(), atoms::invalid_track()).encode(env),
+        Some(track) => track.clone(),
+    };
+
+    if !track.codec().mime_type.eq_ignore_ascii_case(MIME_TYPE_OPUS) {
+        return (atoms::error(), atoms::invalid_codec()).encode(env);
+    }
 
-        // It is important to use a time.Ticker instead of time.Sleep because
-        // * avoids accumulating skew, just calling time.Sleep didn't compensate for the time spent parsing the data
-        // * works around latency issues with Sleep
-        let mut ticker = tokio::time::interval(Duration::from_millis(33));
+    let stop = start_playback(&mut state, &decoded_track_uuid);
+    let resource = resource.clone();
+
+    // this code is taken from webrtc.rs
+    // https://github.com/webrtc-rs/webrtc/blob/master/examples/examples/play-from-disk-vpx/play-from-disk-vpx.rs
+    task::spawn(async move {
+        // Open an Ogg file and start reading using our OggReader
+        let file = match File::open(&path) {
+            Err(err) => {
+                fail_playback(&resource, &decoded_track_uuid, &stop, err.to_string());
+                return;
+            }
+            Ok(file) => file,
+        };
+        let (mut ogg, _) = match OggReader::new(BufReader::new(file), true) {
+            Err(err) => {
+                fail_playback(&resource, &decoded_track_uuid, &stop, err.to_string());
+                return;
+            }
+            Ok(reader) => reader,
+        };
+
+        log::debug!("Play audio from file {}\r", path);
+
+        // Keep track of last granule, the difference is the amount of samples in the buffer
+        let mut last_granule: u64 = 0;
+        let mut ticker = tokio::time::interval(OGG_PAGE_DURATION);
         loop {
-            let nal = match h264.next_nal() {
-                Ok(nal) => nal,
+            let (page_data, page_header) = match ogg.parse_next_page() {
+                Ok(page) => page,
+                Err(err) => {
+                    log::debug!("All audio pages parsed and sent: {:?}\r", err);
+                    break;
+                }
+            };
+
+            // A page on which no packet ends has a granule position of -1, and holds
+            // no complete packet to write. Header pages such as OpusTags have a granule
+            // position of 0, and hold no audio.
+            if page_header.granule_position == u64::MAX || page_header.granule_position == 0 {
+                continue;
+            }
+
+            // The amount of samples is the difference between the last and current
+            // timestamp. A granule position that goes backwards counts as no samples.
+            let sample_count = page_header.granule_position.saturating_sub(last_granule);
+            last_granule = page_header.granule_position;
+            let sample_duration = Duration::from_secs(sample_count) / 48_000;
+
+            let resp = track
+                .write_sample(&Sample {
+                    data: page_data.freeze(),
+                    duration: sample_duration,
+                    ..Default::default()
+                })
+                .await;
+
+            if let Err(err) = resp {
+                fail_playback(&resource, &decoded_track_uuid, &stop, err.to_string());
+                return;
+            }
+
+            tokio::select! {
+                _ = stop.notified() => break,
+                _ = ticker.tick() => (),
+            }
+        }
+
+        finish_playback(&resource, &decoded_track_uuid, &stop);
+    });
+    atoms::ok().encode(env)
+}
+
+#[rustler::nif]
+pub fn play_vp8_from_file<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    track_uuid: Term<'a>,
+    path: String,
+) -> Term<'a> {
+    let mut state = resource.lock();
+
+    let decoded_track_uuid: String = match track_uuid.decode() {
+        Err(_) => return (atoms::error(), atoms::invalid_track()).encode(env),
+        Ok(uuid) => uuid,
+    };
+    let track = match state.get_track_local_static_sample(&decoded_track_uuid) {
+        None => return (atoms::error(), atoms::invalid_track()).encode(env),
+        Some(track) => track.clone(),
+    };
+
+    if !track.codec().mime_type.eq_ignore_ascii_case(MIME_TYPE_VP8) {
+        return (atoms::error(), atoms::invalid_codec()).encode(env);
+    }
+
+    let stop = start_playback(&mut state, &decoded_track_uuid);
+    let resource = resource.clone();
+
+    // this code is taken from webrtc.rs
+    // https://github.com/webrtc-rs/webrtc/blob/master/examples/examples/play-from-disk-vpx/play-from-disk-vpx.rs
+    task::spawn(async move {
+        // Open an IVF file and start reading using our IVFReader
+        let file = match File::open(&path) {
+            Err(err) => {
+                fail_playback(&resource, &decoded_track_uuid, &stop, err.to_string());
+                return;
+            }
+            Ok(file) => file,
+        };
+        let (mut ivf, header) = match IVFReader::new(BufReader::new(file)) {
+            Err(err) => {
+                fail_playback(&resource, &decoded_track_uuid, &stop, err.to_string());
+                return;
+            }
+            Ok(reader) => reader,
+        };
+
+        log::debug!("Play VP8 video from file {}\r", path);
+
+        // The IVF timebase is the duration of a single frame, in seconds.
+        let (numerator, denominator) = (header.timebase_numerator, header.timebase_denominator);
+        let frame_duration = match timebase_duration(numerator, denominator) {
+            None => {
+                let reason = format!("invalid IVF timebase: {}/{}", numerator, denominator);
+                fail_playback(&resource, &decoded_track_uuid, &stop, reason);
+                return;
+            }
+            Some(duration) => duration,
+        };
+
+        let mut ticker = tokio::time::interval(frame_duration);
+        loop {
+            let frame = match ivf.parse_next_frame() {
+                Ok((frame, _)) => frame,
                 Err(err) => {
                     log::debug!("All video frames parsed and sent: {:?}\r", err);
-                    msg_env
-                        .send_and_clear(&pid, |env| {
-                            (atoms::playback_finished(), &decoded_track_uuid).encode(env)
-                        })
-                        .unwrap();
                     break;
                 }
             };
 
-            track
+            let resp = track
                 .write_sample(&Sample {
-                    data: nal.data.freeze(),
-                    duration: Duration::from_secs(1),
+                    data: frame.freeze(),
+                    duration: frame_duration,
                     ..Default::default()
                 })
-                .await
-                .unwrap();
+                .await;
+
+            if let Err(err) = resp {
+                fail_playback(&resource, &decoded_track_uuid, &stop, err.to_string());
+                return;
+            }
 
-            let _ = ticker.tick().await;
+            tokio::select! {
+                _ = stop.notified() => break,
+                _ = ticker.tick() => (),
+            }
         }
+
+        finish_playback(&resource, &decoded_track_uuid, &stop);
     });
     atoms::ok().encode(env)
 }
+
+/// Writes a single encoded sample, such as a video frame or an audio packet, to the
+/// track. This blocks until the sample has been written, so that samples pushed from
+/// Elixir are written in order and write errors can be returned to the caller.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn write_track_sample<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    track_uuid: Term<'a>,
+    payload: Binary<'a>,
+    duration_ms: u64,
+) -> Term<'a> {
+    let track = {
+        let mut state = resource.lock();
+
+        let decoded_track_uuid: String = match track_uuid.decode() {
+            Err(_) => return (atoms::error(), atoms::invalid_track()).encode(env),
+            Ok(uuid) => uuid,
+        };
+
+        match state.get_track_local_static_sample(&decoded_track_uuid) {
+            None => return (atoms::error(), atoms::invalid_track()).encode(env),
+            Some(track) => track.clone(),
+        }
+    };
+
+    let sample = Sample {
+        data: payload.as_slice().to_vec().into(),
+        duration: Duration::from_millis(duration_ms),
+        ..Default::default()
+    };
+
+    let (reply_tx, reply_rx) = oneshot::channel();
+    task::spawn(async move {
+        let _ = reply_tx.send(track.write_sample(&sample).await);
+    });
+
+    match reply_rx.blocking_recv() {
+        Err(_) => (atoms::error(), atoms::webrtc_error()).encode(env),
+        Ok(Err(err)) => (atoms::error(), err.to_string()).encode(env),
+        Ok(Ok(())) => atoms::ok().encode(env),
+    }
+}
+
+/// Writes a raw RTP packet to a `TrackLocalStaticRTP`. The packet is forwarded as-is,
+/// apart from the SSRC and payload type which are rewritten for each binding, so that
+/// packets read from a remote track can be relayed without being decoded.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn write_track_rtp<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    track_uuid: Term<'a>,
+    packet: Binary<'a>,
+) -> Term<'a> {
+    let track = {
+        let mut state = resource.lock();
+
+        let decoded_track_uuid: String = match track_uuid.decode() {
+            Err(_) => return (atoms::error(), atoms::invalid_track()).encode(env),
+            Ok(uuid) => uuid,
+        };
+
+        match state.get_track_local_static_rtp(&decoded_track_uuid) {
+            None => return (atoms::error(), atoms::invalid_track()).encode(env),
+            Some(track) => track.clone(),
+        }
+    };
+
+    let data = packet.as_slice().to_vec();
+
+    let (reply_tx, reply_rx) = oneshot::channel();
+    task::spawn(async move {
+        let _ = reply_tx.send(track.write(&data).await);
+    });
+
+    match reply_rx.blocking_recv() {
+        Err(_) => (atoms::error(), atoms::webrtc_error()).encode(env),
+        Ok(Err(err)) => (atoms::error(), err.to_string()).encode(env),
+        Ok(Ok(_)) => atoms::ok().encode(env),
+    }
+}
+
+/// Stops an in-progress playback on the given track. The playback task sends
+/// `playback_finished` once it has stopped writing samples.
+#[rustler::nif]
+pub fn stop_playback<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    track_uuid: Term<'a>,
+) -> Term<'a> {
+    let mut state = resource.lock();
+
+    let decoded_track_uuid: String = match track_uuid.decode() {
+        Err(_) => return (atoms::error(), atoms::invalid_track()).encode(env),
+        Ok(uuid) => uuid,
+    };
+
+    match state.remove_playback(&decoded_track_uuid) {
+        None => (atoms::error(), atoms::not_found()).encode(env),
+        Some(stop) => {
+            stop.notify_one();
+            atoms::ok().encode(env)
+        }
+    }
+}
+
+/// Removes a local track of any type from the state, stopping any playback on it.
+/// RTP senders the track was added to keep their own reference to it, so media
+/// already negotiated is unaffected, but the track can no longer be written to or
+/// added to a peer connection.
+#[rustler::nif]
+pub fn delete_track<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<Ref>,
+    track_uuid: String,
+) -> Term<'a> {
+    let mut state = resource.lock();
+
+    if !state.remove_track(&track_uuid) {
+        return (atoms::error(), atoms::not_found()).encode(env);
+    }
+
+    if let Some(stop) = state.remove_playback(&track_uuid) {
+        stop.notify_one();
+    }
+
+    atoms::ok().encode(env)
+}
+
+/// Registers a new playback for a track, returning the handle used to stop it.
+/// Any playback already writing to the track is stopped, since two playbacks
+/// interleaving samples would garble the output.
+fn start_playback(state: &mut State, track_uuid: &str) -> Arc<Notify> {
+    if let Some(previous) = state.remove_playback(track_uuid) {
+        previous.notify_one();
+    }
+
+    let stop = Arc::new(Notify::new());
+    state.add_playback(track_uuid, stop.clone());
+    stop
+}
+
+fn finish_playback(resource: &ResourceArc<Ref>, track_uuid: &str, stop: &Arc<Notify>) {
+    let mut state = resource.lock();
+    clear_playback(&mut state, track_uuid, stop);
+
+    OwnedEnv::new()
+        .send_and_clear(&state.pid, |env| {
+            (atoms::playback_finished(), track_uuid).encode(env)
+        })
+        .unwrap();
+}
+
+/// Ends a playback which could not start or continue, such as when its file is missing
+/// or a sample cannot be written, with a `playback_error` in place of `playback_finished`.
+fn fail_playback(
+    resource: &ResourceArc<Ref>,
+    track_uuid: &str,
+    stop: &Arc<Notify>,
+    reason: String,
+) {
+    let mut state = resource.lock();
+    clear_playback(&mut state, track_uuid, stop);
+
+    OwnedEnv::new()
+        .send_and_clear(&state.pid, |env| {
+            (atoms::playback_error(), track_uuid, reason).encode(env)
+        })
+        .unwrap();
+}
+
+fn clear_playback(state: &mut State, track_uuid: &str, stop: &Arc<Notify>) {
+    // Leave the entry alone if a newer playback has since replaced this one.
+    if let Some(current) = state.remove_playback(track_uuid) {
+        if !Arc::ptr_eq(&current, stop) {
+            state.add_playback(track_uuid, current);
+        }
+    }
+}
+
+/// Reads the next NAL of an H264 file as a sample lasting `duration`, or `None` once
+/// every NAL has been read.
+fn next_h264_sample<R: Read>(h264: &mut H264Reader<R>, duration: Duration) -> Option<Sample> {
+    match h264.next_nal() {
+        Ok(nal) => Some(Sample {
+            data: nal.data.freeze(),
+            duration,
+            ..Default::default()
+        }),
+        Err(err) => {
+            log::debug!("All video frames parsed: {:?}\r", err);
+            None
+        }
+    }
+}
+
+/// The duration of `numerator / denominator` seconds, at nanosecond precision. Returns
+/// `None` for a zero denominator or a duration that rounds down to zero, on which a
+/// ticker cannot be built.
+fn timebase_duration(numerator: u32, denominator: u32) -> Option<Duration> {
+    if denominator == 0 {
+        return None;
+    }
+
+    match 1_000_000_000 * u64::from(numerator) / u64::from(denominator) {
+        0 => None,
+        nanos => Some(Duration::from_nanos(nanos)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_h264_sample, timebase_duration};
+    use std::io::Cursor;
+    use tokio::time::Duration;
+    use webrtc::media::io::h264_reader::H264Reader;
+
+    fn nanos(nanos: u64) -> Option<Duration> {
+        Some(Duration::from_nanos(nanos))
+    }
+
+    #[test]
+    fn timebase_duration_matches_the_frame_rate() {
+        assert_eq!(timebase_duration(1, 30), nanos(33_333_333));
+        assert_eq!(timebase_duration(1, 240), nanos(4_166_666));
+        assert_eq!(timebase_duration(1001, 30_000), nanos(33_366_666));
+    }
+
+    #[test]
+    fn timebase_duration_keeps_timebases_shorter_than_a_millisecond() {
+        assert_eq!(timebase_duration(1, 90_000), nanos(11_111));
+    }
+
+    #[test]
+    fn timebase_duration_refuses_unusable_timebases() {
+        assert_eq!(timebase_duration(1, 0), None);
+        assert_eq!(timebase_duration(0, 30), None);
+        assert_eq!(timebase_duration(1, 2_000_000_000), None);
+    }
+
+    #[test]
+    fn h264_samples_last_one_frame_at_the_configured_fps() {
+        // An SPS, a PPS and an IDR slice, each behind an Annex-B start code.
+        let annex_b: &[u8] = &[
+            0, 0, 0, 1, 0x67, 0x42, 0x00, 0x1f, //
+            0, 0, 0, 1, 0x68, 0xce, 0x3c, 0x80, //
+            0, 0, 0, 1, 0x65, 0x88, 0x84, 0x00,
+        ];
+
+        for fps in [24, 30, 60] {
+            let duration = timebase_duration(1, fps).unwrap();
+            let mut h264 = H264Reader::new(Cursor::new(annex_b), 1_048_576);
+
+            let mut count = 0;
+            while let Some(sample) = next_h264_sample(&mut h264, duration) {
+                assert_eq!(sample.duration, Duration::from_secs(1) / fps);
+                count += 1;
+            }
+            assert_eq!(count, 3);
+        }
+    }
+}
@@ -26,5 +26,18 @@ defmodule Test.Integration.IceNegotiationTest do
       assert_receive {:ice_candidate, ^pc_offer, _candidate}
       assert_receive {:ice_candidate, ^pc_answer, _candidate}
     end
+
+    test "sends a message when gathering is complete", %{specter: specter, peer_connection: pc} do
+      assert :ok = Specter.PeerConnection.create_data_channel(specter, pc, "foo")
+      assert_receive {:data_channel_created, ^pc}
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+      assert :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      assert_receive {:ok, ^pc, :set_local_description}
+
+      assert_receive {:ice_gathering_complete, ^pc}, 5_000
+      assert :ok = Specter.PeerConnection.ice_gathering_state(specter, pc)
+      assert_receive {:ice_gathering_state, ^pc, :complete}
+    end
   end
 end
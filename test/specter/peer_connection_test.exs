@@ -4,6 +4,85 @@ defmodule Specter.PeerConnectionTest do
 
   @uuid_regex ~r/\b[0-9a-f]{8}\b-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-\b[0-9a-f]{12}\b/i
 
+  describe "add_encoding" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    setup %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+
+      tracks =
+        for rid <- ["low", "mid", "high"], into: %{} do
+          {:ok, track} =
+            Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter", rid: rid)
+
+          {rid, track}
+        end
+
+      [tracks: tracks]
+    end
+
+    test "returns {:error, :not_found} when given a random id", %{
+      specter: specter,
+      tracks: tracks
+    } do
+      pc = UUID.uuid4()
+
+      assert {:error, :not_found} =
+               Specter.PeerConnection.add_encoding(specter, pc, UUID.uuid4(), tracks["mid"])
+    end
+
+    test "returns {:error, :not_found} for an unknown sender", %{
+      specter: specter,
+      peer_connection: pc,
+      tracks: tracks
+    } do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.add_encoding(specter, pc, UUID.uuid4(), tracks["mid"])
+    end
+
+    test "returns {:error, :invalid_track} for an unknown track", %{
+      specter: specter,
+      peer_connection: pc,
+      tracks: tracks
+    } do
+      {:ok, sender} = Specter.PeerConnection.add_track(specter, pc, tracks["low"])
+
+      assert {:error, :invalid_track} =
+               Specter.PeerConnection.add_encoding(specter, pc, sender, UUID.uuid4())
+    end
+
+    test "returns an error when the base track has no rid", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      {:ok, other} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      {:ok, sender} = Specter.PeerConnection.add_track(specter, pc, track)
+
+      assert {:error, reason} = Specter.PeerConnection.add_encoding(specter, pc, sender, other)
+      assert is_binary(reason)
+    end
+
+    test "offers each encoding as a simulcast layer", %{
+      specter: specter,
+      peer_connection: pc,
+      tracks: tracks
+    } do
+      {:ok, sender} = Specter.PeerConnection.add_track(specter, pc, tracks["low"])
+      assert :ok = Specter.PeerConnection.add_encoding(specter, pc, sender, tracks["mid"])
+      assert :ok = Specter.PeerConnection.add_encoding(specter, pc, sender, tracks["high"])
+
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      %{"sdp" => sdp} = Jason.decode!(offer)
+
+      assert sdp =~ "a=rid:low send"
+      assert sdp =~ "a=rid:mid send"
+      assert sdp =~ "a=rid:high send"
+      assert sdp =~ "a=simulcast:send low;mid;high"
+    end
+  end
+
   describe "add_ice_candidate" do
     setup [:initialize_specter, :init_api, :init_peer_connection]
 
@@ -57,6 +136,260 @@ defmodule Specter.PeerConnectionTest do
       assert :ok = Specter.PeerConnection.add_ice_candidate(specter, pc_offer, candidate)
       assert_receive {:ok, ^pc_offer, :add_ice_candidate}
     end
+
+    test "adds a candidate given as a map", %{specter: specter, peer_connection: pc_offer} do
+      api = init_api(specter)
+      pc_answer = init_peer_connection(specter, api)
+
+      assert :ok = Specter.PeerConnection.create_data_channel(specter, pc_offer, "foo")
+      assert_receive {:data_channel_created, ^pc_offer}
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc_offer)
+      assert_receive {:offer, ^pc_offer, offer}
+      assert :ok = Specter.PeerConnection.set_local_description(specter, pc_offer, offer)
+      assert_receive {:ok, ^pc_offer, :set_local_description}
+
+      assert :ok = Specter.PeerConnection.set_remote_description(specter, pc_answer, offer)
+      assert_receive {:ok, ^pc_answer, :set_remote_description}
+
+      assert_receive {:ice_candidate, ^pc_offer, json}
+      %{"candidate" => candidate, "sdpMid" => sdp_mid} = Jason.decode!(json)
+
+      candidate = %{candidate: candidate, sdp_mid: sdp_mid, sdp_mline_index: 0}
+      assert :ok = Specter.PeerConnection.add_ice_candidate(specter, pc_answer, candidate)
+      assert_receive {:ok, ^pc_answer, :add_ice_candidate}
+    end
+
+    test "sends and adds structured candidates with ice_candidate_format: :struct" do
+      {:ok, specter} = Specter.init(ice_candidate_format: :struct)
+      api = init_api(specter)
+      pc_offer = init_peer_connection(specter, api)
+      api = init_api(specter)
+      pc_answer = init_peer_connection(specter, api)
+
+      assert :ok = Specter.PeerConnection.create_data_channel(specter, pc_offer, "foo")
+      assert_receive {:data_channel_created, ^pc_offer}
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc_offer)
+      assert_receive {:offer, ^pc_offer, offer}
+      assert :ok = Specter.PeerConnection.set_local_description(specter, pc_offer, offer)
+      assert_receive {:ok, ^pc_offer, :set_local_description}
+
+      assert :ok = Specter.PeerConnection.set_remote_description(specter, pc_answer, offer)
+      assert_receive {:ok, ^pc_answer, :set_remote_description}
+
+      assert_receive {:ice_candidate, ^pc_offer, %Specter.IceCandidate{} = candidate}
+      assert candidate.candidate =~ "candidate:"
+      assert :ok = Specter.PeerConnection.add_ice_candidate(specter, pc_answer, candidate)
+      assert_receive {:ok, ^pc_answer, :add_ice_candidate}
+    end
+
+    test "returns {:error, :invalid_ice_candidate} for a malformed map", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      candidate = %Specter.IceCandidate{candidate: "candidate:1", sdp_mline_index: -1}
+
+      assert {:error, :invalid_ice_candidate} =
+               Specter.PeerConnection.add_ice_candidate(specter, pc, candidate)
+    end
+
+    test "returns {:error, :invalid_json} for a malformed string", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert {:error, :invalid_json} =
+               Specter.PeerConnection.add_ice_candidate(specter, pc, "{blah:")
+    end
+  end
+
+  describe "add_track" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+
+      assert {:error, :not_found} = Specter.PeerConnection.add_track(specter, UUID.uuid4(), track)
+    end
+
+    test "returns {:error, :invalid_track} when the track is not a uuid", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert {:error, :invalid_track} = Specter.PeerConnection.add_track(specter, pc, :video)
+      assert {:error, :not_found} = Specter.PeerConnection.add_track(specter, :pc, UUID.uuid4())
+    end
+
+    test "returns the uuid of the rtp sender", %{specter: specter, peer_connection: pc} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+
+      assert {:ok, sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      assert String.match?(sender, @uuid_regex)
+      assert_receive {:rtp_sender, ^pc, ^track, ^sender}
+    end
+
+    test "the rtp sender can be used before it is reported", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+
+      {:ok, sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      assert {:ok, %{encodings: [_]}} =
+               Specter.PeerConnection.get_sender_parameters(specter, pc, sender)
+    end
+  end
+
+  describe "add_transceiver" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.add_transceiver(specter, UUID.uuid4(), :audio, :sendrecv)
+    end
+
+    test "returns {:error, :invalid_atom} for an unknown direction", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert {:error, :invalid_atom} =
+               Specter.PeerConnection.add_transceiver(specter, pc, :audio, :sideways)
+    end
+
+    test "offers media sections in the order transceivers were added", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.add_transceiver(specter, pc, :video, :recvonly)
+      assert_receive {:rtp_transceiver, ^pc, _video}
+      assert :ok = Specter.PeerConnection.add_transceiver(specter, pc, :audio, :sendrecv)
+      assert_receive {:rtp_transceiver, ^pc, _audio}
+
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+      assert {:ok, %{"sdp" => sdp}} = Jason.decode(offer)
+
+      assert [_, video, audio] = String.split(sdp, "m=")
+      assert String.starts_with?(video, "video")
+      assert String.contains?(video, "a=recvonly")
+      assert String.starts_with?(audio, "audio")
+      assert String.contains?(audio, "a=sendrecv")
+    end
+  end
+
+  describe "audio_level" do
+    setup :initialize_specter
+
+    setup %{specter: specter} do
+      api = fn ->
+        {:ok, media_engine} = Specter.new_media_engine(specter)
+        {:ok, registry} = Specter.new_registry(specter, media_engine, audio_level: true)
+        {:ok, api} = Specter.new_api(specter, media_engine, registry)
+        api
+      end
+
+      [
+        pc_offer: init_peer_connection(specter, api.()),
+        pc_answer: init_peer_connection(specter, api.())
+      ]
+    end
+
+    test "sends the audio levels of remote tracks", %{
+      specter: specter,
+      pc_offer: pc_offer,
+      pc_answer: pc_answer
+    } do
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticRTP.new(specter, codec, "audio", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, sender}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      assert {:ok, %{header_extensions: extensions}} =
+               Specter.PeerConnection.get_sender_parameters(specter, pc_offer, sender)
+
+      assert %{id: id} =
+               Enum.find(extensions, &(&1.uri == "urn:ietf:params:rtp-hdrext:ssrc-audio-level"))
+
+      Moar.Retry.rescue_for!(5_000, fn ->
+        seq = :erlang.unique_integer([:positive]) |> rem(0x10000)
+        header = <<0x90, 111, seq::16, seq * 960::32, 1::32>>
+        # A one-byte header extension holding a level of 30 with voice, padded to 4 bytes.
+        extension = <<0xBE, 0xDE, 1::16, id::4, 0::4, 1::1, 30::7, 0::16>>
+        packet = header <> extension <> <<0xF8, 0xFF, 0xFE>>
+
+        :ok = Specter.TrackLocalStaticRTP.write_rtp(specter, track, packet)
+        assert_receive {:audio_level, ^pc_answer, "audio", 30, true}, 200
+      end)
+    end
+
+    test "is not sent without the audio_level registry option", %{specter: specter} do
+      pc_offer = init_peer_connection(specter, init_api(specter))
+      pc_answer = init_peer_connection(specter, init_api(specter))
+
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, _sender}
+      assert :ok = Specter.PeerConnection.set_rtp_forwarding(specter, pc_answer, "audio", true)
+      assert_receive {:ok, ^pc_answer, :set_rtp_forwarding}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      Moar.Retry.rescue_for!(5_000, fn ->
+        :ok = Specter.TrackLocalStaticSample.write_sample(specter, track, <<0, 1, 2>>, 20)
+        assert_receive {:rtp, ^pc_answer, "audio", _packet}, 100
+      end)
+
+      refute_receive {:audio_level, ^pc_answer, _track, _level, _voice_activity}, 200
+    end
+  end
+
+  describe "can_trickle_ice_candidates" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    @offer_sdp """
+    v=0
+    o=- 2927307686215094172 2 IN IP4 127.0.0.1
+    s=-
+    t=0 0
+    a=msid-semantic: WMS
+    a=ice-ufrag:ZZZZ
+    a=ice-pwd:AU/SQPupllyS0SDG/eRWDCfA
+    a=fingerprint:sha-256 B7:D5:86:B0:92:C6:A6:03:80:C8:59:47:25:EC:FF:3F:57:F5:97:EF:76:B9:AA:14:B7:8C:C9:B3:4D:CA:1B:0A
+    """
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.can_trickle_ice_candidates(specter, UUID.uuid4())
+    end
+
+    test "is nil before a remote description is set", %{specter: specter, peer_connection: pc} do
+      assert {:ok, nil} = Specter.PeerConnection.can_trickle_ice_candidates(specter, pc)
+    end
+
+    test "is true when the remote description announces trickle", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      sdp = @offer_sdp <> "a=ice-options:trickle\n"
+      assert :ok = Specter.PeerConnection.set_remote_sdp(specter, pc, :offer, sdp)
+      assert_receive {:ok, ^pc, :set_remote_description}
+
+      assert {:ok, true} = Specter.PeerConnection.can_trickle_ice_candidates(specter, pc)
+    end
+
+    test "is false when the remote description does not announce trickle", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.set_remote_sdp(specter, pc, :offer, @offer_sdp)
+      assert_receive {:ok, ^pc, :set_remote_description}
+
+      assert {:ok, false} = Specter.PeerConnection.can_trickle_ice_candidates(specter, pc)
+    end
   end
 
   describe "close" do
@@ -73,6 +406,57 @@ defmodule Specter.PeerConnectionTest do
       assert :ok = Specter.PeerConnection.close(specter, pc)
       assert_receive {:peer_connection_closed, ^pc}
     end
+
+    test "leaves no peer connections or APIs behind when many are created and closed",
+         %{specter: specter} do
+      assert {:ok, []} = Specter.PeerConnection.list(specter)
+      assert {:ok, apis} = Specter.list_apis(specter)
+
+      pairs =
+        for _ <- 1..100 do
+          api = init_api(specter)
+          pc = init_peer_connection(specter, api)
+          assert :ok = Specter.PeerConnection.close(specter, pc)
+          {api, pc}
+        end
+
+      for {api, pc} <- pairs do
+        assert_receive {:peer_connection_closed, ^pc}, 1_000
+        refute Specter.PeerConnection.exists?(specter, pc)
+        assert :ok = Specter.destroy_api(specter, api)
+      end
+
+      assert {:ok, []} = Specter.PeerConnection.list(specter)
+      assert {:ok, ^apis} = Specter.list_apis(specter)
+    end
+  end
+
+  describe "close_sync" do
+    setup [:initialize_specter, :init_api]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} = Specter.PeerConnection.close_sync(specter, UUID.uuid4())
+    end
+
+    test "has sent the closed message by the time it returns", %{specter: specter, api: api} do
+      pc = init_peer_connection(specter, api)
+
+      assert :ok = Specter.PeerConnection.close_sync(specter, pc)
+      assert_received {:peer_connection_closed, ^pc}
+      refute Specter.PeerConnection.exists?(specter, pc)
+    end
+
+    test "closes a connected peer connection", %{specter: specter, api: api} do
+      pc_offer = init_peer_connection(specter, api)
+      pc_answer = init_peer_connection(specter, init_api(specter))
+      assert :ok = Specter.PeerConnection.create_data_channel(specter, pc_offer, "foo")
+      assert_receive {:data_channel_created, ^pc_offer}
+      :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      assert :ok = Specter.PeerConnection.close_sync(specter, pc_offer)
+      assert_received {:peer_connection_closed, ^pc_offer}
+      assert_receive {:connection_state_change, ^pc_offer, :closed}
+    end
   end
 
   describe "connection_state" do
@@ -85,6 +469,20 @@ defmodule Specter.PeerConnectionTest do
       assert :ok = Specter.PeerConnection.connection_state(specter, peer_connection)
       assert_receive {:connection_state, ^peer_connection, :new}
     end
+
+    test "sends state changes back to elixir as they happen", %{
+      specter: specter,
+      peer_connection: pc_offer
+    } do
+      api = init_api(specter)
+      pc_answer = init_peer_connection(specter, api)
+      assert :ok = create_data_channel(specter, pc_offer)
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      assert_receive {:connection_state_change, ^pc_offer, :connecting}
+      assert_receive {:connection_state_change, ^pc_offer, :connected}
+      assert_receive {:connection_state_change, ^pc_answer, :connected}
+    end
   end
 
   describe "create_answer" do
@@ -115,6 +513,43 @@ defmodule Specter.PeerConnectionTest do
       assert {:ok, answer_json} = Jason.decode(answer)
       assert %{"type" => "answer", "sdp" => _sdp} = answer_json
     end
+
+    test "sends back an error when there is no remote description", %{
+      specter: specter,
+      peer_connection: peer_connection
+    } do
+      assert :ok = Specter.PeerConnection.create_answer(specter, peer_connection)
+      assert_receive {:answer_error, ^peer_connection, :no_remote_description, message}
+      assert is_binary(message)
+    end
+  end
+
+  describe "create_answer_sync" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns an error when peer connection does not exist", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.create_answer_sync(specter, UUID.uuid4())
+    end
+
+    test "errors without a remote description", %{specter: specter, peer_connection: pc} do
+      assert {:error, reason} = Specter.PeerConnection.create_answer_sync(specter, pc)
+      assert is_binary(reason)
+    end
+
+    test "returns the answer", %{specter: specter, peer_connection: peer_connection} do
+      pc_offer = init_peer_connection(specter, init_api(specter))
+      assert :ok = Specter.PeerConnection.create_data_channel(specter, pc_offer, "foo")
+      assert_receive {:data_channel_created, ^pc_offer}
+      assert {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc_offer)
+
+      assert :ok = Specter.PeerConnection.set_remote_description(specter, peer_connection, offer)
+      assert_receive {:ok, ^peer_connection, :set_remote_description}
+
+      assert {:ok, answer} = Specter.PeerConnection.create_answer_sync(specter, peer_connection)
+      assert %{"type" => "answer", "sdp" => _sdp} = Jason.decode!(answer)
+      refute_received {:answer, ^peer_connection, _answer}
+    end
   end
 
   describe "create_data_channel" do
@@ -125,6 +560,55 @@ defmodule Specter.PeerConnectionTest do
                Specter.PeerConnection.create_data_channel(specter, UUID.uuid4(), "foo")
     end
 
+    test "accepts options for unordered and unreliable channels", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok =
+               Specter.PeerConnection.create_data_channel(specter, pc, "foo",
+                 ordered: false,
+                 max_retransmits: 0,
+                 protocol: "json"
+               )
+
+      assert_receive {:data_channel_created, ^pc}
+
+      assert :ok =
+               Specter.PeerConnection.create_data_channel(specter, pc, "bar",
+                 max_packet_life_time: 100
+               )
+
+      assert_receive {:data_channel_created, ^pc}
+
+      assert :ok =
+               Specter.PeerConnection.create_data_channel(specter, pc, "baz",
+                 negotiated: true,
+                 id: 7
+               )
+
+      assert_receive {:data_channel_created, ^pc}
+    end
+
+    test "returns {:error, :invalid_data_channel_options} for invalid options", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      for opts <- [
+            [ordered: "no"],
+            [max_retransmits: -1],
+            [max_retransmits: 70_000],
+            [max_retransmits: 1, max_packet_life_time: 100],
+            [negotiated: true],
+            [id: 1],
+            [negotiated: false, id: 1]
+          ] do
+        assert {:error, :invalid_data_channel_options} =
+                 Specter.PeerConnection.create_data_channel(specter, pc, "foo", opts)
+      end
+
+      refute_received {:data_channel_created, ^pc}
+    end
+
     test "sends an :ok message to elixir, and adds it to offers", %{
       specter: specter,
       peer_connection: peer_connection
@@ -152,19 +636,58 @@ defmodule Specter.PeerConnectionTest do
     end
   end
 
-  describe "create_offer" do
+  describe "create_data_channel_sync" do
     setup [:initialize_specter, :init_api, :init_peer_connection]
 
-    test "returns an error when peer connection does not exist", %{specter: specter} do
-      assert {:error, :not_found} = Specter.PeerConnection.create_offer(specter, UUID.uuid4())
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.create_data_channel_sync(specter, UUID.uuid4(), "foo")
     end
 
-    test "returns :ok and then sends an offer json", %{
+    test "returns {:error, :invalid_data_channel_options} for invalid options", %{
       specter: specter,
-      peer_connection: peer_connection
+      peer_connection: pc
     } do
-      assert :ok = Specter.PeerConnection.create_offer(specter, peer_connection)
-      assert_receive {:offer, ^peer_connection, offer}
+      assert {:error, :invalid_data_channel_options} =
+               Specter.PeerConnection.create_data_channel_sync(specter, pc, "foo", id: 1)
+    end
+
+    test "returns the uuid of the data channel", %{specter: specter, peer_connection: pc} do
+      assert {:ok, data_channel} =
+               Specter.PeerConnection.create_data_channel_sync(specter, pc, "foo")
+
+      assert String.match?(data_channel, @uuid_regex)
+      refute_receive {:data_channel_created, ^pc}
+
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+      assert offer |> Jason.decode!() |> Map.get("sdp") |> String.contains?("webrtc-datachannel")
+    end
+
+    test "returns {:error, :draining} once the peer connection is draining", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.drain(specter, pc, grace_period: 1_000)
+
+      assert {:error, :draining} =
+               Specter.PeerConnection.create_data_channel_sync(specter, pc, "foo")
+    end
+  end
+
+  describe "create_offer" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns an error when peer connection does not exist", %{specter: specter} do
+      assert {:error, :not_found} = Specter.PeerConnection.create_offer(specter, UUID.uuid4())
+    end
+
+    test "returns :ok and then sends an offer json", %{
+      specter: specter,
+      peer_connection: peer_connection
+    } do
+      assert :ok = Specter.PeerConnection.create_offer(specter, peer_connection)
+      assert_receive {:offer, ^peer_connection, offer}
       assert is_binary(offer)
 
       assert {:ok, offer_json} = Jason.decode(offer)
@@ -194,7 +717,75 @@ defmodule Specter.PeerConnectionTest do
       assert :ok =
                Specter.PeerConnection.create_offer(specter, peer_connection, ice_restart: true)
 
-      assert_receive {:offer_error, ^peer_connection, "ICEAgent does not exist"}
+      assert_receive {:offer_error, ^peer_connection, :no_ice_agent, "ICEAgent does not exist"}
+    end
+  end
+
+  describe "create_offer_if_needed" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns an error when peer connection does not exist", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.create_offer_if_needed(specter, UUID.uuid4())
+    end
+
+    test "sends an offer when the signaling state is stable", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.create_offer_if_needed(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+      assert %{"type" => "offer", "sdp" => _sdp} = Jason.decode!(offer)
+    end
+
+    test "does not create an offer while one is pending", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+      assert :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      assert_receive {:ok, ^pc, :set_local_description}
+
+      assert {:ok, :no_offer_needed} =
+               Specter.PeerConnection.create_offer_if_needed(specter, pc)
+
+      refute_receive {:offer, ^pc, _offer}
+    end
+
+    test "does not create an offer while answering", %{specter: specter, api: api} do
+      pc_offer = init_peer_connection(specter, api)
+      pc_answer = init_peer_connection(specter, api)
+
+      assert :ok = Specter.PeerConnection.create_data_channel(specter, pc_offer, "foo")
+      assert_receive {:data_channel_created, ^pc_offer}
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc_offer)
+      assert_receive {:offer, ^pc_offer, offer}
+      assert :ok = Specter.PeerConnection.set_remote_description(specter, pc_answer, offer)
+      assert_receive {:ok, ^pc_answer, :set_remote_description}
+
+      assert {:ok, :no_offer_needed} =
+               Specter.PeerConnection.create_offer_if_needed(specter, pc_answer)
+    end
+  end
+
+  describe "create_offer_sync" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns an error when peer connection does not exist", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.create_offer_sync(specter, UUID.uuid4())
+    end
+
+    test "returns the offer", %{specter: specter, peer_connection: peer_connection} do
+      assert {:ok, offer} =
+               Specter.PeerConnection.create_offer_sync(specter, peer_connection,
+                 voice_activity_detection: true,
+                 ice_restart: true
+               )
+
+      assert %{"type" => "offer", "sdp" => _sdp} = Jason.decode!(offer)
+      refute_received {:offer, ^peer_connection, _offer}
     end
   end
 
@@ -257,6 +848,79 @@ defmodule Specter.PeerConnectionTest do
     end
   end
 
+  describe "data_channel_buffered_amount" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.data_channel_buffered_amount(specter, UUID.uuid4(), "dc")
+
+      assert {:error, :not_found} =
+               Specter.PeerConnection.data_channel_buffered_amount(specter, pc, UUID.uuid4())
+    end
+
+    test "returns 0 for a new data channel", %{specter: specter, peer_connection: pc} do
+      {:ok, data_channel} = Specter.PeerConnection.create_data_channel_sync(specter, pc, "foo")
+      assert String.match?(data_channel, @uuid_regex)
+
+      assert {:ok, 0} =
+               Specter.PeerConnection.data_channel_buffered_amount(specter, pc, data_channel)
+    end
+  end
+
+  describe "drain" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} = Specter.PeerConnection.drain(specter, UUID.uuid4())
+    end
+
+    test "refuses new tracks, then closes after the grace period", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+
+      assert :ok = Specter.PeerConnection.drain(specter, pc, grace_period: 200)
+      assert_receive {:ok, ^pc, :drain_peer_connection}
+
+      assert {:ok, sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      assert_receive {:draining_error, ^pc, :add_track}
+      refute_received {:rtp_sender, ^pc, ^track, _sender}
+
+      assert {:error, :not_found} =
+               Specter.PeerConnection.get_sender_parameters(specter, pc, sender)
+
+      refute_received {:peer_connection_closed, ^pc}
+      assert_receive {:peer_connection_closed, ^pc}, 1_000
+      refute Specter.PeerConnection.exists?(specter, pc)
+    end
+  end
+
+  describe "dtls_transport_state_change" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "is sent as the DTLS handshake proceeds", %{
+      specter: specter,
+      peer_connection: pc_offer
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+      assert :ok = create_data_channel(specter, pc_offer)
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      assert_receive {:dtls_transport_state_change, ^pc_offer, :connecting}, 5_000
+      assert_receive {:dtls_transport_state_change, ^pc_offer, :connected}, 5_000
+      assert_receive {:dtls_transport_state_change, ^pc_answer, :connected}, 5_000
+
+      assert :ok = Specter.PeerConnection.close_sync(specter, pc_offer)
+      assert_receive {:dtls_transport_state_change, ^pc_offer, :closed}, 5_000
+    end
+  end
+
   describe "exists?" do
     setup [:initialize_specter, :init_api]
 
@@ -284,100 +948,155 @@ defmodule Specter.PeerConnectionTest do
     end
   end
 
-  describe "get_stats" do
-    setup [
-      :initialize_specter,
-      :init_api,
-      :init_peer_connection,
-      :create_data_channel
-    ]
+  describe "fingerprint" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
 
     test "returns an error when peer connection does not exist", %{specter: specter} do
-      assert {:error, :not_found} = Specter.PeerConnection.get_stats(specter, UUID.uuid4())
+      assert {:error, :not_found} = Specter.PeerConnection.fingerprint(specter, UUID.uuid4())
     end
 
-    test "returns json of all stats", %{specter: specter, peer_connection: pc_offer} do
-      assert :ok = Specter.PeerConnection.get_stats(specter, pc_offer)
-      stats = receive_stats(pc_offer)
+    test "returns the fingerprint offered in the session description", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert {:ok, %{algorithm: "sha-256", value: value}} =
+               Specter.PeerConnection.fingerprint(specter, pc)
 
-      assert %{
-               "dataChannelsAccepted" => 0,
-               "dataChannelsClosed" => 0,
-               "dataChannelsOpened" => 0,
-               "dataChannelsRequested" => 1,
-               "type" => "peer-connection"
-             } = find_stats(stats, "PeerConnection-")
+      create_data_channel(specter, pc)
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
 
-      api = init_api(specter)
-      pc_answer = init_peer_connection(specter, api)
-      assert :ok = create_data_channel(specter, pc_answer)
-      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+      assert %{"sdp" => sdp} = Jason.decode!(offer)
+      assert [_, offered] = Regex.run(~r/a=fingerprint:sha-256 (\S+)/, sdp)
+      assert String.upcase(offered) == String.upcase(value)
+    end
 
-      assert_stats(specter, pc_offer, "PeerConn",
-        data_channels_accepted: 1,
-        data_channels_closed: 0,
-        data_channels_opened: 1,
-        data_channels_requested: 1
-      )
+    test "returns the fingerprint of the configured DTLS certificate" do
+      cert_pem = File.read!("test/fixtures/dtls/cert.pem")
+      key_pem = File.read!("test/fixtures/dtls/key.pem")
+      {:ok, specter} = Specter.init(certificate: cert_pem, private_key: key_pem)
 
-      assert_stats(specter, pc_answer, "PeerConn",
-        data_channels_accepted: 1,
-        data_channels_closed: 0,
-        data_channels_opened: 1,
-        data_channels_requested: 1
-      )
+      pc_1 = init_peer_connection(specter, init_api(specter))
+      pc_2 = init_peer_connection(specter, init_api(specter))
 
-      assert :ok = Specter.PeerConnection.get_stats(specter, pc_offer)
-      stats = receive_stats(pc_offer)
+      [{:Certificate, der, _}] = :public_key.pem_decode(cert_pem)
 
-      assert %{
-               "bytesReceived" => bytes_received,
-               "bytesSent" => bytes_sent
-             } = find_stats(stats, "ice_transport")
+      expected =
+        :crypto.hash(:sha256, der)
+        |> Base.encode16()
+        |> String.graphemes()
+        |> Enum.chunk_every(2)
+        |> Enum.map_join(":", &Enum.join/1)
 
-      assert bytes_received > 0
-      assert bytes_sent > 0
+      assert {:ok, %{value: value}} = Specter.PeerConnection.fingerprint(specter, pc_1)
+      assert String.upcase(value) == expected
+      assert {:ok, %{value: ^value}} = Specter.PeerConnection.fingerprint(specter, pc_2)
     end
   end
 
-  describe "ice_connection_state" do
+  describe "forward_track" do
     setup [:initialize_specter, :init_api, :init_peer_connection]
 
-    test "sends `:new` just after creating a new peer connection back to elixir", %{
+    setup %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, relay} = Specter.TrackLocalStaticRTP.new(specter, codec, "relay", "specter")
+
+      [codec: codec, relay: relay]
+    end
+
+    test "returns {:error, :not_found} when given a random id", %{
       specter: specter,
-      peer_connection: peer_connection
+      relay: relay
     } do
-      assert :ok = Specter.PeerConnection.ice_connection_state(specter, peer_connection)
-      assert_receive {:ice_connection_state, ^peer_connection, :new}
+      assert {:error, :not_found} =
+               Specter.PeerConnection.forward_track(specter, UUID.uuid4(), "audio", [relay])
     end
-  end
 
-  describe "ice_gathering_state" do
-    setup [:initialize_specter, :init_api, :init_peer_connection]
+    test "returns {:error, :invalid_track} for targets that are not RTP tracks", %{
+      specter: specter,
+      peer_connection: pc,
+      codec: codec
+    } do
+      {:ok, sample} = Specter.TrackLocalStaticSample.new(specter, codec, "sample", "specter")
 
-    test "sends `:new` just after creating a new peer connection back to elixir", %{
+      assert {:error, :invalid_track} =
+               Specter.PeerConnection.forward_track(specter, pc, "audio", [UUID.uuid4()])
+
+      assert {:error, :invalid_track} =
+               Specter.PeerConnection.forward_track(specter, pc, "audio", [sample])
+    end
+
+    test "writes the packets of the remote track to the targets", %{
       specter: specter,
-      peer_connection: peer_connection
+      peer_connection: pc_offer,
+      codec: codec,
+      relay: relay
     } do
-      assert :ok = Specter.PeerConnection.ice_gathering_state(specter, peer_connection)
-      assert_receive {:ice_gathering_state, ^peer_connection, :new}
+      pc_answer = init_peer_connection(specter, init_api(specter))
+      pc_relay = init_peer_connection(specter, init_api(specter))
+      pc_viewer = init_peer_connection(specter, init_api(specter))
+
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, _sender}
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_relay, relay)
+      assert_receive {:rtp_sender, ^pc_relay, ^relay, _sender}
+
+      assert :ok = Specter.PeerConnection.forward_track(specter, pc_answer, "audio", [relay])
+      assert_receive {:ok, ^pc_answer, :forward_track}
+      assert :ok = Specter.PeerConnection.set_rtp_forwarding(specter, pc_viewer, "relay", true)
+      assert_receive {:ok, ^pc_viewer, :set_rtp_forwarding}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+      assert :ok = negotiate_connection(specter, pc_relay, pc_viewer)
+
+      Moar.Retry.rescue_for!(5_000, fn ->
+        :ok = Specter.TrackLocalStaticSample.write_sample(specter, track, <<0, 1, 2>>, 20)
+        assert_receive {:rtp, ^pc_viewer, "relay", _packet}, 100
+      end)
+    end
+
+    test "skips targets that have been deleted", %{
+      specter: specter,
+      peer_connection: pc_offer,
+      codec: codec,
+      relay: relay
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, _sender}
+
+      assert :ok = Specter.PeerConnection.forward_track(specter, pc_answer, "audio", [relay])
+      assert_receive {:ok, ^pc_answer, :forward_track}
+      assert :ok = Specter.delete_track(specter, relay)
+
+      assert :ok = Specter.PeerConnection.set_rtp_forwarding(specter, pc_answer, "audio", true)
+      assert_receive {:ok, ^pc_answer, :set_rtp_forwarding}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      Moar.Retry.rescue_for!(5_000, fn ->
+        :ok = Specter.TrackLocalStaticSample.write_sample(specter, track, <<0, 1, 2>>, 20)
+        assert_receive {:rtp, ^pc_answer, "audio", _packet}, 100
+      end)
     end
   end
 
-  describe "local_description" do
+  describe "gathered_local_description" do
     setup [:initialize_specter, :init_api, :init_peer_connection]
 
     test "returns an error when peer connection does not exist", %{specter: specter} do
       assert {:error, :not_found} =
-               Specter.PeerConnection.local_description(specter, UUID.uuid4())
+               Specter.PeerConnection.gathered_local_description(specter, UUID.uuid4())
     end
 
-    test "sends the pending local description back to elixir before ICE finishes", %{
+    test "sends the local description with candidates once ICE gathering finishes", %{
       specter: specter,
       peer_connection: pc
     } do
-      assert :ok = Specter.PeerConnection.local_description(specter, pc)
-      assert_receive {:local_description, ^pc, nil}
+      create_data_channel(specter, pc)
 
       assert :ok = Specter.PeerConnection.create_offer(specter, pc)
       assert_receive {:offer, ^pc, offer}
@@ -385,65 +1104,535 @@ defmodule Specter.PeerConnectionTest do
       assert :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
       assert_receive {:ok, ^pc, :set_local_description}
 
-      assert :ok = Specter.PeerConnection.local_description(specter, pc)
-      assert_receive {:local_description, ^pc, ^offer}
+      assert :ok = Specter.PeerConnection.gathered_local_description(specter, pc)
+      assert_receive {:local_description, ^pc, description}, 5_000
+      assert {:ok, %{"type" => "offer", "sdp" => sdp}} = Jason.decode(description)
+      assert sdp =~ "a=candidate:"
+
+      assert :ok = Specter.PeerConnection.ice_gathering_state(specter, pc)
+      assert_receive {:ice_gathering_state, ^pc, :complete}
     end
   end
 
-  describe "new" do
+  describe "get_configuration" do
     setup [:initialize_specter, :init_api]
 
-    test "returns a UUID, then sends a :peer_connection_ready", %{specter: specter, api: api} do
-      assert {:ok, pc} = Specter.PeerConnection.new(specter, api)
-      assert_receive {:peer_connection_ready, ^pc}
-
-      assert is_binary(pc)
-      assert String.match?(pc, @uuid_regex)
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.get_configuration(specter, UUID.uuid4())
     end
 
-    test "returns {:error, :not_found} when given a random api id", %{specter: specter} do
-      assert {:error, :not_found} = Specter.PeerConnection.new(specter, UUID.uuid4())
+    test "returns the ice servers of the peer connection" do
+      {:ok, specter} =
+        Specter.init(ice_servers: ["stun:stun.example.com:3478", "stun:stun.example.org:3478"])
+
+      pc = init_peer_connection(specter, init_api(specter))
+
+      assert {:ok, config} = Specter.PeerConnection.get_configuration(specter, pc)
+
+      assert %{
+               ice_servers: ["stun:stun.example.com:3478", "stun:stun.example.org:3478"],
+               ice_transport_policy: ice_transport_policy,
+               bundle_policy: bundle_policy,
+               rtcp_mux_policy: rtcp_mux_policy,
+               ice_candidate_pool_size: 0
+             } = config
+
+      assert is_binary(ice_transport_policy)
+      assert is_binary(bundle_policy)
+      assert is_binary(rtcp_mux_policy)
     end
   end
 
-  describe "pending_local_description" do
+  describe "get_receivers" do
     setup [:initialize_specter, :init_api, :init_peer_connection]
 
-    test "returns an error when peer connection does not exist", %{specter: specter} do
-      assert {:error, :not_found} =
-               Specter.PeerConnection.pending_local_description(specter, UUID.uuid4())
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} = Specter.PeerConnection.get_receivers(specter, UUID.uuid4())
     end
 
-    test "sends the pending local description back to elixir before ICE finishes", %{
+    test "sends back receivers with their mid once negotiated", %{
       specter: specter,
-      peer_connection: pc
+      peer_connection: pc_offer
     } do
-      assert :ok = Specter.PeerConnection.pending_local_description(specter, pc)
-      assert_receive {:pending_local_description, ^pc, nil}
+      api = init_api(specter)
+      pc_answer = init_peer_connection(specter, api)
 
-      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
-      assert_receive {:offer, ^pc, offer}
+      assert :ok = Specter.PeerConnection.add_transceiver(specter, pc_offer, :audio, :recvonly)
+      assert_receive {:rtp_transceiver, ^pc_offer, _transceiver}
 
-      assert :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
-      assert_receive {:ok, ^pc, :set_local_description}
+      assert :ok = Specter.PeerConnection.get_receivers(specter, pc_offer)
+      assert_receive {:receivers, ^pc_offer, [%{mid: nil, kind: :audio, tracks: []}]}
 
-      assert :ok = Specter.PeerConnection.pending_local_description(specter, pc)
-      assert_receive {:pending_local_description, ^pc, ^offer}
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc_offer)
+      assert_receive {:offer, ^pc_offer, offer}
+      assert :ok = Specter.PeerConnection.set_local_description(specter, pc_offer, offer)
+      assert_receive {:ok, ^pc_offer, :set_local_description}
+      assert :ok = Specter.PeerConnection.set_remote_description(specter, pc_answer, offer)
+      assert_receive {:ok, ^pc_answer, :set_remote_description}
+
+      assert :ok = Specter.PeerConnection.get_receivers(specter, pc_answer)
+      assert_receive {:receivers, ^pc_answer, [%{mid: "0", kind: :audio}]}
     end
   end
 
-  describe "pending_remote_description" do
-    setup [
-      :initialize_specter,
-      :init_api,
-      :init_peer_connection,
-      :create_data_channel,
-      :create_offer
-    ]
+  describe "get_selected_candidate_pair" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
 
-    test "returns an error when peer connection does not exist", %{specter: specter} do
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
       assert {:error, :not_found} =
-               Specter.PeerConnection.pending_remote_description(specter, UUID.uuid4())
+               Specter.PeerConnection.get_selected_candidate_pair(specter, UUID.uuid4())
+    end
+
+    test "sends back nil before connecting", %{specter: specter, peer_connection: pc} do
+      assert :ok = Specter.PeerConnection.get_selected_candidate_pair(specter, pc)
+      assert_receive {:selected_candidate_pair, ^pc, nil}
+    end
+
+    test "sends back the candidates once connected", %{
+      specter: specter,
+      peer_connection: pc_offer
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+      assert :ok = Specter.PeerConnection.create_data_channel(specter, pc_offer, "foo")
+      assert_receive {:data_channel_created, ^pc_offer}
+      :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      pair =
+        Moar.Retry.rescue_for!(5_000, fn ->
+          :ok = Specter.PeerConnection.get_selected_candidate_pair(specter, pc_offer)
+          assert_receive {:selected_candidate_pair, ^pc_offer, %{} = pair}
+          pair
+        end)
+
+      assert %{local: local, remote: remote} = pair
+
+      for candidate <- [local, remote] do
+        assert %{candidate_type: type, protocol: "udp", address: address, port: port} = candidate
+        assert type in ["host", "srflx", "prflx", "relay"]
+        assert is_binary(address)
+        assert port > 0
+      end
+    end
+  end
+
+  describe "get_senders" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} = Specter.PeerConnection.get_senders(specter, UUID.uuid4())
+    end
+
+    test "sends back an empty list before tracks are added", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.get_senders(specter, pc)
+      assert_receive {:senders, ^pc, []}
+    end
+
+    test "sends back each sender with its track", %{specter: specter, peer_connection: pc} do
+      audio = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      video = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, audio_track} = Specter.TrackLocalStaticSample.new(specter, audio, "audio", "specter")
+      {:ok, video_track} = Specter.TrackLocalStaticSample.new(specter, video, "video", "specter")
+
+      assert {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc, audio_track)
+      assert_receive {:rtp_sender, ^pc, ^audio_track, audio_sender}
+      assert {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc, video_track)
+      assert_receive {:rtp_sender, ^pc, ^video_track, video_sender}
+
+      assert :ok = Specter.PeerConnection.get_senders(specter, pc)
+      assert_receive {:senders, ^pc, senders}
+
+      assert [
+               %{id: ^audio_sender, track_id: "audio", stream_id: "specter", kind: :audio},
+               %{id: ^video_sender, track_id: "video", stream_id: "specter", kind: :video}
+             ] = Enum.sort_by(senders, & &1.track_id)
+    end
+
+    test "sends back the ssrc each sender sends with", %{specter: specter, peer_connection: pc} do
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+
+      assert {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      assert_receive {:rtp_sender, ^pc, ^track, sender}
+
+      assert {:ok, %{encodings: [%{ssrc: ssrc}]}} =
+               Specter.PeerConnection.get_sender_parameters(specter, pc, sender)
+
+      assert :ok = Specter.PeerConnection.get_senders(specter, pc)
+      assert_receive {:senders, ^pc, [%{id: ^sender, ssrc: ^ssrc}]}
+      assert is_integer(ssrc)
+    end
+
+    test "tracks sharing a stream_id are announced in the same msid group", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      audio = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      video = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, audio_track} = Specter.TrackLocalStaticSample.new(specter, audio, "audio", "stream")
+      {:ok, video_track} = Specter.TrackLocalStaticSample.new(specter, video, "video", "stream")
+      {:ok, other_track} = Specter.TrackLocalStaticSample.new(specter, video, "other", "other")
+
+      for track <- [audio_track, video_track, other_track] do
+        assert {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc, track)
+        assert_receive {:rtp_sender, ^pc, ^track, _sender}
+      end
+
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+      assert {:ok, %{"sdp" => sdp}} = Jason.decode(offer)
+
+      msids =
+        sdp
+        |> String.split("\r\n")
+        |> Enum.filter(&String.starts_with?(&1, "a=msid:"))
+        |> Enum.sort()
+
+      assert msids == ["a=msid:other other", "a=msid:stream audio", "a=msid:stream video"]
+    end
+  end
+
+  describe "get_stats" do
+    setup [
+      :initialize_specter,
+      :init_api,
+      :init_peer_connection,
+      :create_data_channel
+    ]
+
+    test "returns an error when peer connection does not exist", %{specter: specter} do
+      assert {:error, :not_found} = Specter.PeerConnection.get_stats(specter, UUID.uuid4())
+    end
+
+    test "returns json of all stats", %{specter: specter, peer_connection: pc_offer} do
+      assert :ok = Specter.PeerConnection.get_stats(specter, pc_offer)
+      stats = receive_stats(pc_offer)
+
+      assert %{
+               "dataChannelsAccepted" => 0,
+               "dataChannelsClosed" => 0,
+               "dataChannelsOpened" => 0,
+               "dataChannelsRequested" => 1,
+               "type" => "peer-connection"
+             } = find_stats(stats, "PeerConnection-")
+
+      api = init_api(specter)
+      pc_answer = init_peer_connection(specter, api)
+      assert :ok = create_data_channel(specter, pc_answer)
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      assert_stats(specter, pc_offer, "PeerConn",
+        data_channels_accepted: 1,
+        data_channels_closed: 0,
+        data_channels_opened: 1,
+        data_channels_requested: 1
+      )
+
+      assert_stats(specter, pc_answer, "PeerConn",
+        data_channels_accepted: 1,
+        data_channels_closed: 0,
+        data_channels_opened: 1,
+        data_channels_requested: 1
+      )
+
+      assert :ok = Specter.PeerConnection.get_stats(specter, pc_offer)
+      stats = receive_stats(pc_offer)
+
+      assert %{
+               "bytesReceived" => bytes_received,
+               "bytesSent" => bytes_sent
+             } = find_stats(stats, "ice_transport")
+
+      assert bytes_received > 0
+      assert bytes_sent > 0
+    end
+  end
+
+  describe "get_track_stats" do
+    setup [:initialize_specter, :init_api]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.get_track_stats(specter, UUID.uuid4(), "audio")
+    end
+
+    test "returns {:error, :not_found} for a track that is not sent", %{
+      specter: specter,
+      api: api
+    } do
+      pc = init_peer_connection(specter, api)
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+
+      assert {:error, :not_found} = Specter.PeerConnection.get_track_stats(specter, pc, track)
+    end
+
+    test "returns only the rtp stats of local and remote tracks", %{
+      specter: specter,
+      api: api
+    } do
+      pc_offer = init_peer_connection(specter, api)
+      pc_answer = init_peer_connection(specter, api)
+
+      opus = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      h264 = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+      {:ok, audio} = Specter.TrackLocalStaticSample.new(specter, opus, "audio", "specter")
+      {:ok, video} = Specter.TrackLocalStaticSample.new(specter, h264, "video", "specter")
+
+      for track <- [audio, video] do
+        {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+        assert_receive {:rtp_sender, ^pc_offer, ^track, _sender}
+      end
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      Moar.Retry.rescue_for!(5_000, fn ->
+        :ok = Specter.TrackLocalStaticSample.write_sample(specter, audio, <<0, 1, 2>>, 20)
+
+        assert {:ok, outbound} = Specter.PeerConnection.get_track_stats(specter, pc_offer, audio)
+        assert [%{"type" => "outbound-rtp"}] = Map.values(Jason.decode!(outbound))
+
+        assert {:ok, inbound} =
+                 Specter.PeerConnection.get_track_stats(specter, pc_answer, "audio")
+
+        assert [%{"type" => "inbound-rtp"}] = Map.values(Jason.decode!(inbound))
+      end)
+    end
+  end
+
+  describe "ice_connection_state" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "sends `:new` just after creating a new peer connection back to elixir", %{
+      specter: specter,
+      peer_connection: peer_connection
+    } do
+      assert :ok = Specter.PeerConnection.ice_connection_state(specter, peer_connection)
+      assert_receive {:ice_connection_state, ^peer_connection, :new}
+    end
+
+    test "sends state changes back to elixir as they happen", %{
+      specter: specter,
+      peer_connection: pc_offer
+    } do
+      api = init_api(specter)
+      pc_answer = init_peer_connection(specter, api)
+      assert :ok = create_data_channel(specter, pc_offer)
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      assert_receive {:ice_connection_state_change, ^pc_offer, :checking}
+      assert_receive {:ice_connection_state_change, ^pc_offer, :connected}
+      assert_receive {:ice_connection_state_change, ^pc_answer, :connected}
+    end
+  end
+
+  describe "ice_gathering_state" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "sends `:new` just after creating a new peer connection back to elixir", %{
+      specter: specter,
+      peer_connection: peer_connection
+    } do
+      assert :ok = Specter.PeerConnection.ice_gathering_state(specter, peer_connection)
+      assert_receive {:ice_gathering_state, ^peer_connection, :new}
+    end
+  end
+
+  describe "list" do
+    setup [:initialize_specter, :init_api]
+
+    test "is empty when there are no peer connections", %{specter: specter} do
+      assert {:ok, []} = Specter.PeerConnection.list(specter)
+    end
+
+    test "returns peer connections that have not closed", %{specter: specter, api: api} do
+      pc_1 = init_peer_connection(specter, api)
+      pc_2 = init_peer_connection(specter, init_api(specter))
+      pc_3 = init_peer_connection(specter, init_api(specter))
+
+      assert {:ok, pcs} = Specter.PeerConnection.list(specter)
+      assert pcs == Enum.sort([pc_1, pc_2, pc_3])
+
+      assert :ok = Specter.PeerConnection.close_sync(specter, pc_2)
+      assert {:ok, pcs} = Specter.PeerConnection.list(specter)
+      assert pcs == Enum.sort([pc_1, pc_3])
+    end
+  end
+
+  describe "local_description" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns an error when peer connection does not exist", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.local_description(specter, UUID.uuid4())
+    end
+
+    test "sends the pending local description back to elixir before ICE finishes", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.local_description(specter, pc)
+      assert_receive {:local_description, ^pc, nil}
+
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+
+      assert :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      assert_receive {:ok, ^pc, :set_local_description}
+
+      assert :ok = Specter.PeerConnection.local_description(specter, pc)
+      assert_receive {:local_description, ^pc, ^offer}
+    end
+  end
+
+  describe "new" do
+    setup [:initialize_specter, :init_api]
+
+    test "returns a UUID, then sends a :peer_connection_ready", %{specter: specter, api: api} do
+      assert {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      assert_receive {:peer_connection_ready, ^pc}
+
+      assert is_binary(pc)
+      assert String.match?(pc, @uuid_regex)
+    end
+
+    test "returns {:error, :not_found} when given a random api id", %{specter: specter} do
+      assert {:error, :not_found} = Specter.PeerConnection.new(specter, UUID.uuid4())
+    end
+
+    test "sends :peer_connection_error with the reason when webrtc fails" do
+      {:ok, specter} = Specter.init(ice_servers: ["turn:turn.example.com:3478"])
+      api = init_api(specter)
+
+      assert {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      assert_receive {:peer_connection_error, ^pc, reason}
+      assert is_binary(reason)
+      assert reason != ""
+      refute Specter.PeerConnection.exists?(specter, pc)
+    end
+
+    test "uses the command_buffer_size from the config" do
+      {:ok, specter} = Specter.init(command_buffer_size: 8)
+      api = init_api(specter)
+      pc = init_peer_connection(specter, api)
+
+      assert {:ok, %{size: 8, available: 8}} = Specter.PeerConnection.command_buffer(specter, pc)
+    end
+
+    test "honors a custom command_buffer_size", %{specter: specter, api: api} do
+      assert {:ok, pc} = Specter.PeerConnection.new(specter, api, command_buffer_size: 4)
+      assert_receive {:peer_connection_ready, ^pc}
+
+      assert {:ok, %{size: 4, available: 4}} = Specter.PeerConnection.command_buffer(specter, pc)
+    end
+
+    test "refuses commands with {:error, :busy} once the command buffer is full", %{
+      specter: specter,
+      api: api
+    } do
+      assert {:ok, pc} = Specter.PeerConnection.new(specter, api, command_buffer_size: 1)
+      assert_receive {:peer_connection_ready, ^pc}
+
+      results = for _ <- 1..100, do: Specter.PeerConnection.connection_state(specter, pc)
+      accepted = Enum.count(results, &(&1 == :ok))
+
+      assert {:error, :busy} in results
+      assert Enum.all?(results, &(&1 in [:ok, {:error, :busy}]))
+
+      for _ <- 1..accepted, do: assert_receive({:connection_state, ^pc, _state})
+      refute_received {:connection_state, ^pc, _state}
+    end
+
+    test "refuses tracks with {:error, :busy} once the command buffer is full", %{
+      specter: specter,
+      api: api
+    } do
+      assert {:ok, pc} = Specter.PeerConnection.new(specter, api, command_buffer_size: 1)
+      assert_receive {:peer_connection_ready, ^pc}
+
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+
+      results = for _ <- 1..100, do: Specter.PeerConnection.add_track(specter, pc, track)
+
+      assert {:error, :busy} in results
+      assert Enum.all?(results, &(match?({:ok, _sender}, &1) or &1 == {:error, :busy}))
+    end
+
+    test "returns {:error, :invalid_command_buffer_size} for a size of zero", %{
+      specter: specter,
+      api: api
+    } do
+      assert {:error, :invalid_command_buffer_size} =
+               Specter.PeerConnection.new(specter, api, command_buffer_size: 0)
+    end
+
+    test "offers the fingerprint of the configured DTLS certificate" do
+      cert_pem = File.read!("test/fixtures/dtls/cert.pem")
+      key_pem = File.read!("test/fixtures/dtls/key.pem")
+      {:ok, specter} = Specter.init(certificate: cert_pem, private_key: key_pem)
+
+      pc = init_peer_connection(specter, init_api(specter))
+      assert :ok = Specter.PeerConnection.create_data_channel(specter, pc, "foo")
+      assert_receive {:data_channel_created, ^pc}
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+
+      [{:Certificate, der, _}] = :public_key.pem_decode(cert_pem)
+
+      fingerprint =
+        :crypto.hash(:sha256, der)
+        |> Base.encode16()
+        |> String.graphemes()
+        |> Enum.chunk_every(2)
+        |> Enum.map_join(":", &Enum.join/1)
+
+      assert %{"sdp" => sdp} = Jason.decode!(offer)
+      assert sdp =~ "a=fingerprint:sha-256 #{fingerprint}"
+    end
+  end
+
+  describe "pending_local_description" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns an error when peer connection does not exist", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.pending_local_description(specter, UUID.uuid4())
+    end
+
+    test "sends the pending local description back to elixir before ICE finishes", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.pending_local_description(specter, pc)
+      assert_receive {:pending_local_description, ^pc, nil}
+
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+
+      assert :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      assert_receive {:ok, ^pc, :set_local_description}
+
+      assert :ok = Specter.PeerConnection.pending_local_description(specter, pc)
+      assert_receive {:pending_local_description, ^pc, ^offer}
+    end
+  end
+
+  describe "pending_remote_description" do
+    setup [
+      :initialize_specter,
+      :init_api,
+      :init_peer_connection,
+      :create_data_channel,
+      :create_offer
+    ]
+
+    test "returns an error when peer connection does not exist", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.pending_remote_description(specter, UUID.uuid4())
     end
 
     test "sends the pending remote description back to elixir before ICE finishes", %{
@@ -463,6 +1652,151 @@ defmodule Specter.PeerConnectionTest do
     end
   end
 
+  describe "record_to_file" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.record_to_file(specter, UUID.uuid4(), "video", "video.h264")
+    end
+
+    @tag :tmp_dir
+    test "returns an error when the file cannot be created", %{
+      specter: specter,
+      peer_connection: pc,
+      tmp_dir: tmp_dir
+    } do
+      path = Path.join([tmp_dir, "missing", "video.h264"])
+
+      assert {:error, reason} = Specter.PeerConnection.record_to_file(specter, pc, "video", path)
+      assert is_binary(reason)
+    end
+
+    @tag :tmp_dir
+    test "writes the remote track to the file until it ends", %{
+      specter: specter,
+      peer_connection: pc_offer,
+      tmp_dir: tmp_dir
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+      path = Path.join(tmp_dir, "video.h264")
+
+      codec = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, _sender}
+
+      assert :ok = Specter.PeerConnection.record_to_file(specter, pc_answer, "video", path)
+      assert_receive {:ok, ^pc_answer, :record_to_file}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      assert :ok =
+               Specter.TrackLocalStaticSample.play_from_file_h264(
+                 specter,
+                 track,
+                 "examples/play_from_file_h264/sample_video.h264",
+                 fps: 240
+               )
+
+      assert_receive {:playback_finished, ^track}, 5_000
+
+      assert :ok = Specter.PeerConnection.close_sync(specter, pc_answer)
+      assert_receive {:recording_finished, ^pc_answer, "video"}, 5_000
+      assert_receive {:track_ended, ^pc_answer, "video"}
+
+      assert <<0, 0, 0, 1, _rest::binary>> = File.read!(path)
+    end
+
+    @tag :tmp_dir
+    test "writes a VP8 track as IVF", %{
+      specter: specter,
+      peer_connection: pc_offer,
+      tmp_dir: tmp_dir
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+      path = Path.join(tmp_dir, "video.ivf")
+
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, _sender}
+
+      assert :ok = Specter.PeerConnection.record_to_file(specter, pc_answer, "video", path)
+      assert_receive {:ok, ^pc_answer, :record_to_file}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      # A VP8 frame whose first bit is clear is a keyframe.
+      for _ <- 1..30 do
+        :ok = Specter.TrackLocalStaticSample.write_sample(specter, track, <<0x10, 0, 0>>, 33)
+        Process.sleep(33)
+      end
+
+      assert :ok = Specter.PeerConnection.close_sync(specter, pc_answer)
+      assert_receive {:recording_finished, ^pc_answer, "video"}, 5_000
+
+      assert <<"DKIF", _header::binary-size(28), frames::binary>> = File.read!(path)
+      assert byte_size(frames) > 0
+    end
+
+    @tag :tmp_dir
+    test "writes an Opus track as Ogg", %{
+      specter: specter,
+      peer_connection: pc_offer,
+      tmp_dir: tmp_dir
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+      path = Path.join(tmp_dir, "audio.ogg")
+
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, _sender}
+
+      assert :ok = Specter.PeerConnection.record_to_file(specter, pc_answer, "audio", path)
+      assert_receive {:ok, ^pc_answer, :record_to_file}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      for _ <- 1..50 do
+        :ok = Specter.TrackLocalStaticSample.write_sample(specter, track, <<0xF8, 0, 0>>, 20)
+        Process.sleep(20)
+      end
+
+      assert :ok = Specter.PeerConnection.close_sync(specter, pc_answer)
+      assert_receive {:recording_finished, ^pc_answer, "audio"}, 5_000
+      assert_receive {:track_ended, ^pc_answer, "audio"}
+
+      assert <<"OggS", _rest::binary>> = File.read!(path)
+    end
+
+    @tag :tmp_dir
+    test "sends an error for a codec that cannot be recorded", %{
+      specter: specter,
+      peer_connection: pc_offer,
+      tmp_dir: tmp_dir
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+      path = Path.join(tmp_dir, "audio")
+
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/PCMU"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, _sender}
+
+      assert :ok = Specter.PeerConnection.record_to_file(specter, pc_answer, "audio", path)
+      assert_receive {:ok, ^pc_answer, :record_to_file}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      Moar.Retry.rescue_for!(5_000, fn ->
+        :ok = Specter.TrackLocalStaticSample.write_sample(specter, track, <<0, 1, 2>>, 20)
+        assert_receive {:recording_error, ^pc_answer, "audio", "unsupported codec: " <> _}, 100
+      end)
+    end
+  end
+
   describe "remote_description" do
     setup [
       :initialize_specter,
@@ -472,25 +1806,387 @@ defmodule Specter.PeerConnectionTest do
       :create_offer
     ]
 
-    test "returns an error when peer connection does not exist", %{specter: specter} do
-      assert {:error, :not_found} =
-               Specter.PeerConnection.remote_description(specter, UUID.uuid4())
+    test "returns an error when peer connection does not exist", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.remote_description(specter, UUID.uuid4())
+    end
+
+    test "sends the pending remote description back to elixir before ICE finishes", %{
+      specter: specter,
+      offer: offer
+    } do
+      api = init_api(specter)
+      pc = init_peer_connection(specter, api)
+      assert :ok = Specter.PeerConnection.remote_description(specter, pc)
+      assert_receive {:remote_description, ^pc, nil}
+
+      assert :ok = Specter.PeerConnection.set_remote_description(specter, pc, offer)
+      assert_receive {:ok, ^pc, :set_remote_description}
+
+      assert :ok = Specter.PeerConnection.remote_description(specter, pc)
+      assert_receive {:remote_description, ^pc, ^offer}
+    end
+  end
+
+  describe "replace_track" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    setup %{specter: specter, peer_connection: pc} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, camera} = Specter.TrackLocalStaticSample.new(specter, codec, "camera", "specter")
+      {:ok, screen} = Specter.TrackLocalStaticSample.new(specter, codec, "screen", "specter")
+      {:ok, sender} = Specter.PeerConnection.add_track(specter, pc, camera)
+      assert_receive {:rtp_sender, ^pc, ^camera, ^sender}
+
+      [sender: sender, screen: screen]
+    end
+
+    test "returns {:error, :not_found} when given a random id", %{
+      specter: specter,
+      sender: sender,
+      screen: screen
+    } do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.replace_track(specter, UUID.uuid4(), sender, screen)
+    end
+
+    test "returns {:error, :not_found} for an unknown sender", %{
+      specter: specter,
+      peer_connection: pc,
+      screen: screen
+    } do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.replace_track(specter, pc, UUID.uuid4(), screen)
+    end
+
+    test "returns {:error, :invalid_track} for an unknown track", %{
+      specter: specter,
+      peer_connection: pc,
+      sender: sender
+    } do
+      assert {:error, :invalid_track} =
+               Specter.PeerConnection.replace_track(specter, pc, sender, UUID.uuid4())
+    end
+
+    test "returns {:error, :incompatible_track} for a track of another kind", %{
+      specter: specter,
+      peer_connection: pc,
+      sender: sender
+    } do
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, audio} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+
+      assert {:error, :incompatible_track} =
+               Specter.PeerConnection.replace_track(specter, pc, sender, audio)
+    end
+
+    test "replaces the track of the sender", %{
+      specter: specter,
+      peer_connection: pc,
+      sender: sender,
+      screen: screen
+    } do
+      assert :ok = Specter.PeerConnection.replace_track(specter, pc, sender, screen)
+      assert Specter.PeerConnection.sender_active?(specter, pc, sender)
+    end
+
+    test "detaches the track of the sender when given nil", %{
+      specter: specter,
+      peer_connection: pc,
+      sender: sender
+    } do
+      assert :ok = Specter.PeerConnection.replace_track(specter, pc, sender, nil)
+      refute Specter.PeerConnection.sender_active?(specter, pc, sender)
+    end
+  end
+
+  describe "request_keyframe" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.request_keyframe(specter, UUID.uuid4(), "video")
+    end
+
+    test "sends an error when the remote track does not exist", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.request_keyframe(specter, pc, "video")
+      assert_receive {:keyframe_error, ^pc, :not_found}
+    end
+  end
+
+  describe "restart_ice" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} = Specter.PeerConnection.restart_ice(specter, UUID.uuid4())
+    end
+
+    test "has no effect before a local description has been set", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.restart_ice(specter, pc)
+      assert_receive {:ok, ^pc, :restart_ice}
+
+      assert {:ok, _offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+    end
+
+    test "the next offer has new ICE credentials", %{specter: specter, peer_connection: pc} do
+      remote_pc = init_peer_connection(specter, init_api(specter))
+      :ok = create_data_channel(specter, pc)
+      :ok = negotiate_connection(specter, pc, remote_pc)
+
+      ice_ufrag = fn offer ->
+        %{"sdp" => sdp} = Jason.decode!(offer)
+        [_, ufrag] = Regex.run(~r/a=ice-ufrag:(\S+)/, sdp)
+        ufrag
+      end
+
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      ufrag = ice_ufrag.(offer)
+
+      assert :ok = Specter.PeerConnection.restart_ice(specter, pc)
+      assert_receive {:ok, ^pc, :restart_ice}
+
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      restarted_ufrag = ice_ufrag.(offer)
+      refute restarted_ufrag == ufrag
+
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      assert ice_ufrag.(offer) == restarted_ufrag
+    end
+  end
+
+  describe "sctp_transport" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} = Specter.PeerConnection.sctp_transport(specter, UUID.uuid4())
+    end
+
+    test "returns the limits of the transport before it connects", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert {:ok, %{state: :connecting, max_channels: 65_535, max_message_size: 65_536}} =
+               Specter.PeerConnection.sctp_transport(specter, pc)
+    end
+
+    test "lowers the largest message to the one accepted by the remote description", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      sdp = """
+      v=0
+      o=- 2927307686215094172 2 IN IP4 127.0.0.1
+      s=-
+      t=0 0
+      a=msid-semantic: WMS
+      a=ice-ufrag:ZZZZ
+      a=ice-pwd:AU/SQPupllyS0SDG/eRWDCfA
+      a=fingerprint:sha-256 B7:D5:86:B0:92:C6:A6:03:80:C8:59:47:25:EC:FF:3F:57:F5:97:EF:76:B9:AA:14:B7:8C:C9:B3:4D:CA:1B:0A
+      a=max-message-size:16384
+      """
+
+      assert :ok = Specter.PeerConnection.set_remote_sdp(specter, pc, :offer, sdp)
+      assert_receive {:ok, ^pc, :set_remote_description}
+
+      assert {:ok, %{max_message_size: 16_384}} =
+               Specter.PeerConnection.sctp_transport(specter, pc)
+    end
+
+    test "is connected once a data channel has been negotiated", %{
+      specter: specter,
+      peer_connection: pc_offer
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+      assert :ok = create_data_channel(specter, pc_offer)
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      Moar.Retry.rescue_for!(5_000, fn ->
+        assert {:ok, %{state: :connected}} =
+                 Specter.PeerConnection.sctp_transport(specter, pc_offer)
+      end)
+    end
+  end
+
+  describe "sender_active?" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "raises when the peer connection does not exist", %{specter: specter} do
+      assert_raise RuntimeError, ~r/not_found/, fn ->
+        Specter.PeerConnection.sender_active?(specter, UUID.uuid4(), UUID.uuid4())
+      end
+    end
+
+    test "raises when the sender does not exist", %{specter: specter, peer_connection: pc} do
+      assert_raise RuntimeError, ~r/not_found/, fn ->
+        Specter.PeerConnection.sender_active?(specter, pc, UUID.uuid4())
+      end
+    end
+  end
+
+  describe "sender_stats" do
+    setup [:initialize_specter, :init_api]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.sender_stats(specter, UUID.uuid4(), UUID.uuid4())
+    end
+
+    test "sends nil stats for a sender that does not exist", %{specter: specter, api: api} do
+      pc = init_peer_connection(specter, api)
+      sender = UUID.uuid4()
+
+      assert :ok = Specter.PeerConnection.sender_stats(specter, pc, sender)
+      assert_receive {:sender_stats, ^pc, ^sender, nil}
+    end
+
+    test "counts the bytes and packets sent once connected", %{specter: specter, api: api} do
+      pc_offer = init_peer_connection(specter, api)
+      pc_answer = init_peer_connection(specter, api)
+
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, sender}
+
+      assert :ok = Specter.PeerConnection.sender_stats(specter, pc_offer, sender)
+      assert_receive {:sender_stats, ^pc_offer, ^sender, %{bytes_sent: 0, packets_sent: 0}}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      Moar.Retry.rescue_for!(5_000, fn ->
+        :ok = Specter.TrackLocalStaticSample.write_sample(specter, track, <<0, 1, 2>>, 20)
+        :ok = Specter.PeerConnection.sender_stats(specter, pc_offer, sender)
+
+        assert_receive {:sender_stats, ^pc_offer, ^sender,
+                        %{bytes_sent: bytes_sent, packets_sent: packets_sent}}
+
+        assert bytes_sent > 0
+        assert packets_sent > 0
+      end)
+    end
+  end
+
+  describe "set_codec_preferences" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    setup %{specter: specter, peer_connection: pc} do
+      :ok = Specter.PeerConnection.add_transceiver(specter, pc, :video, :recvonly)
+      assert_receive {:rtp_transceiver, ^pc, _transceiver}
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      assert_receive {:ok, ^pc, :set_local_description}
+      :ok
+    end
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      h264 = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+
+      assert {:error, :not_found} =
+               Specter.PeerConnection.set_codec_preferences(specter, UUID.uuid4(), "0", [h264])
+    end
+
+    test "returns {:error, :not_found} for an unknown mid", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      h264 = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+
+      assert {:error, :not_found} =
+               Specter.PeerConnection.set_codec_preferences(specter, pc, "7", [h264])
+    end
+
+    test "returns {:error, :unsupported_codec} for codecs missing from the media engine", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      h264 = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+      h265 = %Specter.RtpCodecCapability{mime_type: "video/H265"}
+      opus = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+
+      assert {:error, :unsupported_codec} =
+               Specter.PeerConnection.set_codec_preferences(specter, pc, "0", [h264, h265])
+
+      assert {:error, :unsupported_codec} =
+               Specter.PeerConnection.set_codec_preferences(specter, pc, "0", [opus])
+    end
+
+    test "returns {:error, :unknown_mime_type} for codecs unknown to webrtc.rs", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      h264 = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+      bogus = %Specter.RtpCodecCapability{mime_type: "video/bogus"}
+
+      assert {:error, :unknown_mime_type} =
+               Specter.PeerConnection.set_codec_preferences(specter, pc, "0", [h264, bogus])
+    end
+
+    test "matches mime types regardless of case", %{specter: specter, peer_connection: pc} do
+      h264 = %Specter.RtpCodecCapability{mime_type: "video/h264"}
+      assert :ok = Specter.PeerConnection.set_codec_preferences(specter, pc, "0", [h264])
+
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      %{"sdp" => sdp} = Jason.decode!(offer)
+
+      assert sdp =~ "H264/90000"
+      refute sdp =~ "VP8/90000"
+    end
+
+    test "limits the codecs offered for the transceiver", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      h264 = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+      assert :ok = Specter.PeerConnection.set_codec_preferences(specter, pc, "0", [h264])
+
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      %{"sdp" => sdp} = Jason.decode!(offer)
+
+      assert sdp =~ "H264/90000"
+      refute sdp =~ "VP8/90000"
     end
+  end
 
-    test "sends the pending remote description back to elixir before ICE finishes", %{
+  describe "set_data_channel_buffered_amount_low_threshold" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{
       specter: specter,
-      offer: offer
+      peer_connection: pc
     } do
-      api = init_api(specter)
-      pc = init_peer_connection(specter, api)
-      assert :ok = Specter.PeerConnection.remote_description(specter, pc)
-      assert_receive {:remote_description, ^pc, nil}
+      assert {:error, :not_found} =
+               Specter.PeerConnection.set_data_channel_buffered_amount_low_threshold(
+                 specter,
+                 UUID.uuid4(),
+                 "dc",
+                 1024
+               )
 
-      assert :ok = Specter.PeerConnection.set_remote_description(specter, pc, offer)
-      assert_receive {:ok, ^pc, :set_remote_description}
+      assert {:error, :not_found} =
+               Specter.PeerConnection.set_data_channel_buffered_amount_low_threshold(
+                 specter,
+                 pc,
+                 UUID.uuid4(),
+                 1024
+               )
+    end
 
-      assert :ok = Specter.PeerConnection.remote_description(specter, pc)
-      assert_receive {:remote_description, ^pc, ^offer}
+    test "sets the threshold of a data channel", %{specter: specter, peer_connection: pc} do
+      {:ok, data_channel} = Specter.PeerConnection.create_data_channel_sync(specter, pc, "foo")
+
+      assert :ok =
+               Specter.PeerConnection.set_data_channel_buffered_amount_low_threshold(
+                 specter,
+                 pc,
+                 data_channel,
+                 1024
+               )
     end
   end
 
@@ -515,18 +2211,54 @@ defmodule Specter.PeerConnectionTest do
       assert_receive {:ok, ^pc, :set_local_description}
     end
 
-    test "sends :invalid_local_description when given an invalid session", %{
+    test "returns :invalid_sdp when given sdp that does not parse", %{
       specter: specter,
       peer_connection: pc
     } do
-      assert :ok =
+      assert {:error, {:invalid_sdp, error}} =
                Specter.PeerConnection.set_local_description(
                  specter,
                  pc,
                  ~S[{"type":"offer","sdp":"derp"}]
                )
 
-      assert_receive {:invalid_local_description, ^pc, "SdpInvalidSyntax: derp"}
+      assert %{line: 1, text: "derp", section: nil, reason: "SdpInvalidSyntax: derp"} = error
+      refute_receive {:invalid_local_description, ^pc, _reason}
+    end
+
+    test "points at the munged line of a media section", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      :ok = Specter.PeerConnection.add_transceiver(specter, pc, :audio, :recvonly)
+      assert_receive {:rtp_transceiver, ^pc, _transceiver}
+      :ok = Specter.PeerConnection.add_transceiver(specter, pc, :video, :recvonly)
+      assert_receive {:rtp_transceiver, ^pc, _transceiver}
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+
+      %{"sdp" => sdp} = Jason.decode!(offer)
+      lines = String.split(sdp, "\r\n")
+      video = Enum.find_index(lines, &String.starts_with?(&1, "m=video"))
+      munged = List.insert_at(lines, video + 1, "b=AS:notanumber")
+      offer = Jason.encode!(%{type: "offer", sdp: Enum.join(munged, "\r\n")})
+
+      assert {:error, {:invalid_sdp, error}} =
+               Specter.PeerConnection.set_local_description(specter, pc, offer)
+
+      line = video + 2
+      assert %{line: ^line, text: "b=AS:notanumber", section: 1} = error
+    end
+
+    test "sends :invalid_local_description when the sdp cannot be applied", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+      answer = String.replace(offer, ~S["type":"offer"], ~S["type":"answer"])
+
+      assert :ok = Specter.PeerConnection.set_local_description(specter, pc, answer)
+      assert_receive {:invalid_local_description, ^pc, _reason}
     end
   end
 
@@ -580,6 +2312,145 @@ defmodule Specter.PeerConnectionTest do
     end
   end
 
+
+  describe "set_remote_sdp" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "sets an offer given as raw SDP", %{specter: specter, peer_connection: pc} do
+      assert :ok = Specter.PeerConnection.set_remote_sdp(specter, pc, :offer, @valid_offer_sdp)
+      assert_receive {:ok, ^pc, :set_remote_description}
+    end
+
+    test "returns an error for an unknown SDP type", %{specter: specter, peer_connection: pc} do
+      assert {:error, :invalid_atom} =
+               Specter.PeerConnection.set_remote_sdp(specter, pc, :bogus, @valid_offer_sdp)
+    end
+
+    test "rolls back a remote offer", %{specter: specter, peer_connection: pc} do
+      assert :ok = Specter.PeerConnection.set_remote_sdp(specter, pc, :offer, @valid_offer_sdp)
+      assert_receive {:ok, ^pc, :set_remote_description}
+
+      assert :ok = Specter.PeerConnection.set_remote_sdp(specter, pc, :rollback, @valid_offer_sdp)
+      assert_receive {:ok, ^pc, :set_remote_description}
+
+      assert :ok = Specter.PeerConnection.signaling_state(specter, pc)
+      assert_receive {:signaling_state, ^pc, :stable}
+    end
+
+    test "is used by set_remote_description when given an SDP type", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      sdp = @valid_offer_sdp
+
+      assert :ok = Specter.PeerConnection.set_remote_description(specter, pc, sdp, :offer)
+      assert_receive {:ok, ^pc, :set_remote_description}
+
+      assert {:error, :invalid_atom} =
+               Specter.PeerConnection.set_remote_description(specter, pc, sdp, :bogus)
+    end
+
+    test "returns an error when SDP is invalid", %{specter: specter, peer_connection: pc} do
+      assert {:error, :invalid_session_description} =
+               Specter.PeerConnection.set_remote_sdp(specter, pc, :offer, "Hello world")
+    end
+
+    test "returns an error when peer connection does not exist", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.set_remote_sdp(
+                 specter,
+                 UUID.uuid4(),
+                 :offer,
+                 @valid_offer_sdp
+               )
+    end
+  end
+  describe "set_remote_description during glare" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    setup %{specter: specter, peer_connection: pc} do
+      :ok = create_data_channel(specter, pc)
+      [offer: offer] = create_offer(%{specter: specter, peer_connection: pc})
+      :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      assert_receive {:ok, ^pc, :set_local_description}
+
+      remote_pc = init_peer_connection(specter, init_api(specter))
+      :ok = create_data_channel(specter, remote_pc)
+      [offer: remote_offer] = create_offer(%{specter: specter, peer_connection: remote_pc})
+
+      [remote_offer: remote_offer]
+    end
+
+    test "a polite peer rolls back its offer and accepts the remote offer", %{
+      specter: specter,
+      peer_connection: pc,
+      remote_offer: remote_offer
+    } do
+      assert :ok =
+               Specter.PeerConnection.set_remote_description(specter, pc, remote_offer,
+                 polite: true
+               )
+
+      assert_receive {:glare, ^pc, :rolled_back}
+      assert_receive {:ok, ^pc, :set_remote_description}
+
+      assert :ok = Specter.PeerConnection.signaling_state(specter, pc)
+      assert_receive {:signaling_state, ^pc, :have_remote_offer}
+    end
+
+    test "an impolite peer ignores the remote offer", %{
+      specter: specter,
+      peer_connection: pc,
+      remote_offer: remote_offer
+    } do
+      assert :ok =
+               Specter.PeerConnection.set_remote_description(specter, pc, remote_offer,
+                 polite: false
+               )
+
+      assert_receive {:glare, ^pc, :ignored}
+      refute_receive {:ok, ^pc, :set_remote_description}
+
+      assert :ok = Specter.PeerConnection.signaling_state(specter, pc)
+      assert_receive {:signaling_state, ^pc, :have_local_offer}
+    end
+  end
+
+  describe "set_rtp_forwarding" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.set_rtp_forwarding(specter, UUID.uuid4(), "audio", true)
+    end
+
+    test "sends the packets of a remote track while enabled", %{
+      specter: specter,
+      peer_connection: pc_offer
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, _sender}
+
+      assert :ok = Specter.PeerConnection.set_rtp_forwarding(specter, pc_answer, "audio", true)
+      assert_receive {:ok, ^pc_answer, :set_rtp_forwarding}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      Moar.Retry.rescue_for!(5_000, fn ->
+        :ok = Specter.TrackLocalStaticSample.write_sample(specter, track, <<0, 1, 2>>, 20)
+        assert_receive {:rtp, ^pc_answer, "audio", packet}, 100
+        assert <<2::2, _rest::bitstring>> = packet
+      end)
+
+      assert :ok = Specter.PeerConnection.set_rtp_forwarding(specter, pc_answer, "audio", false)
+      assert_receive {:ok, ^pc_answer, :set_rtp_forwarding}
+    end
+  end
+
   describe "signaling_state" do
     setup [:initialize_specter, :init_api, :init_peer_connection]
 
@@ -591,4 +2462,289 @@ defmodule Specter.PeerConnectionTest do
       assert_receive {:signaling_state, ^peer_connection, :stable}
     end
   end
+
+  describe "snapshot" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} = Specter.PeerConnection.snapshot(specter, UUID.uuid4())
+    end
+
+    test "sends back the states of a new peer connection", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.snapshot(specter, pc)
+
+      assert_receive {:snapshot, ^pc,
+                      %{
+                        connection_state: :new,
+                        ice_connection_state: :new,
+                        ice_gathering_state: :new,
+                        signaling_state: :stable,
+                        senders: 0,
+                        receivers: 0,
+                        round_trip_time: nil
+                      }}
+    end
+
+    test "counts senders and receivers in use once connected", %{
+      specter: specter,
+      peer_connection: pc_offer
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, _sender}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+      assert_receive {:connection_state_change, ^pc_offer, :connected}, 5_000
+
+      assert :ok = Specter.PeerConnection.snapshot(specter, pc_offer)
+
+      assert_receive {:snapshot, ^pc_offer,
+                      %{connection_state: :connected, senders: 1, receivers: 0}}
+
+      Moar.Retry.rescue_for!(5_000, fn ->
+        :ok = Specter.TrackLocalStaticSample.write_sample(specter, track, <<0, 1, 2>>, 20)
+        assert :ok = Specter.PeerConnection.snapshot(specter, pc_answer)
+        assert_receive {:snapshot, ^pc_answer, %{senders: 0, receivers: 1}}, 100
+      end)
+    end
+  end
+
+  describe "start_stats_reporting" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.start_stats_reporting(specter, UUID.uuid4(), 100)
+    end
+
+    test "returns {:error, :invalid_interval} for an interval that is not positive", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert {:error, :invalid_interval} =
+               Specter.PeerConnection.start_stats_reporting(specter, pc, 0)
+
+      assert {:error, :invalid_interval} =
+               Specter.PeerConnection.start_stats_reporting(specter, pc, 1.5)
+    end
+
+    test "sends the stats every interval until stopped", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.start_stats_reporting(specter, pc, 50)
+      assert_receive {:ok, ^pc, :start_stats_reporting}
+
+      for _ <- 1..3 do
+        assert_receive {:stats, ^pc, json}, 500
+        assert {:ok, %{}} = Jason.decode(json)
+      end
+
+      assert :ok = Specter.PeerConnection.stop_stats_reporting(specter, pc)
+      assert_receive {:ok, ^pc, :stop_stats_reporting}
+      flush_stats(pc)
+
+      refute_receive {:stats, ^pc, _json}, 200
+    end
+
+    test "stops sending stats once the peer connection is closed", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.start_stats_reporting(specter, pc, 50)
+      assert_receive {:stats, ^pc, _json}, 500
+
+      assert :ok = Specter.PeerConnection.close_sync(specter, pc)
+      flush_stats(pc)
+
+      refute_receive {:stats, ^pc, _json}, 200
+    end
+  end
+
+  describe "stop_stats_reporting" do
+    setup [:initialize_specter, :init_api]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.stop_stats_reporting(specter, UUID.uuid4())
+    end
+  end
+
+  describe "track_ended" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "is sent once a remote track can no longer be read", %{
+      specter: specter,
+      peer_connection: pc_offer
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc_offer, track)
+      assert_receive {:rtp_sender, ^pc_offer, ^track, _sender}
+
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      # The remote track is only announced once its first packets arrive.
+      for _ <- 1..50 do
+        :ok = Specter.TrackLocalStaticSample.write_sample(specter, track, <<0, 1, 2>>, 20)
+        Process.sleep(20)
+      end
+
+      refute_received {:track_ended, ^pc_answer, _track_id}
+
+      assert :ok = Specter.PeerConnection.close_sync(specter, pc_answer)
+      assert_receive {:track_ended, ^pc_answer, "audio"}, 5_000
+    end
+  end
+
+  describe "transceiver_current_direction" do
+    setup [:initialize_specter, :init_api]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.transceiver_current_direction(specter, UUID.uuid4(), "0")
+    end
+
+    test "returns the direction negotiated with the remote peer", %{specter: specter, api: api} do
+      pc_offer = init_peer_connection(specter, api)
+      pc_answer = init_peer_connection(specter, api)
+
+      :ok = Specter.PeerConnection.add_transceiver(specter, pc_offer, :video, :sendrecv)
+      assert_receive {:rtp_transceiver, ^pc_offer, _transceiver}
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc_offer)
+      :ok = Specter.PeerConnection.set_local_description(specter, pc_offer, offer)
+      assert_receive {:ok, ^pc_offer, :set_local_description}
+
+      assert {:ok, nil} =
+               Specter.PeerConnection.transceiver_current_direction(specter, pc_offer, "0")
+
+      assert {:error, :not_found} =
+               Specter.PeerConnection.transceiver_current_direction(specter, pc_offer, "7")
+
+      :ok = Specter.PeerConnection.set_remote_description(specter, pc_answer, offer)
+      assert_receive {:ok, ^pc_answer, :set_remote_description}
+      {:ok, answer} = Specter.PeerConnection.create_answer_sync(specter, pc_answer)
+      :ok = Specter.PeerConnection.set_local_description(specter, pc_answer, answer)
+      assert_receive {:ok, ^pc_answer, :set_local_description}
+      :ok = Specter.PeerConnection.set_remote_description(specter, pc_offer, answer)
+      assert_receive {:ok, ^pc_offer, :set_remote_description}
+
+      # The answerer has no track to send, so it only receives.
+      assert {:ok, :sendonly} =
+               Specter.PeerConnection.transceiver_current_direction(specter, pc_offer, "0")
+    end
+  end
+
+  describe "transceiver_mid" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.transceiver_mid(specter, UUID.uuid4(), UUID.uuid4())
+    end
+
+    test "returns {:error, :not_found} for an unknown transceiver", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.transceiver_mid(specter, pc, UUID.uuid4())
+    end
+
+    test "returns the mid of each transceiver once a description is set", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      :ok = Specter.PeerConnection.add_transceiver(specter, pc, :audio, :recvonly)
+      assert_receive {:rtp_transceiver, ^pc, audio}
+      :ok = Specter.PeerConnection.add_transceiver(specter, pc, :video, :sendrecv)
+      assert_receive {:rtp_transceiver, ^pc, video}
+
+      assert {:ok, nil} = Specter.PeerConnection.transceiver_mid(specter, pc, video)
+
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      assert_receive {:ok, ^pc, :set_local_description}
+
+      assert {:ok, "0"} = Specter.PeerConnection.transceiver_mid(specter, pc, audio)
+      assert {:ok, "1"} = Specter.PeerConnection.transceiver_mid(specter, pc, video)
+      assert {:ok, nil} = Specter.PeerConnection.transceiver_current_direction(specter, pc, "1")
+    end
+  end
+
+  describe "write_rtcp" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.PeerConnection.write_rtcp(specter, UUID.uuid4(), {:pli, 1})
+    end
+
+    test "returns an error for packets that are not supported", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert {:error, :unsupported_rtcp_packet} =
+               Specter.PeerConnection.write_rtcp(specter, pc, {:nack, 1})
+
+      assert {:error, :unsupported_rtcp_packet} =
+               Specter.PeerConnection.write_rtcp(specter, pc, {:remb, 1_000, :all})
+
+      # A packet type of 210 is not defined.
+      assert {:error, :unsupported_rtcp_packet} =
+               Specter.PeerConnection.write_rtcp(specter, pc, <<0x80, 210, 0, 1, 0::32>>)
+    end
+
+    test "returns an error for binaries that are not RTCP", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert {:error, :invalid_rtcp_packet} =
+               Specter.PeerConnection.write_rtcp(specter, pc, <<1, 2, 3>>)
+    end
+
+    test "sends an error before the peer connection has connected", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      assert :ok = Specter.PeerConnection.write_rtcp(specter, pc, {:pli, 1})
+      assert_receive {:rtcp_error, ^pc, :not_connected}
+    end
+
+    test "writes each kind of packet once connected", %{
+      specter: specter,
+      peer_connection: pc_offer
+    } do
+      pc_answer = init_peer_connection(specter, init_api(specter))
+      assert :ok = create_data_channel(specter, pc_offer)
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+      assert_receive {:connection_state_change, ^pc_offer, :connected}, 5_000
+
+      # A PLI from sender SSRC 0 for media SSRC 1.
+      pli = <<0x81, 206, 0, 2, 0::32, 1::32>>
+
+      for packet <- [{:pli, 1}, {:fir, 1}, {:remb, 500_000, [1]}, pli] do
+        assert :ok = Specter.PeerConnection.write_rtcp(specter, pc_offer, packet)
+        assert_receive {:ok, ^pc_offer, :write_rtcp}
+      end
+    end
+  end
+
+  # Drops stats that were sent before reporting stopped, so that only later ones are
+  # refuted.
+  defp flush_stats(pc) do
+    receive do
+      {:stats, ^pc, _json} -> flush_stats(pc)
+    after
+      50 -> :ok
+    end
+  end
 end
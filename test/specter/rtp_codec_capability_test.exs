@@ -0,0 +1,4 @@
+defmodule Specter.RtpCodecCapabilityTest do
+  use SpecterTest.Case
+  doctest Specter.RtpCodecCapability
+end
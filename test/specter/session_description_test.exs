@@ -0,0 +1,4 @@
+defmodule Specter.SessionDescriptionTest do
+  use SpecterTest.Case
+  doctest Specter.SessionDescription
+end
@@ -0,0 +1,31 @@
+defmodule Specter.TrackLocalStaticRTPTest do
+  use SpecterTest.Case
+  doctest Specter.TrackLocalStaticRTP
+
+  describe "write_rtp" do
+    setup [:initialize_specter]
+
+    test "returns an error when the packet cannot be parsed", %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticRTP.new(specter, codec, "video", "specter")
+
+      assert {:error, reason} = Specter.TrackLocalStaticRTP.write_rtp(specter, track, <<0x80>>)
+      assert is_binary(reason)
+    end
+  end
+
+  describe "add_track" do
+    setup [:initialize_specter, :init_api, :init_peer_connection]
+
+    test "adds a TrackLocalStaticRTP to the peer connection", %{
+      specter: specter,
+      peer_connection: pc
+    } do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticRTP.new(specter, codec, "video", "specter")
+
+      assert {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      assert_receive {:rtp_sender, ^pc, ^track, _rtp_sender}
+    end
+  end
+end
@@ -1,4 +1,231 @@
 defmodule Specter.TrackLocalStaticSampleTest do
   use SpecterTest.Case
   doctest Specter.TrackLocalStaticSample
+
+  @h264_file "examples/play_from_file_h264/sample_video.h264"
+  @ogg_file "test/fixtures/media/sample_audio.ogg"
+
+  describe "new" do
+    setup [:initialize_specter]
+
+    test "returns :invalid_codec for a codec missing its mime type", %{specter: specter} do
+      codec = %{clock_rate: 90_000, channels: 0, sdp_fmtp_line: ""}
+
+      assert {:error, :invalid_codec} =
+               Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+
+      assert {:error, :invalid_codec} =
+               Specter.TrackLocalStaticRTP.new(specter, codec, "video", "specter")
+
+      assert {:ok, []} = Specter.list_tracks(specter)
+    end
+
+    test "returns :invalid_track for an id that is not a string", %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+
+      assert {:error, :invalid_track} =
+               Specter.TrackLocalStaticSample.new(specter, codec, :video, "specter")
+    end
+
+    test "returns :unknown_mime_type for a mime type webrtc.rs does not know", %{
+      specter: specter
+    } do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/h.264", clock_rate: 90_000}
+
+      assert {:error, :unknown_mime_type} =
+               Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+
+      assert {:error, :unknown_mime_type} =
+               Specter.TrackLocalStaticRTP.new(specter, codec, "video", "specter")
+
+      assert {:ok, []} = Specter.list_tracks(specter)
+    end
+  end
+
+  describe "play_audio_from_file" do
+    setup [:initialize_specter]
+
+    test "sends playback_finished once every page has been written", %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "audio/opus"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "audio", "specter")
+
+      assert :ok = Specter.TrackLocalStaticSample.play_audio_from_file(specter, track, @ogg_file)
+      assert_receive {:playback_finished, ^track}, 1_000
+      refute_received {:playback_error, ^track, _reason}
+    end
+
+    test "returns {:error, :invalid_codec} for a track that is not Opus", %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+
+      assert {:error, :invalid_codec} =
+               Specter.TrackLocalStaticSample.play_audio_from_file(specter, track, @ogg_file)
+
+      refute_receive {:playback_finished, ^track}
+    end
+  end
+
+  describe "play_from_file_h264" do
+    setup [:initialize_specter]
+
+    setup %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      [track: track]
+    end
+
+    test "plays the file to the end at the configured frame rate", %{
+      specter: specter,
+      track: track
+    } do
+      # The sample file holds roughly 8 seconds of video at the default 30 fps. Sample
+      # durations are unit tested in track.rs.
+      assert :ok =
+               Specter.TrackLocalStaticSample.play_from_file_h264(specter, track, @h264_file,
+                 fps: 240
+               )
+
+      assert_receive {:playback_finished, ^track}, 5_000
+    end
+
+    test "returns an error when fps is zero", %{specter: specter, track: track} do
+      assert {:error, :invalid_fps} =
+               Specter.TrackLocalStaticSample.play_from_file_h264(specter, track, @h264_file,
+                 fps: 0
+               )
+    end
+
+    test "returns an error when the track does not exist", %{specter: specter} do
+      assert {:error, :invalid_track} =
+               Specter.TrackLocalStaticSample.play_from_file_h264(
+                 specter,
+                 UUID.uuid4(),
+                 @h264_file
+               )
+    end
+  end
+
+  describe "delete_track" do
+    setup [:initialize_specter]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} = Specter.delete_track(specter, UUID.uuid4())
+    end
+
+    test "deletes tracks of every type", %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, sample} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      {:ok, rtp} = Specter.TrackLocalStaticRTP.new(specter, codec, "video", "specter")
+
+      assert :ok = Specter.delete_track(specter, sample)
+      assert {:ok, [^rtp]} = Specter.list_tracks(specter)
+
+      assert :ok = Specter.delete_track(specter, rtp)
+      assert {:ok, []} = Specter.list_tracks(specter)
+    end
+
+    test "stops an in-progress playback", %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+
+      assert :ok = Specter.TrackLocalStaticSample.play_from_file_h264(specter, track, @h264_file)
+      assert :ok = Specter.delete_track(specter, track)
+      assert_receive {:playback_finished, ^track}, 500
+
+      assert {:error, :invalid_track} =
+               Specter.TrackLocalStaticSample.play_from_file_h264(specter, track, @h264_file)
+    end
+
+    test "leaves a track added to a peer connection on its sender", %{specter: specter} do
+      api = init_api(specter)
+      pc = init_peer_connection(specter, api)
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      {:ok, _sender} = Specter.PeerConnection.add_track(specter, pc, track)
+      assert_receive {:rtp_sender, ^pc, ^track, _sender}
+
+      assert :ok = Specter.delete_track(specter, track)
+
+      assert :ok = Specter.PeerConnection.get_senders(specter, pc)
+      assert_receive {:senders, ^pc, [_sender]}
+    end
+  end
+
+  describe "play_vp8_from_file" do
+    setup [:initialize_specter]
+
+    test "sends a playback error when the file cannot be read", %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+
+      assert :ok = Specter.TrackLocalStaticSample.play_vp8_from_file(specter, track, @h264_file)
+      assert_receive {:playback_error, ^track, _reason}
+      refute_receive {:playback_finished, ^track}
+    end
+
+    @tag :tmp_dir
+    test "plays a file whose timebase is shorter than a millisecond", %{
+      specter: specter,
+      tmp_dir: tmp_dir
+    } do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      path = Path.join(tmp_dir, "video.ivf")
+      File.write!(path, ivf(1, 90_000, 3))
+
+      assert :ok = Specter.TrackLocalStaticSample.play_vp8_from_file(specter, track, path)
+      assert_receive {:playback_finished, ^track}, 1_000
+      refute_received {:playback_error, ^track, _reason}
+    end
+
+    @tag :tmp_dir
+    test "sends a playback error for a timebase with a zero denominator", %{
+      specter: specter,
+      tmp_dir: tmp_dir
+    } do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      path = Path.join(tmp_dir, "video.ivf")
+      File.write!(path, ivf(1, 0, 3))
+
+      assert :ok = Specter.TrackLocalStaticSample.play_vp8_from_file(specter, track, path)
+      assert_receive {:playback_error, ^track, "invalid IVF timebase: 1/0"}
+      refute_receive {:playback_finished, ^track}
+    end
+  end
+
+  describe "stop_playback" do
+    setup [:initialize_specter]
+
+    test "stops an in-progress playback", %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/H264"}
+      {:ok, track} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+
+      assert :ok = Specter.TrackLocalStaticSample.play_from_file_h264(specter, track, @h264_file)
+      refute_receive {:playback_finished, ^track}, 100
+
+      assert :ok = Specter.TrackLocalStaticSample.stop_playback(specter, track)
+      assert_receive {:playback_finished, ^track}, 500
+    end
+
+    test "returns an error when nothing is playing", %{specter: specter} do
+      assert {:error, :not_found} =
+               Specter.TrackLocalStaticSample.stop_playback(specter, UUID.uuid4())
+    end
+
+    test "returns an error when the track is not a uuid", %{specter: specter} do
+      assert {:error, :invalid_track} = Specter.TrackLocalStaticSample.stop_playback(specter, 1)
+    end
+  end
+
+  # An IVF file holding `frames` small frames, with the given timebase in its header.
+  defp ivf(numerator, denominator, frames) do
+    header =
+      <<"DKIF", 0::little-16, 32::little-16, "VP80", 640::little-16, 480::little-16,
+        denominator::little-32, numerator::little-32, frames::little-32, 0::little-32>>
+
+    Enum.reduce(0..(frames - 1), header, fn index, acc ->
+      acc <> <<3::little-32, index::little-64, 0x10, 0x02, 0x00>>
+    end)
+  end
 end
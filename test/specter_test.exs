@@ -16,6 +16,294 @@ defmodule SpecterTest do
 
       assert is_reference(ref)
     end
+
+    test "initializes with a DTLS certificate" do
+      assert {:ok, %Specter{} = specter} =
+               Specter.init(
+                 certificate: File.read!("test/fixtures/dtls/cert.pem"),
+                 private_key: File.read!("test/fixtures/dtls/key.pem")
+               )
+
+      assert {:ok, %Specter.Config{certificate: "-----BEGIN CERTIFICATE-----" <> _}} =
+               Specter.config(specter)
+    end
+
+    test "returns {:error, :invalid_certificate} for a malformed certificate" do
+      assert {:error, :invalid_certificate} =
+               Specter.init(
+                 certificate: "not a certificate",
+                 private_key: File.read!("test/fixtures/dtls/key.pem")
+               )
+    end
+
+    test "returns {:error, :invalid_private_key} for a malformed private key" do
+      assert {:error, :invalid_private_key} =
+               Specter.init(
+                 certificate: File.read!("test/fixtures/dtls/cert.pem"),
+                 private_key: "not a key"
+               )
+    end
+
+    test "returns {:error, :invalid_private_key} when only a certificate is given" do
+      assert {:error, :invalid_private_key} =
+               Specter.init(certificate: File.read!("test/fixtures/dtls/cert.pem"))
+    end
+
+    test "initializes with a command_buffer_size" do
+      assert {:ok, specter} = Specter.init(command_buffer_size: 10)
+      assert {:ok, %Specter.Config{command_buffer_size: 10}} = Specter.config(specter)
+    end
+
+    test "initializes with an ice_candidate_pool_size" do
+      assert {:ok, specter} = Specter.init(ice_candidate_pool_size: 4)
+      assert {:ok, %Specter.Config{ice_candidate_pool_size: 4}} = Specter.config(specter)
+
+      pc = init_peer_connection(specter, init_api(specter))
+
+      assert {:ok, %{ice_candidate_pool_size: 4}} =
+               Specter.PeerConnection.get_configuration(specter, pc)
+    end
+
+    test "returns {:error, :invalid_configuration} for an invalid ice_candidate_pool_size" do
+      assert {:error, :invalid_configuration} = Specter.init(ice_candidate_pool_size: -1)
+      assert {:error, :invalid_configuration} = Specter.init(ice_candidate_pool_size: 256)
+      assert {:error, :invalid_configuration} = Specter.init(ice_candidate_pool_size: "4")
+    end
+
+    test "initializes with a udp_port_range" do
+      assert {:ok, specter} = Specter.init(udp_port_range: {50_000, 50_100})
+      assert {:ok, %Specter.Config{udp_port_range: {50_000, 50_100}}} = Specter.config(specter)
+    end
+
+    test "gathers host candidates within the udp_port_range" do
+      assert {:ok, specter} = Specter.init(ice_servers: [], udp_port_range: {50_000, 50_100})
+      pc = init_peer_connection(specter, init_api(specter))
+      :ok = create_data_channel(specter, pc)
+
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      assert :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      assert_receive {:ok, ^pc, :set_local_description}
+
+      assert :ok = Specter.PeerConnection.gathered_local_description(specter, pc)
+      assert_receive {:local_description, ^pc, description}, 5_000
+      assert {:ok, %{"sdp" => sdp}} = Jason.decode(description)
+
+      ports =
+        ~r/a=candidate:\S+ \d+ udp \d+ \S+ (\d+) typ host/
+        |> Regex.scan(sdp, capture: :all_but_first)
+        |> Enum.map(fn [port] -> String.to_integer(port) end)
+
+      assert ports != []
+      assert Enum.all?(ports, &(&1 in 50_000..50_100))
+    end
+
+    test "returns {:error, :invalid_udp_port_range} for an empty or open range" do
+      assert {:error, :invalid_udp_port_range} = Specter.init(udp_port_range: {50_100, 50_000})
+      assert {:error, :invalid_udp_port_range} = Specter.init(udp_port_range: {0, 50_000})
+      assert {:error, :invalid_configuration} = Specter.init(udp_port_range: {1, 70_000})
+    end
+
+    test "initializes with an ice_multicast_dns_mode" do
+      assert {:ok, specter} = Specter.init()
+      assert {:ok, %Specter.Config{ice_multicast_dns_mode: :query_only}} = Specter.config(specter)
+
+      assert {:ok, specter} = Specter.init(ice_multicast_dns_mode: :disabled)
+      assert {:ok, %Specter.Config{ice_multicast_dns_mode: :disabled}} = Specter.config(specter)
+    end
+
+    test "gathers mDNS host candidates with ice_multicast_dns_mode: :query_and_gather" do
+      assert {:ok, specter} =
+               Specter.init(ice_servers: [], ice_multicast_dns_mode: :query_and_gather)
+
+      pc = init_peer_connection(specter, init_api(specter))
+      :ok = create_data_channel(specter, pc)
+
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      assert :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      assert_receive {:ok, ^pc, :set_local_description}
+
+      assert :ok = Specter.PeerConnection.gathered_local_description(specter, pc)
+      assert_receive {:local_description, ^pc, description}, 5_000
+      assert {:ok, %{"sdp" => sdp}} = Jason.decode(description)
+
+      addresses =
+        ~r/a=candidate:\S+ \d+ udp \d+ (\S+) \d+ typ host/
+        |> Regex.scan(sdp, capture: :all_but_first)
+        |> List.flatten()
+
+      assert addresses != []
+      assert Enum.all?(addresses, &String.ends_with?(&1, ".local"))
+    end
+
+    test "returns {:error, :invalid_configuration} for an unknown ice_multicast_dns_mode" do
+      assert {:error, :invalid_configuration} = Specter.init(ice_multicast_dns_mode: :gather)
+    end
+
+    test "initializes with ice_network_types and ice_interfaces" do
+      assert {:ok, specter} = Specter.init(ice_network_types: [:udp4], ice_interfaces: ["lo"])
+
+      assert {:ok, %Specter.Config{ice_network_types: [:udp4], ice_interfaces: ["lo"]}} =
+               Specter.config(specter)
+    end
+
+    test "gathers host candidates on the given ice_network_types only" do
+      assert {:ok, specter} = Specter.init(ice_servers: [], ice_network_types: [:udp4])
+      pc = init_peer_connection(specter, init_api(specter))
+      :ok = create_data_channel(specter, pc)
+
+      {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+      assert :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+      assert_receive {:ok, ^pc, :set_local_description}
+
+      assert :ok = Specter.PeerConnection.gathered_local_description(specter, pc)
+      assert_receive {:local_description, ^pc, description}, 5_000
+      assert {:ok, %{"sdp" => sdp}} = Jason.decode(description)
+
+      addresses =
+        ~r/a=candidate:\S+ \d+ udp \d+ (\S+) \d+ typ host/
+        |> Regex.scan(sdp, capture: :all_but_first)
+        |> List.flatten()
+
+      assert addresses != []
+      refute Enum.any?(addresses, &String.contains?(&1, ":"))
+    end
+
+    test "returns {:error, :invalid_configuration} for empty or unknown ice_network_types" do
+      assert {:error, :invalid_configuration} = Specter.init(ice_network_types: [])
+      assert {:error, :invalid_configuration} = Specter.init(ice_network_types: [:udp])
+      assert {:error, :invalid_configuration} = Specter.init(ice_interfaces: [])
+    end
+
+    test "initializes with ICE timeouts" do
+      assert {:ok, specter} =
+               Specter.init(
+                 ice_disconnected_timeout: 1_000,
+                 ice_failed_timeout: 2_000,
+                 ice_keepalive_interval: 200
+               )
+
+      assert {:ok,
+              %Specter.Config{
+                ice_disconnected_timeout: 1_000,
+                ice_failed_timeout: 2_000,
+                ice_keepalive_interval: 200
+              }} = Specter.config(specter)
+    end
+
+    test "reports a quiet connection as disconnected after the ice_disconnected_timeout" do
+      assert {:ok, specter} =
+               Specter.init(
+                 ice_servers: [],
+                 ice_disconnected_timeout: 500,
+                 ice_keepalive_interval: 100
+               )
+
+      api = init_api(specter)
+      pc_offer = init_peer_connection(specter, api)
+      pc_answer = init_peer_connection(specter, api)
+      :ok = create_data_channel(specter, pc_offer)
+      :ok = negotiate_connection(specter, pc_offer, pc_answer)
+
+      assert :ok = Specter.PeerConnection.close_sync(specter, pc_answer)
+      assert_receive {:ice_connection_state_change, ^pc_offer, :disconnected}, 3_000
+    end
+
+    test "returns {:error, :invalid_configuration} for ICE timeouts that are not positive" do
+      assert {:error, :invalid_configuration} = Specter.init(ice_disconnected_timeout: 0)
+      assert {:error, :invalid_configuration} = Specter.init(ice_failed_timeout: -1)
+      assert {:error, :invalid_configuration} = Specter.init(ice_keepalive_interval: 1.5)
+    end
+
+    test "initializes with nat_1to1_ips" do
+      assert {:ok, specter} = Specter.init()
+      assert {:ok, %Specter.Config{nat_1to1_ips: nil}} = Specter.config(specter)
+
+      assert {:ok, specter} =
+               Specter.init(
+                 ice_servers: [],
+                 nat_1to1_ips: ["203.0.113.7"],
+                 nat_1to1_candidate_type: :srflx
+               )
+
+      assert {:ok,
+              %Specter.Config{nat_1to1_ips: ["203.0.113.7"], nat_1to1_candidate_type: :srflx}} =
+               Specter.config(specter)
+    end
+
+    test "advertises the nat_1to1_ips in place of host addresses" do
+      assert {:ok, specter} =
+               Specter.init(
+                 ice_servers: [],
+                 ice_network_types: [:udp4],
+                 nat_1to1_ips: ["203.0.113.7"]
+               )
+
+      assert ["203.0.113.7"] = specter |> candidate_addresses("host") |> Enum.uniq()
+    end
+
+    test "adds a server reflexive candidate for the nat_1to1_ips with :srflx" do
+      assert {:ok, specter} =
+               Specter.init(
+                 ice_servers: [],
+                 ice_network_types: [:udp4],
+                 nat_1to1_ips: ["203.0.113.7"],
+                 nat_1to1_candidate_type: :srflx
+               )
+
+      assert "203.0.113.7" in candidate_addresses(specter, "srflx")
+    end
+
+    test "returns {:error, :invalid_configuration} for invalid nat_1to1 options" do
+      assert {:error, :invalid_configuration} = Specter.init(nat_1to1_ips: [])
+      assert {:error, :invalid_configuration} = Specter.init(nat_1to1_ips: ["example.com"])
+
+      assert {:error, :invalid_configuration} =
+               Specter.init(ice_servers: [], nat_1to1_candidate_type: :relay)
+
+      assert {:error, :invalid_configuration} =
+               Specter.init(
+                 nat_1to1_ips: ["203.0.113.7"],
+                 ice_multicast_dns_mode: :query_and_gather
+               )
+
+      assert {:error, :invalid_configuration} =
+               Specter.init(
+                 ice_servers: ["stun:stun.example.com:3478"],
+                 nat_1to1_ips: ["203.0.113.7"],
+                 nat_1to1_candidate_type: :srflx
+               )
+    end
+
+    test "returns {:error, :invalid_configuration} for a command_buffer_size of zero" do
+      assert {:error, :invalid_configuration} = Specter.init(command_buffer_size: 0)
+    end
+
+    test "returns {:error, :invalid_configuration} for an ice server with an unknown scheme" do
+      assert {:error, :invalid_configuration} = Specter.init(ice_servers: ["stunn:example.com"])
+
+      assert {:error, :invalid_configuration} =
+               Specter.init(ice_servers: ["stun:stun.example.com", "example.com:3478"])
+    end
+
+    test "accepts stun, stuns, turn and turns ice servers" do
+      ice_servers = [
+        "stun:stun.example.com:3478",
+        "stuns:stun.example.com:5349",
+        "turn:turn.example.com:3478",
+        "turns:turn.example.com:5349"
+      ]
+
+      assert {:ok, specter} = Specter.init(ice_servers: ice_servers)
+      assert {:ok, %Specter.Config{ice_servers: ^ice_servers}} = Specter.config(specter)
+    end
+
+    test "returns {:error, :invalid_configuration} when ice_servers is not a list" do
+      assert {:error, :invalid_configuration} = Specter.init(ice_servers: "stun:example.com")
+    end
+
+    test "returns {:error, :invalid_configuration} for an unknown ice_candidate_format" do
+      assert {:error, :invalid_configuration} = Specter.init(ice_candidate_format: :xml)
+    end
   end
 
   describe "config" do
@@ -33,9 +321,18 @@ defmodule SpecterTest do
                 ice_servers: [
                   "stun:stun.example.com:3478",
                   "stun:stun.l.example.com:3478"
-                ]
+                ],
+                ice_candidate_format: :json,
+                certificate: nil,
+                command_buffer_size: 1000,
+                ice_candidate_pool_size: 0
               }} = Specter.config(ref)
     end
+
+    test "includes the ice_candidate_format" do
+      assert {:ok, ref} = Specter.init(ice_candidate_format: :struct)
+      assert {:ok, %Specter.Config{ice_candidate_format: :struct}} = Specter.config(ref)
+    end
   end
 
   describe "new_api" do
@@ -67,6 +364,32 @@ defmodule SpecterTest do
     end
   end
 
+  describe "new_api_with_defaults" do
+    setup :initialize_specter
+
+    test "returns a UUID without leaving a media engine or registry behind", %{
+      specter: specter
+    } do
+      assert {:ok, api} = Specter.new_api_with_defaults(specter)
+      assert String.match?(api, @uuid_regex)
+
+      assert {:ok, [^api]} = Specter.list_apis(specter)
+      assert {:ok, []} = Specter.list_media_engines(specter)
+      assert {:ok, []} = Specter.list_registries(specter)
+    end
+
+    test "creates APIs that can negotiate connections", %{specter: specter} do
+      {:ok, api_offer} = Specter.new_api_with_defaults(specter)
+      {:ok, api_answer} = Specter.new_api_with_defaults(specter)
+
+      pc_offer = init_peer_connection(specter, api_offer)
+      pc_answer = init_peer_connection(specter, api_answer)
+
+      assert :ok = create_data_channel(specter, pc_offer)
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+    end
+  end
+
   describe "new_media_engine" do
     setup :initialize_specter
 
@@ -75,6 +398,80 @@ defmodule SpecterTest do
       assert is_binary(media_engine)
       assert String.match?(media_engine, @uuid_regex)
     end
+
+    test "can be used by new_api without default codecs", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter, default_codecs: false)
+      assert {:ok, registry} = Specter.new_registry(specter, media_engine)
+      assert {:ok, api} = Specter.new_api(specter, media_engine, registry)
+
+      assert {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      assert_receive {:peer_connection_ready, ^pc}
+    end
+
+    test "only offers registered codecs without default codecs", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter, default_codecs: false)
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8", clock_rate: 90_000}
+      assert :ok = Specter.register_codec(specter, media_engine, codec, 96, :video)
+      assert {:ok, registry} = Specter.new_registry(specter, media_engine)
+      assert {:ok, api} = Specter.new_api(specter, media_engine, registry)
+
+      {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      assert_receive {:peer_connection_ready, ^pc}
+      assert :ok = Specter.PeerConnection.add_transceiver(specter, pc, :video, :recvonly)
+      assert_receive {:rtp_transceiver, ^pc, _transceiver}
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+
+      assert {:ok, %{"sdp" => sdp}} = Jason.decode(offer)
+      assert sdp =~ "a=rtpmap:96 VP8/90000"
+      refute sdp =~ "H264"
+    end
+
+    test "leaves out VP9 with vp9: false", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter, vp9: false)
+      assert {:ok, %{video: video}} = Specter.media_engine_codecs(specter, media_engine)
+
+      mime_types = Enum.map(video, & &1.mime_type)
+      assert "video/VP8" in mime_types
+      refute "video/VP9" in mime_types
+      assert "video/AV1" in mime_types
+    end
+
+    test "includes AV1 by default, as webrtc.rs does", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter)
+      assert {:ok, %{video: video}} = Specter.media_engine_codecs(specter, media_engine)
+
+      assert %{mime_type: "video/AV1", payload_type: 41, clock_rate: 90_000} =
+               Enum.find(video, &(&1.mime_type == "video/AV1"))
+
+      assert Enum.any?(video, &(&1.mime_type == "video/VP9"))
+    end
+
+    test "leaves out AV1 with av1: false", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter, av1: false)
+      assert {:ok, %{video: video}} = Specter.media_engine_codecs(specter, media_engine)
+
+      mime_types = Enum.map(video, & &1.mime_type)
+      assert "video/VP9" in mime_types
+      refute "video/AV1" in mime_types
+    end
+
+    test "offers AV1 and not VP9 when negotiating", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter, vp9: false)
+      assert {:ok, registry} = Specter.new_registry(specter, media_engine)
+      assert {:ok, api} = Specter.new_api(specter, media_engine, registry)
+
+      {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      assert_receive {:peer_connection_ready, ^pc}
+      assert :ok = Specter.PeerConnection.add_transceiver(specter, pc, :video, :recvonly)
+      assert_receive {:rtp_transceiver, ^pc, _transceiver}
+      assert :ok = Specter.PeerConnection.create_offer(specter, pc)
+      assert_receive {:offer, ^pc, offer}
+
+      assert {:ok, %{"sdp" => sdp}} = Jason.decode(offer)
+      assert sdp =~ "a=rtpmap:41 AV1/90000"
+      refute sdp =~ "VP9"
+    end
   end
 
   describe "new_registry" do
@@ -90,6 +487,253 @@ defmodule SpecterTest do
     test "returns {:error, :not_found} when given a random media engine id", %{specter: specter} do
       assert {:error, :not_found} = Specter.new_registry(specter, UUID.uuid4())
     end
+
+    test "returns a UUID when selecting interceptors", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter)
+
+      assert {:ok, registry} =
+               Specter.new_registry(specter, media_engine, nack: false, rtcp_reports: false)
+
+      assert String.match?(registry, @uuid_regex)
+      assert {:ok, _api} = Specter.new_api(specter, media_engine, registry)
+    end
+
+    test "builds a bare registry that can be used by new_api", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter)
+
+      assert {:ok, registry} =
+               Specter.new_registry(specter, media_engine, default_interceptors: false)
+
+      assert {:ok, api} = Specter.new_api(specter, media_engine, registry)
+
+      pc_offer = init_peer_connection(specter, api)
+      pc_answer = init_peer_connection(specter, init_api(specter))
+
+      assert :ok = Specter.PeerConnection.create_data_channel(specter, pc_offer, "foo")
+      assert_receive {:data_channel_created, ^pc_offer}
+      assert :ok = negotiate_connection(specter, pc_offer, pc_answer)
+    end
+
+    test "returns {:error, :invalid_registry_options} when an option is not a boolean", %{
+      specter: specter
+    } do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter)
+
+      assert {:error, :invalid_registry_options} =
+               Specter.new_registry(specter, media_engine, nack: "no")
+    end
+  end
+
+  describe "clone_media_engine" do
+    setup :initialize_specter
+
+    test "returns {:error, :not_found} when the media engine does not exist", %{
+      specter: specter
+    } do
+      assert {:error, :not_found} = Specter.clone_media_engine(specter, UUID.uuid4())
+    end
+
+    test "returns {:error, :not_found} when the media engine has been consumed", %{
+      specter: specter
+    } do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter)
+      assert {:ok, registry} = Specter.new_registry(specter, media_engine)
+      assert {:ok, _api} = Specter.new_api(specter, media_engine, registry)
+
+      assert {:error, :not_found} = Specter.clone_media_engine(specter, media_engine)
+    end
+
+    test "copies the codecs of the media engine", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter, default_codecs: false)
+      codec = %Specter.RtpCodecCapability{mime_type: "video/H264", clock_rate: 90_000}
+      assert :ok = Specter.register_codec(specter, media_engine, codec, 102, :video)
+
+      assert {:ok, copy} = Specter.clone_media_engine(specter, media_engine)
+      refute copy == media_engine
+      assert Specter.media_engine_exists?(specter, media_engine)
+
+      assert {:ok, %{audio: [], video: [%{mime_type: "video/H264", payload_type: 102}]}} =
+               Specter.media_engine_codecs(specter, copy)
+    end
+
+    test "seeds several APIs with one configuration", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter)
+      assert {:ok, copy} = Specter.clone_media_engine(specter, media_engine)
+
+      for engine <- [media_engine, copy] do
+        assert {:ok, registry} = Specter.new_registry(specter, engine)
+        assert {:ok, api} = Specter.new_api(specter, engine, registry)
+        assert {:ok, pc} = Specter.PeerConnection.new(specter, api)
+        assert_receive {:peer_connection_ready, ^pc}
+      end
+    end
+
+    test "copies are configured independently", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter, default_codecs: false)
+      assert {:ok, copy} = Specter.clone_media_engine(specter, media_engine)
+
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8", clock_rate: 90_000}
+      assert :ok = Specter.register_codec(specter, copy, codec, 96, :video)
+
+      assert {:ok, %{video: []}} = Specter.media_engine_codecs(specter, media_engine)
+      assert {:ok, %{video: [_vp8]}} = Specter.media_engine_codecs(specter, copy)
+    end
+  end
+
+  describe "destroy_api" do
+    setup [:initialize_specter, :init_api]
+
+    test "returns {:error, :not_found} when given a random id", %{specter: specter} do
+      assert {:error, :not_found} = Specter.destroy_api(specter, UUID.uuid4())
+    end
+
+    test "removes the api", %{specter: specter, api: api} do
+      assert :ok = Specter.destroy_api(specter, api)
+      assert {:ok, []} = Specter.list_apis(specter)
+      assert {:error, :not_found} = Specter.PeerConnection.new(specter, api)
+      assert {:error, :not_found} = Specter.destroy_api(specter, api)
+    end
+
+    test "refuses while a peer connection is open", %{specter: specter, api: api} do
+      pc = init_peer_connection(specter, api)
+
+      assert {:error, :in_use} = Specter.destroy_api(specter, api)
+      assert {:ok, [^api]} = Specter.list_apis(specter)
+
+      assert :ok = Specter.PeerConnection.close_sync(specter, pc)
+      assert :ok = Specter.destroy_api(specter, api)
+    end
+
+    test "refuses while a peer connection is being created", %{specter: specter, api: api} do
+      {:ok, pc} = Specter.PeerConnection.new(specter, api)
+
+      assert {:error, :in_use} = Specter.destroy_api(specter, api)
+      assert_receive {:peer_connection_ready, ^pc}
+    end
+
+    test "forgets peer connections that could not be created" do
+      {:ok, specter} = Specter.init(ice_servers: ["turn:turn.example.com:3478"])
+      api = init_api(specter)
+
+      {:ok, pc} = Specter.PeerConnection.new(specter, api)
+      assert_receive {:peer_connection_error, ^pc, _reason}
+
+      assert :ok = Specter.destroy_api(specter, api)
+    end
+  end
+
+  describe "list_apis" do
+    setup :initialize_specter
+
+    test "is empty when no api has been created", %{specter: specter} do
+      assert {:ok, []} = Specter.list_apis(specter)
+    end
+
+    test "returns every api", %{specter: specter} do
+      api_1 = init_api(specter)
+      api_2 = init_api(specter)
+
+      assert {:ok, apis} = Specter.list_apis(specter)
+      assert apis == Enum.sort([api_1, api_2])
+    end
+  end
+
+  describe "list_media_engines" do
+    setup :initialize_specter
+
+    test "returns media engines until they are consumed", %{specter: specter} do
+      assert {:ok, []} = Specter.list_media_engines(specter)
+
+      {:ok, media_engine_1} = Specter.new_media_engine(specter)
+      {:ok, media_engine_2} = Specter.new_media_engine(specter)
+      assert {:ok, media_engines} = Specter.list_media_engines(specter)
+      assert media_engines == Enum.sort([media_engine_1, media_engine_2])
+
+      {:ok, registry} = Specter.new_registry(specter, media_engine_1)
+      {:ok, _api} = Specter.new_api(specter, media_engine_1, registry)
+      assert {:ok, [^media_engine_2]} = Specter.list_media_engines(specter)
+    end
+  end
+
+  describe "list_registries" do
+    setup :initialize_specter
+
+    test "returns registries until they are consumed", %{specter: specter} do
+      assert {:ok, []} = Specter.list_registries(specter)
+
+      {:ok, media_engine} = Specter.new_media_engine(specter)
+      {:ok, registry} = Specter.new_registry(specter, media_engine)
+      assert {:ok, [^registry]} = Specter.list_registries(specter)
+
+      {:ok, _api} = Specter.new_api(specter, media_engine, registry)
+      assert {:ok, []} = Specter.list_registries(specter)
+    end
+  end
+
+  describe "list_tracks" do
+    setup :initialize_specter
+
+    test "is empty when no track has been created", %{specter: specter} do
+      assert {:ok, []} = Specter.list_tracks(specter)
+    end
+
+    test "returns tracks of every type", %{specter: specter} do
+      codec = %Specter.RtpCodecCapability{mime_type: "video/VP8"}
+      {:ok, sample} = Specter.TrackLocalStaticSample.new(specter, codec, "video", "specter")
+      {:ok, rtp} = Specter.TrackLocalStaticRTP.new(specter, codec, "video", "specter")
+
+      assert {:ok, tracks} = Specter.list_tracks(specter)
+      assert tracks == Enum.sort([sample, rtp])
+    end
+  end
+
+  describe "media_engine_codecs" do
+    setup :initialize_specter
+
+    test "returns {:error, :not_found} when the media engine does not exist", %{
+      specter: specter
+    } do
+      assert {:error, :not_found} = Specter.media_engine_codecs(specter, UUID.uuid4())
+    end
+
+    test "returns {:error, :not_found} when the media engine has been consumed", %{
+      specter: specter
+    } do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter)
+      assert {:ok, registry} = Specter.new_registry(specter, media_engine)
+      assert {:ok, _api} = Specter.new_api(specter, media_engine, registry)
+
+      assert {:error, :not_found} = Specter.media_engine_codecs(specter, media_engine)
+    end
+
+    test "lists the default codecs", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter)
+
+      assert {:ok, %{audio: audio, video: video}} =
+               Specter.media_engine_codecs(specter, media_engine)
+
+      opus = %{mime_type: "audio/opus", payload_type: 111, clock_rate: 48_000, channels: 2}
+      vp8 = %{mime_type: "video/VP8", payload_type: 96, clock_rate: 90_000, channels: 0}
+      assert opus in audio
+      assert vp8 in video
+    end
+
+    test "is empty without default codecs", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter, default_codecs: false)
+      assert {:ok, %{audio: [], video: []}} = Specter.media_engine_codecs(specter, media_engine)
+    end
+
+    test "includes registered codecs", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter, default_codecs: false)
+      codec = %Specter.RtpCodecCapability{mime_type: "video/H264", clock_rate: 90_000}
+      assert :ok = Specter.register_codec(specter, media_engine, codec, 102, :video)
+      assert :ok = Specter.register_codec(specter, media_engine, codec, 102, :video)
+
+      assert {:ok, %{audio: [], video: [h264]}} =
+               Specter.media_engine_codecs(specter, media_engine)
+
+      assert %{mime_type: "video/H264", payload_type: 102, clock_rate: 90_000, channels: 0} = h264
+    end
   end
 
   describe "media_engine_exists?" do
@@ -105,6 +749,74 @@ defmodule SpecterTest do
     end
   end
 
+  describe "register_codec" do
+    setup :initialize_specter
+
+    setup do
+      codec = %Specter.RtpCodecCapability{
+        mime_type: "audio/opus",
+        clock_rate: 48_000,
+        channels: 2
+      }
+
+      [codec: codec]
+    end
+
+    test "returns {:error, :not_found} when the media engine has been consumed", %{
+      specter: specter,
+      codec: codec
+    } do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter)
+      assert {:ok, registry} = Specter.new_registry(specter, media_engine)
+      assert {:ok, _api} = Specter.new_api(specter, media_engine, registry)
+
+      assert {:error, :not_found} =
+               Specter.register_codec(specter, media_engine, codec, 111, :audio)
+    end
+
+    test "returns {:error, :invalid_atom} when given an unknown kind", %{
+      specter: specter,
+      codec: codec
+    } do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter)
+      assert {:error, :invalid_atom} =
+               Specter.register_codec(specter, media_engine, codec, 111, :text)
+    end
+
+    test "returns {:error, :invalid_payload_type} when the payload type is out of range", %{
+      specter: specter,
+      codec: codec
+    } do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter)
+
+      assert {:error, :invalid_payload_type} =
+               Specter.register_codec(specter, media_engine, codec, 300, :audio)
+    end
+
+    test "returns {:error, :unknown_mime_type} for a mime type webrtc.rs does not know", %{
+      specter: specter,
+      codec: codec
+    } do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter, default_codecs: false)
+      codec = %{codec | mime_type: "audio/opus2"}
+
+      assert {:error, :unknown_mime_type} =
+               Specter.register_codec(specter, media_engine, codec, 111, :audio)
+
+      assert {:ok, %{audio: [], video: []}} = Specter.media_engine_codecs(specter, media_engine)
+    end
+
+    test "registers the mime type in the case webrtc.rs expects", %{specter: specter} do
+      assert {:ok, media_engine} = Specter.new_media_engine(specter, default_codecs: false)
+      codec = %{Specter.RtpCodecCapability.vp8() | mime_type: "VIDEO/vp8"}
+
+      assert :ok = Specter.register_codec(specter, media_engine, codec, 96, :video)
+
+      assert {:ok, %{video: [%{mime_type: "video/VP8", payload_type: 96}]}} =
+               Specter.media_engine_codecs(specter, media_engine)
+    end
+  end
+
   describe "registry_exists?" do
     setup :initialize_specter
 
@@ -118,4 +830,23 @@ defmodule SpecterTest do
       assert Specter.registry_exists?(specter, registry)
     end
   end
+
+  # Gathers the local candidates of a new peer connection, and returns the addresses of
+  # those of the given type.
+  defp candidate_addresses(specter, type) do
+    pc = init_peer_connection(specter, init_api(specter))
+    :ok = create_data_channel(specter, pc)
+
+    {:ok, offer} = Specter.PeerConnection.create_offer_sync(specter, pc)
+    assert :ok = Specter.PeerConnection.set_local_description(specter, pc, offer)
+    assert_receive {:ok, ^pc, :set_local_description}
+
+    assert :ok = Specter.PeerConnection.gathered_local_description(specter, pc)
+    assert_receive {:local_description, ^pc, description}, 5_000
+    assert {:ok, %{"sdp" => sdp}} = Jason.decode(description)
+
+    ~r/a=candidate:\S+ \d+ udp \d+ (\S+) \d+ typ #{type}/
+    |> Regex.scan(sdp, capture: :all_but_first)
+    |> List.flatten()
+  end
 end